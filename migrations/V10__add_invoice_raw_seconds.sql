@@ -0,0 +1,2 @@
+ALTER TABLE invoice_lines ADD COLUMN raw_seconds INTEGER;
+UPDATE invoice_lines SET raw_seconds = seconds;
@@ -0,0 +1,7 @@
+CREATE TABLE imports (
+	source TEXT NOT NULL,
+	external_id TEXT NOT NULL,
+	log_id INTEGER NOT NULL,
+	PRIMARY KEY (source, external_id),
+	CONSTRAINT imports_logs_FK FOREIGN KEY (log_id) REFERENCES logs(id)
+);
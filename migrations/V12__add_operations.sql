@@ -0,0 +1,14 @@
+CREATE TABLE operations (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	command TEXT NOT NULL,
+	created INTEGER NOT NULL,
+	state TEXT NOT NULL
+);
+CREATE TABLE operation_changes (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	operation_id INTEGER NOT NULL,
+	table_name TEXT NOT NULL,
+	old_row TEXT,
+	new_row TEXT,
+	CONSTRAINT operation_changes_operations_FK FOREIGN KEY (operation_id) REFERENCES operations(id)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE log_revisions (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	log_id INTEGER NOT NULL,
+	changed INTEGER NOT NULL,
+	changed_by TEXT NOT NULL,
+	message TEXT NOT NULL,
+	start INTEGER NOT NULL,
+	end INTEGER NOT NULL,
+	CONSTRAINT log_revisions_logs_FK FOREIGN KEY (log_id) REFERENCES logs(id)
+);
@@ -0,0 +1,30 @@
+-- log times and project durations were whole seconds, they are now milliseconds
+UPDATE logs SET start = start * 1000, end = end * 1000;
+UPDATE log_revisions SET start = start * 1000, end = end * 1000;
+UPDATE projects SET duration = duration * 1000;
+
+-- rows recorded for undo are restored as they were, so they are converted too
+UPDATE operation_changes
+SET old_row = json_set(old_row, '$.start', json_extract(old_row, '$.start') * 1000, '$.end', json_extract(old_row, '$.end') * 1000)
+WHERE table_name IN ('logs', 'log_revisions') AND old_row IS NOT NULL;
+UPDATE operation_changes
+SET new_row = json_set(new_row, '$.start', json_extract(new_row, '$.start') * 1000, '$.end', json_extract(new_row, '$.end') * 1000)
+WHERE table_name IN ('logs', 'log_revisions') AND new_row IS NOT NULL;
+UPDATE operation_changes
+SET old_row = json_set(old_row, '$.duration', json_extract(old_row, '$.duration') * 1000)
+WHERE table_name = 'projects' AND old_row IS NOT NULL;
+UPDATE operation_changes
+SET new_row = json_set(new_row, '$.duration', json_extract(new_row, '$.duration') * 1000)
+WHERE table_name = 'projects' AND new_row IS NOT NULL;
+
+-- logs that already end before they start are kept as they are, but no more can be saved
+CREATE TRIGGER logs_insert_end_after_start BEFORE INSERT ON logs
+WHEN NEW.end < NEW.start
+BEGIN
+	SELECT RAISE(ABORT, 'log ends before it starts');
+END;
+CREATE TRIGGER logs_update_end_after_start BEFORE UPDATE OF start, end ON logs
+WHEN NEW.end < NEW.start
+BEGIN
+	SELECT RAISE(ABORT, 'log ends before it starts');
+END;
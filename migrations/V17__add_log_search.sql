@@ -0,0 +1,17 @@
+-- messages of logs indexed by every three characters, so any part of them can be searched for
+CREATE VIRTUAL TABLE logs_search USING fts5(message, content = 'logs', content_rowid = 'id', tokenize = 'trigram');
+INSERT INTO logs_search (logs_search) VALUES ('rebuild');
+
+CREATE TRIGGER logs_search_insert AFTER INSERT ON logs
+BEGIN
+	INSERT INTO logs_search (rowid, message) VALUES (NEW.id, NEW.message);
+END;
+CREATE TRIGGER logs_search_update AFTER UPDATE OF message ON logs
+BEGIN
+	INSERT INTO logs_search (logs_search, rowid, message) VALUES ('delete', OLD.id, OLD.message);
+	INSERT INTO logs_search (rowid, message) VALUES (NEW.id, NEW.message);
+END;
+CREATE TRIGGER logs_search_delete AFTER DELETE ON logs
+BEGIN
+	INSERT INTO logs_search (logs_search, rowid, message) VALUES ('delete', OLD.id, OLD.message);
+END;
@@ -0,0 +1,5 @@
+CREATE TABLE project_aliases (
+	alias TEXT NOT NULL PRIMARY KEY,
+	project_id INTEGER NOT NULL,
+	CONSTRAINT project_aliases_projects_FK FOREIGN KEY (project_id) REFERENCES projects(id)
+);
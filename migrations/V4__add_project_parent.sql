@@ -0,0 +1 @@
+ALTER TABLE projects ADD COLUMN parent_id INTEGER REFERENCES projects(id);
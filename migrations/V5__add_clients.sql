@@ -0,0 +1,9 @@
+CREATE TABLE clients (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	name TEXT NOT NULL UNIQUE,
+	contact TEXT,
+	currency TEXT NOT NULL,
+	hourly_rate TEXT,
+	address TEXT
+);
+ALTER TABLE projects ADD COLUMN client_id INTEGER REFERENCES clients(id);
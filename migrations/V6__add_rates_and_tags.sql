@@ -0,0 +1,24 @@
+CREATE TABLE tags (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE log_tags (
+	log_id INTEGER NOT NULL,
+	tag_id INTEGER NOT NULL,
+	PRIMARY KEY (log_id, tag_id),
+	CONSTRAINT log_tags_logs_FK FOREIGN KEY (log_id) REFERENCES logs(id),
+	CONSTRAINT log_tags_tags_FK FOREIGN KEY (tag_id) REFERENCES tags(id)
+);
+ALTER TABLE logs ADD COLUMN billable INTEGER NOT NULL DEFAULT 1;
+CREATE TABLE rates (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	client_id INTEGER,
+	project_id INTEGER,
+	tag_id INTEGER,
+	amount TEXT NOT NULL,
+	effective_from INTEGER NOT NULL,
+	CONSTRAINT rates_clients_FK FOREIGN KEY (client_id) REFERENCES clients(id),
+	CONSTRAINT rates_projects_FK FOREIGN KEY (project_id) REFERENCES projects(id),
+	CONSTRAINT rates_tags_FK FOREIGN KEY (tag_id) REFERENCES tags(id),
+	CONSTRAINT rates_single_scope CHECK ((client_id IS NOT NULL) + (project_id IS NOT NULL) + (tag_id IS NOT NULL) = 1)
+);
@@ -0,0 +1,21 @@
+CREATE TABLE invoices (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	number TEXT NOT NULL UNIQUE,
+	client_id INTEGER NOT NULL,
+	period_start INTEGER NOT NULL,
+	period_end INTEGER NOT NULL,
+	created INTEGER NOT NULL,
+	currency TEXT NOT NULL,
+	total TEXT NOT NULL,
+	CONSTRAINT invoices_clients_FK FOREIGN KEY (client_id) REFERENCES clients(id)
+);
+CREATE TABLE invoice_lines (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	invoice_id INTEGER NOT NULL,
+	description TEXT NOT NULL,
+	seconds INTEGER NOT NULL,
+	rate TEXT NOT NULL,
+	amount TEXT NOT NULL,
+	CONSTRAINT invoice_lines_invoices_FK FOREIGN KEY (invoice_id) REFERENCES invoices(id)
+);
+ALTER TABLE logs ADD COLUMN invoice_id INTEGER REFERENCES invoices(id);
@@ -0,0 +1,2 @@
+ALTER TABLE projects ADD COLUMN budget_seconds INTEGER;
+ALTER TABLE projects ADD COLUMN budget_period TEXT CHECK (budget_period IN ('week', 'month'));
@@ -0,0 +1,10 @@
+CREATE TABLE goals (
+	id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+	project_id INTEGER,
+	tag_id INTEGER,
+	seconds INTEGER NOT NULL,
+	period TEXT NOT NULL CHECK (period IN ('day', 'weekday', 'week')),
+	CONSTRAINT goals_projects_FK FOREIGN KEY (project_id) REFERENCES projects(id),
+	CONSTRAINT goals_tags_FK FOREIGN KEY (tag_id) REFERENCES tags(id),
+	CONSTRAINT goals_single_scope CHECK ((project_id IS NOT NULL) + (tag_id IS NOT NULL) <= 1)
+);
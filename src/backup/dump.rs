@@ -0,0 +1,708 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    model::{
+        Client, Goal, GoalScope, Invoice, InvoiceLine, Log, LogRevision, Project, Rate, RateScope,
+    },
+    repository::Repository,
+};
+
+/// Version of the backup format. Bump it whenever a table is added or a field changes meaning,
+/// and upgrade older backups in `parse`.
+pub const VERSION: u32 = 3;
+
+/// Everything in the database, written independently of its schema so a backup can be restored
+/// after migrations have changed the tables.
+#[derive(Deserialize, Serialize)]
+pub struct Dump {
+    /// Version of the backup format the dump was written in
+    pub version: u32,
+    pub created: DateTime<Local>,
+    #[serde(default)]
+    pub clients: Vec<Client>,
+    /// Durations are in milliseconds from version 3, and in seconds before
+    #[serde(default)]
+    pub projects: Vec<Project>,
+    #[serde(default)]
+    pub aliases: Vec<Alias>,
+    #[serde(default)]
+    pub logs: Vec<Log>,
+    #[serde(default)]
+    pub imports: Vec<Import>,
+    /// Added in version 2
+    #[serde(default)]
+    pub revisions: Vec<LogRevision>,
+    #[serde(default)]
+    pub rates: Vec<Rate>,
+    #[serde(default)]
+    pub goals: Vec<Goal>,
+    #[serde(default)]
+    pub invoices: Vec<Invoice>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Alias {
+    pub alias: String,
+    pub project_id: u32,
+}
+
+/// Where an imported log came from, so importing the same entries again still skips them
+#[derive(Deserialize, Serialize)]
+pub struct Import {
+    pub source: String,
+    pub external_id: String,
+    pub log_id: u32,
+}
+
+/// How a backup is restored into a database that already holds data
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum Mode {
+    /// Keep what is there and add what is missing, giving new IDs to records whose IDs are taken
+    Merge,
+    /// Delete everything first, so the database matches the backup exactly
+    Replace,
+}
+
+/// What restoring a backup changed
+#[derive(Debug, Default)]
+pub struct Restored {
+    /// Records added to the database
+    pub added: usize,
+    /// Records left out because the database already had them
+    pub skipped: usize,
+    /// Projects that were already in the database and gained logs, so their durations are stale
+    pub projects: BTreeSet<u32>,
+}
+
+/// Reads everything in the database.
+///
+/// # Arguments
+///
+/// * `repo` - Repository to back up
+/// * `created` - When the backup was taken
+pub fn take(repo: &impl Repository, created: DateTime<Local>) -> rusqlite::Result<Dump> {
+    let mut projects = repo.all_projects()?;
+    projects.sort_by_key(|p| p.id);
+
+    Ok(Dump {
+        version: VERSION,
+        created,
+        clients: repo.all_clients()?,
+        projects,
+        aliases: repo
+            .all_aliases()?
+            .into_iter()
+            .map(|(alias, project_id)| Alias { alias, project_id })
+            .collect(),
+        logs: repo.all_logs()?,
+        imports: repo
+            .all_imports()?
+            .into_iter()
+            .map(|(source, external_id, log_id)| Import {
+                source,
+                external_id,
+                log_id,
+            })
+            .collect(),
+        revisions: repo.all_log_revisions()?,
+        rates: repo.all_rates()?,
+        goals: repo.all_goals()?,
+        invoices: repo.all_invoices()?,
+    })
+}
+
+/// Reads a backup, refusing ones written by a newer version of arrow.
+pub fn parse(text: &str) -> Result<Dump, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("Not an arrow backup: {}", e))?;
+    let version = value
+        .get("version")
+        .and_then(|v| v.as_u64())
+        .ok_or("Not an arrow backup, it has no version")?;
+    if version > VERSION as u64 {
+        return Err(format!(
+            "The backup is in format {}, but this version of arrow only reads up to format {}. Please upgrade arrow.",
+            version, VERSION
+        ));
+    }
+
+    if version < 3 {
+        upgrade_durations(&mut value);
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Invalid backup: {}", e))
+}
+
+/// Turns the project durations of a backup written before version 3 from seconds into
+/// milliseconds.
+fn upgrade_durations(value: &mut serde_json::Value) {
+    let projects = value.get_mut("projects").and_then(|p| p.as_array_mut());
+    for project in projects.into_iter().flatten() {
+        if let Some(duration) = project.get_mut("duration") {
+            if let Some(seconds) = duration.as_i64() {
+                *duration = (seconds * 1000).into();
+            }
+        }
+    }
+}
+
+/// Checks every ID a record refers to is in the backup, before anything is restored.
+fn check(dump: &Dump) -> Result<(), String> {
+    let clients: HashSet<u32> = dump.clients.iter().map(|c| c.id).collect();
+    let projects: HashSet<u32> = dump.projects.iter().map(|p| p.id).collect();
+    let logs: HashSet<u32> = dump.logs.iter().map(|l| l.id).collect();
+    let invoices: HashSet<u32> = dump.invoices.iter().map(|i| i.id).collect();
+
+    let missing = |what: &str, id: u32, kind: &str, target: u32| {
+        Err(format!(
+            "Invalid backup: {} {} refers to missing {} {}",
+            what, id, kind, target
+        ))
+    };
+
+    for project in &dump.projects {
+        if let Some(parent_id) = project.parent_id.filter(|id| !projects.contains(id)) {
+            return missing("project", project.id, "project", parent_id);
+        }
+        if let Some(client_id) = project.client_id.filter(|id| !clients.contains(id)) {
+            return missing("project", project.id, "client", client_id);
+        }
+    }
+    if let Some(alias) = dump
+        .aliases
+        .iter()
+        .find(|a| !projects.contains(&a.project_id))
+    {
+        return Err(format!(
+            "Invalid backup: alias {} refers to missing project {}",
+            alias.alias, alias.project_id
+        ));
+    }
+    for log in &dump.logs {
+        if !projects.contains(&log.proj_id) {
+            return missing("log", log.id, "project", log.proj_id);
+        }
+        if let Some(invoice_id) = log.invoice_id.filter(|id| !invoices.contains(id)) {
+            return missing("log", log.id, "invoice", invoice_id);
+        }
+    }
+    if let Some(import) = dump.imports.iter().find(|i| !logs.contains(&i.log_id)) {
+        return Err(format!(
+            "Invalid backup: import {} refers to missing log {}",
+            import.external_id, import.log_id
+        ));
+    }
+    if let Some(revision) = dump.revisions.iter().find(|r| !logs.contains(&r.log_id)) {
+        return missing("revision", revision.id, "log", revision.log_id);
+    }
+    for rate in &dump.rates {
+        match rate.scope {
+            RateScope::Client(id) if !clients.contains(&id) => {
+                return missing("rate", rate.id, "client", id)
+            }
+            RateScope::Project(id) if !projects.contains(&id) => {
+                return missing("rate", rate.id, "project", id)
+            }
+            _ => {}
+        }
+    }
+    for goal in &dump.goals {
+        if let GoalScope::Project(id) = goal.scope {
+            if !projects.contains(&id) {
+                return missing("goal", goal.id, "project", id);
+            }
+        }
+    }
+    for invoice in &dump.invoices {
+        if !clients.contains(&invoice.client_id) {
+            return missing("invoice", invoice.id, "client", invoice.client_id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Restores a backup. Records keep their IDs when their table is empty, otherwise records the
+/// database already has are skipped and the rest are given new IDs, with every reference to
+/// them remapped. The backup is checked before anything is changed.
+///
+/// # Arguments
+///
+/// * `repo` - Repository to restore into
+/// * `dump` - The backup
+/// * `mode` - Whether to merge into the existing data or replace it
+pub fn restore(repo: &impl Repository, dump: &Dump, mode: Mode) -> Result<Restored, String> {
+    check(dump)?;
+
+    let err = |e: rusqlite::Error| format!("Failed to restore backup: {}", e);
+    if mode == Mode::Replace {
+        repo.clear().map_err(err)?;
+    }
+
+    let mut restored = Restored::default();
+    let id = |keep: bool, id: u32| if keep { id } else { 0 };
+
+    // clients, matched by name
+    let existing = repo.all_clients().map_err(err)?;
+    let keep = existing.is_empty();
+    let mut clients = HashMap::new();
+    for record in &dump.clients {
+        let new_id = match existing.iter().find(|c| c.name == record.name) {
+            Some(c) => {
+                restored.skipped += 1;
+                c.id
+            }
+            None => {
+                let mut client = Client::new(
+                    record.name.clone(),
+                    record.contact.clone(),
+                    record.currency.clone(),
+                    record.hourly_rate,
+                    record.address.clone(),
+                );
+                client.id = id(keep, record.id);
+                restored.added += 1;
+                repo.save_client(&client).map_err(err)?
+            }
+        };
+        clients.insert(record.id, new_id);
+    }
+
+    // projects, parents first, matched by name and parent
+    let existing = repo.all_projects().map_err(err)?;
+    let keep = existing.is_empty();
+    let had_projects: HashSet<u32> = existing.iter().map(|p| p.id).collect();
+    let mut projects = HashMap::new();
+    let mut pending: Vec<&Project> = dump.projects.iter().collect();
+    while !pending.is_empty() {
+        let (ready, rest): (Vec<&Project>, Vec<&Project>) = pending
+            .into_iter()
+            .partition(|p| p.parent_id.is_none_or(|id| projects.contains_key(&id)));
+        if ready.is_empty() {
+            return Err("Invalid backup: projects are nested in a loop".to_owned());
+        }
+
+        for record in ready {
+            let parent_id = record.parent_id.map(|id| projects[&id]);
+            let new_id = match existing
+                .iter()
+                .find(|p| p.name == record.name && p.parent_id == parent_id)
+            {
+                Some(p) => {
+                    restored.skipped += 1;
+                    p.id
+                }
+                None => {
+                    let mut project = Project::load(
+                        id(keep, record.id),
+                        record.name.clone(),
+                        record.description.clone(),
+                        record.created,
+                        record.updated,
+                        record.duration,
+                    );
+                    project.parent_id = parent_id;
+                    project.client_id = record.client_id.map(|id| clients[&id]);
+                    project.budget = record.budget;
+                    restored.added += 1;
+                    repo.save_project(&project).map_err(err)?
+                }
+            };
+            projects.insert(record.id, new_id);
+        }
+        pending = rest;
+    }
+
+    // aliases, matched by alias
+    let existing = repo.all_aliases().map_err(err)?;
+    for record in &dump.aliases {
+        if existing.iter().any(|(alias, _)| *alias == record.alias) {
+            restored.skipped += 1;
+            continue;
+        }
+        repo.save_alias(&projects[&record.project_id], &record.alias)
+            .map_err(err)?;
+        restored.added += 1;
+    }
+
+    // logs, matched by project, times and message
+    let existing = repo.all_logs().map_err(err)?;
+    let keep = existing.is_empty();
+    let imports: HashMap<u32, &Import> = dump.imports.iter().map(|i| (i.log_id, i)).collect();
+    let mut invoiced: HashSet<u32> = existing
+        .iter()
+        .filter(|l| l.invoice_id.is_some())
+        .map(|l| l.id)
+        .collect();
+    let mut logs = HashMap::new();
+    for record in &dump.logs {
+        let proj_id = projects[&record.proj_id];
+        let found = existing.iter().find(|l| {
+            l.proj_id == proj_id
+                && l.start == record.start
+                && l.end == record.end
+                && l.message == record.message
+        });
+        if let Some(l) = found {
+            restored.skipped += 1;
+            logs.insert(record.id, l.id);
+            continue;
+        }
+
+        let mut log = Log::new(
+            id(keep, record.id),
+            proj_id,
+            record.message.clone(),
+            record.start,
+            record.end,
+        );
+        log.billable = record.billable;
+        log.tags = record.tags.clone();
+        log.zone = record.zone;
+
+        let new_id = match imports.get(&record.id) {
+            Some(import) => repo
+                .import_log(&import.source, &import.external_id, &log)
+                .map_err(err)?,
+            None => Some(repo.save_log(&proj_id, &log).map_err(err)?),
+        };
+        match new_id {
+            Some(new_id) => {
+                restored.added += 1;
+                logs.insert(record.id, new_id);
+                if had_projects.contains(&proj_id) {
+                    restored.projects.insert(proj_id);
+                }
+            }
+            None => restored.skipped += 1,
+        }
+    }
+
+    // revisions, matched by log and when the change was made
+    let existing = repo.all_log_revisions().map_err(err)?;
+    let keep = existing.is_empty();
+    for record in &dump.revisions {
+        let Some(&log_id) = logs.get(&record.log_id) else {
+            // the log was imported into the database before
+            restored.skipped += 1;
+            continue;
+        };
+        if existing
+            .iter()
+            .any(|r| r.log_id == log_id && r.changed == record.changed)
+        {
+            restored.skipped += 1;
+            continue;
+        }
+
+        repo.save_log_revision(&LogRevision {
+            id: id(keep, record.id),
+            log_id,
+            changed: record.changed,
+            changed_by: record.changed_by.clone(),
+            message: record.message.clone(),
+            start: record.start,
+            end: record.end,
+        })
+        .map_err(err)?;
+        restored.added += 1;
+    }
+
+    // invoices, matched by number
+    let existing = repo.all_invoices().map_err(err)?;
+    let keep = existing.is_empty();
+    for record in &dump.invoices {
+        if existing.iter().any(|i| i.number == record.number) {
+            restored.skipped += 1;
+            continue;
+        }
+
+        let log_ids: Vec<u32> = dump
+            .logs
+            .iter()
+            .filter(|l| l.invoice_id == Some(record.id))
+            .filter_map(|l| logs.get(&l.id).copied())
+            .filter(|id| invoiced.insert(*id))
+            .collect();
+        let invoice = Invoice {
+            id: id(keep, record.id),
+            number: record.number.clone(),
+            client_id: clients[&record.client_id],
+            period_start: record.period_start,
+            period_end: record.period_end,
+            created: record.created,
+            currency: record.currency.clone(),
+            lines: record
+                .lines
+                .iter()
+                .map(|l| InvoiceLine {
+                    description: l.description.clone(),
+                    raw_seconds: l.raw_seconds,
+                    seconds: l.seconds,
+                    rate: l.rate,
+                    amount: l.amount,
+                })
+                .collect(),
+            total: record.total,
+        };
+        repo.save_invoice(&invoice, &log_ids).map_err(err)?;
+        restored.added += 1;
+    }
+
+    // rates, matched by scope and when they take effect
+    let existing = repo.all_rates().map_err(err)?;
+    let keep = existing.is_empty();
+    for record in &dump.rates {
+        let scope = match &record.scope {
+            RateScope::Client(id) => RateScope::Client(clients[id]),
+            RateScope::Project(id) => RateScope::Project(projects[id]),
+            RateScope::Tag(name) => RateScope::Tag(name.clone()),
+        };
+        if existing
+            .iter()
+            .any(|r| r.scope == scope && r.effective_from == record.effective_from)
+        {
+            restored.skipped += 1;
+            continue;
+        }
+
+        let mut rate = Rate::new(scope, record.amount, record.effective_from);
+        rate.id = id(keep, record.id);
+        repo.save_rate(&rate).map_err(err)?;
+        restored.added += 1;
+    }
+
+    // goals, matched by scope and period
+    let existing = repo.all_goals().map_err(err)?;
+    let keep = existing.is_empty();
+    for record in &dump.goals {
+        let scope = match &record.scope {
+            GoalScope::Project(id) => GoalScope::Project(projects[id]),
+            scope => scope.clone(),
+        };
+        if existing
+            .iter()
+            .any(|g| g.scope == scope && g.period == record.period)
+        {
+            restored.skipped += 1;
+            continue;
+        }
+
+        let mut goal = Goal::new(scope, record.seconds, record.period);
+        goal.id = id(keep, record.id);
+        repo.save_goal(&goal).map_err(err)?;
+        restored.added += 1;
+    }
+
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use rust_decimal::Decimal;
+
+    use crate::{
+        model::{Budget, BudgetPeriod, GoalPeriod},
+        repository::Sqlite,
+        time::parse_datetime,
+    };
+
+    use super::*;
+
+    /// A database with a record in every table
+    fn filled_repo() -> Sqlite {
+        let repo = Sqlite::in_memory().unwrap();
+        let at = |time: &str| parse_datetime(time).unwrap();
+
+        let client_id = repo
+            .save_client(&Client::new(
+                "acme".to_owned(),
+                None,
+                "AUD".to_owned(),
+                Some(Decimal::new(12050, 2)),
+                None,
+            ))
+            .unwrap();
+        let mut parent = Project::new(
+            "web".to_owned(),
+            None,
+            at("2026-01-01 09:00"),
+            at("2026-02-01 09:00"),
+        );
+        parent.client_id = Some(client_id);
+        parent.budget = Some(Budget {
+            seconds: 3600,
+            period: Some(BudgetPeriod::Month),
+        });
+        let parent_id = repo.save_project(&parent).unwrap();
+        let mut child = Project::new(
+            "api".to_owned(),
+            Some("backend".to_owned()),
+            at("2026-01-02 09:00"),
+            at("2026-01-02 09:00"),
+        );
+        child.parent_id = Some(parent_id);
+        let child_id = repo.save_project(&child).unwrap();
+        repo.save_alias(&child_id, "a").unwrap();
+
+        let mut log = Log::new(
+            0,
+            child_id,
+            "Fix login".to_owned(),
+            at("2026-01-05 09:00"),
+            at("2026-01-05 10:00"),
+        );
+        log.tags = vec!["bug".to_owned()];
+        log.zone = Some("Europe/Berlin".parse().unwrap());
+        let log_id = repo.save_log(&child_id, &log).unwrap();
+        log.id = log_id;
+        log.message = "Fix login bug".to_owned();
+        repo.update_log(&log).unwrap();
+        let mut imported = Log::new(
+            0,
+            parent_id,
+            "Standup".to_owned(),
+            at("2026-01-06 09:00"),
+            at("2026-01-06 09:15"),
+        );
+        imported.billable = false;
+        repo.import_log("ics", "standup", &imported).unwrap();
+
+        repo.save_invoice(
+            &Invoice {
+                id: 0,
+                number: "INV-0001".to_owned(),
+                client_id,
+                period_start: at("2026-01-01 00:00"),
+                period_end: at("2026-02-01 00:00"),
+                created: at("2026-02-01 09:00"),
+                currency: "AUD".to_owned(),
+                lines: vec![InvoiceLine {
+                    description: "web/api".to_owned(),
+                    raw_seconds: 3600,
+                    seconds: 3600,
+                    rate: Decimal::new(12050, 2),
+                    amount: Decimal::new(12050, 2),
+                }],
+                total: Decimal::new(12050, 2),
+            },
+            &[log_id],
+        )
+        .unwrap();
+        repo.save_rate(&Rate::new(
+            RateScope::Project(child_id),
+            Decimal::new(150, 0),
+            at("2026-01-01 00:00"),
+        ))
+        .unwrap();
+        repo.save_goal(&Goal::new(
+            GoalScope::Tag("bug".to_owned()),
+            1800,
+            GoalPeriod::Weekday,
+        ))
+        .unwrap();
+
+        repo
+    }
+
+    fn to_json(repo: &Sqlite) -> serde_json::Value {
+        let created = parse_datetime("2026-03-01 09:00").unwrap();
+        serde_json::to_value(take(repo, created).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn backup_then_restore_into_empty_database_is_lossless() {
+        // Arrange
+        let repo = filled_repo();
+        let text = serde_json::to_string(&take(&repo, Local::now()).unwrap()).unwrap();
+        let target = Sqlite::in_memory().unwrap();
+
+        // Act
+        let restored = restore(&target, &parse(&text).unwrap(), Mode::Merge).unwrap();
+
+        // Assert
+        assert_eq!(0, restored.skipped);
+        assert_eq!(to_json(&repo), to_json(&target));
+    }
+
+    #[test]
+    fn merge_remaps_ids_and_skips_existing_records() {
+        // Arrange
+        let dump = take(&filled_repo(), Local::now()).unwrap();
+        let target = Sqlite::in_memory().unwrap();
+        let other = Project::new("other".to_owned(), None, Local::now(), Local::now());
+        let other_id = target.save_project(&other).unwrap();
+        restore(&target, &dump, Mode::Merge).unwrap();
+
+        // Act
+        let again = restore(&target, &dump, Mode::Merge).unwrap();
+
+        // Assert
+        let projects = target.all_projects().unwrap();
+        let api = projects.iter().find(|p| p.name == "api").unwrap();
+        let logs = target.all_logs().unwrap();
+        assert_eq!(0, again.added);
+        assert_eq!(3, projects.len());
+        assert_ne!(other_id, api.id);
+        assert!(logs
+            .iter()
+            .any(|l| l.proj_id == api.id && l.invoice_id.is_some()));
+        assert_eq!(2, logs.len());
+        assert!(target.is_imported("ics", "standup").unwrap());
+    }
+
+    #[test]
+    fn replace_removes_existing_records() {
+        // Arrange
+        let repo = filled_repo();
+        let dump = take(&repo, Local::now()).unwrap();
+        let target = Sqlite::in_memory().unwrap();
+        let other = Project::new("other".to_owned(), None, Local::now(), Local::now());
+        target.save_project(&other).unwrap();
+
+        // Act
+        restore(&target, &dump, Mode::Replace).unwrap();
+
+        // Assert
+        assert_eq!(to_json(&repo), to_json(&target));
+    }
+
+    #[test]
+    fn parse_reads_durations_of_older_format_as_seconds() {
+        let res = parse(
+            r#"{"version": 2, "created": "2026-01-01T00:00:00Z", "projects": [{"id": 1,
+            "name": "web", "description": null, "created": "2026-01-01T00:00:00Z",
+            "updated": "2026-01-01T00:00:00Z", "duration": 90, "parent_id": null,
+            "client_id": null, "budget": null}]}"#,
+        );
+
+        assert_eq!(Duration::seconds(90), res.unwrap().projects[0].duration);
+    }
+
+    #[test]
+    fn parse_rejects_newer_format() {
+        let res = parse(r#"{"version": 99, "created": "2026-01-01T00:00:00Z"}"#);
+
+        assert!(res.err().unwrap().starts_with("The backup is in format 99"));
+    }
+
+    #[test]
+    fn restore_rejects_missing_reference_before_changing_anything() {
+        // Arrange
+        let mut dump = take(&filled_repo(), Local::now()).unwrap();
+        dump.logs[0].proj_id = 42;
+        let target = filled_repo();
+
+        // Act
+        let res = restore(&target, &dump, Mode::Replace);
+
+        // Assert
+        assert!(res.is_err());
+        assert_eq!(to_json(&filled_repo()), to_json(&target));
+    }
+}
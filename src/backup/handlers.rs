@@ -0,0 +1,75 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use crate::{
+    backup::dump::{self, Mode},
+    clock::Clock,
+    project::{self, handlers::ProjectChanges},
+    repository::{atomically, Repository},
+};
+
+/// Write everything in the database to a versioned JSON backup. Unlike a copy of the database
+/// file, the backup can be restored after the schema has changed.
+///
+/// # Arguments:
+///
+/// * `path` - Optional file to write to instead of stdout
+pub fn backup(repo: &impl Repository, clock: &impl Clock, path: Option<PathBuf>) {
+    let dump = match dump::take(repo, clock.now()) {
+        Ok(dump) => dump,
+        Err(e) => return eprintln!("Failed to read database: {}", e),
+    };
+    let json = match serde_json::to_string_pretty(&dump) {
+        Ok(json) => json + "\n",
+        Err(e) => return eprintln!("Failed to write backup: {}", e),
+    };
+
+    match path {
+        Some(path) => match fs::write(&path, json) {
+            Ok(_) => println!("Backup written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write backup to {}: {}", path.display(), e),
+        },
+        None => print!("{}", json),
+    }
+}
+
+/// Restore a backup written by `arrow backup`. If anything goes wrong part way, the database is
+/// left the way it was.
+///
+/// # Arguments:
+///
+/// * `path` - Backup to restore
+/// * `mode` - Whether to merge the backup into the existing data or replace it
+pub fn restore(repo: &impl Repository, path: PathBuf, mode: Mode) {
+    let backup = match fs::read_to_string(&path) {
+        Ok(text) => match dump::parse(&text) {
+            Ok(backup) => backup,
+            Err(e) => return eprintln!("{} {}", path.display(), e),
+        },
+        Err(e) => return eprintln!("Failed to read {}: {}", path.display(), e),
+    };
+
+    let restored = atomically(repo, || {
+        let restored = dump::restore(repo, &backup, mode)?;
+        for id in &restored.projects {
+            project::handlers::update(repo, *id, ProjectChanges::default())?;
+        }
+        Ok::<_, Box<dyn Error>>(restored)
+    });
+
+    match restored {
+        Ok(restored) => {
+            println!(
+                "Restored {} record(s) from {}.",
+                restored.added,
+                path.display()
+            );
+            if restored.skipped > 0 {
+                println!(
+                    "Skipped {} record(s) already in the database.",
+                    restored.skipped
+                );
+            }
+        }
+        Err(e) => eprintln!("{}\nNothing was changed.", e),
+    }
+}
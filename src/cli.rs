@@ -1,6 +1,30 @@
+use std::{path::PathBuf, process};
+
 use clap::{Parser, Subcommand};
 
-use crate::{log, project};
+use crate::{
+    backup,
+    backup::dump::Mode,
+    client,
+    clock::SystemClock,
+    config::Config,
+    db, export,
+    export::handlers::Selection,
+    formats::csv::Preset,
+    goal, import,
+    import::handlers::CsvFile,
+    integrity, invoice,
+    invoice::handlers::InvoiceFormat,
+    journal, log,
+    log::search::Matching,
+    model::GoalPeriod,
+    project,
+    project::handlers::ProjectEdits,
+    rate, report,
+    report::handlers::{Days, GroupBy},
+    repository::{text::Text, Backend, Repository, Sqlite},
+    time::zone::DisplayZone,
+};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -14,23 +38,118 @@ pub struct Cli {
 pub enum Cmds {
     /// Start tracking time against a project. Stops when SIGTERM is received
     Start {
-        /// Project to log time for
+        /// Project to log time for. Accepts an ID, name, alias or unique prefix
         project: String,
         /// Description for work to achieve
         message: String,
+        /// Tag the log. Can be given more than once
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Record the time as not chargeable to the client
+        #[arg(long)]
+        non_billable: bool,
     },
     /// Manage your projects
     Project {
-        /// Get logs from project + info
-        id: Option<u32>,
+        /// Get logs from project + info. Accepts an ID, name, alias or unique prefix
+        project: Option<String>,
+        /// Show times in the current zone or the zone each log was recorded in
+        #[arg(long, value_enum, default_value_t)]
+        zone: DisplayZone,
+        #[command(subcommand)]
+        sub: Option<ProjectSubCmds>,
+    },
+    /// Summarise time logged per project. Sub-project time is included in parent totals
+    Report {
+        /// First day to include, e.g. 2026-09-01
+        #[arg(short, long)]
+        from: Option<String>,
+        /// Last day to include, e.g. 2026-09-30
+        #[arg(short, long)]
+        to: Option<String>,
+        /// Only include projects billed to this client
+        #[arg(short, long)]
+        client: Option<String>,
+        /// Group durations by project or by client
+        #[arg(short, long, value_enum, default_value_t = GroupBy::Project)]
+        by: GroupBy,
+        /// Print machine readable JSON with raw and rounded durations
+        #[arg(long)]
+        json: bool,
+        /// Run days midnight to midnight in the current zone or the zone each log was recorded in
+        #[arg(long, value_enum, default_value_t)]
+        zone: DisplayZone,
+    },
+    /// Manage the clients your projects are billed to
+    Client {
+        #[command(subcommand)]
+        sub: ClientSubCmds,
+    },
+    /// Manage hourly rates for clients, projects and tags
+    Rate {
+        #[command(subcommand)]
+        sub: RateSubCmds,
+    },
+    /// Show progress towards your daily and weekly goals and how long your streaks are
+    Goals {
+        #[command(subcommand)]
+        sub: Option<GoalSubCmds>,
+    },
+    /// Manage logs
+    Log {
+        #[command(subcommand)]
+        sub: LogSubCmds,
+    },
+    /// Bill clients for the time logged against their projects
+    Invoice {
         #[command(subcommand)]
-        sub: ProjectSubCmds,
+        sub: InvoiceSubCmds,
+    },
+    /// Copy logs from other time trackers. Entries imported before are skipped
+    Import {
+        #[command(subcommand)]
+        sub: ImportSubCmds,
+    },
+    /// Write logs in formats other time trackers read
+    Export {
+        #[command(subcommand)]
+        sub: ExportSubCmds,
+    },
+    /// Write everything to a versioned JSON backup that survives schema changes
+    Backup {
+        /// File to write to. Defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Restore a JSON backup. Records already in the database are skipped
+    Restore {
+        /// Backup to restore
+        file: PathBuf,
+        /// Merge the backup into the existing data, or replace everything with it
+        #[arg(short, long, value_enum, default_value_t = Mode::Merge)]
+        mode: Mode,
+    },
+    /// Manage the automatic backups of the database file
+    Db {
+        #[command(subcommand)]
+        sub: DbSubCmds,
+    },
+    /// Revert the most recent command that changed anything
+    Undo,
+    /// Replay the command undone last
+    Redo,
+    /// List the commands that changed data, newest first
+    History {
+        /// Most commands to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Check the hash chain over the logs for changes made without arrow
+    Verify {
+        /// Seal logs saved before the chain was turned on, or while it was off
+        #[arg(long)]
+        seal: bool,
     },
-    // // Manage logs
-    // Log {
-    //     #[command(subcommand)]
-    //     sub: LogSubCmds
-    // }
 }
 
 #[derive(Subcommand)]
@@ -41,6 +160,12 @@ pub enum ProjectSubCmds {
         name: String,
         /// Optional project description
         description: Option<String>,
+        /// Project to nest this project under. Accepts an ID, name, alias or path like acme/api
+        #[arg(short, long)]
+        parent: Option<String>,
+        /// Client the project is billed to. Sub-projects inherit their parent's client
+        #[arg(short, long)]
+        client: Option<String>,
     },
     // // Remove project with the given ID
     // Rm { id: u32 },
@@ -48,73 +173,773 @@ pub enum ProjectSubCmds {
     Ls,
     /// Edit a project
     Edit {
-        /// ID of the project to edit
-        id: u32,
+        /// Project to edit. Accepts an ID, name, alias or unique prefix
+        project: String,
         /// New name for project
         #[arg(short, long)]
         name: Option<String>,
         /// New description for project
         #[arg(short, long)]
         description: Option<String>,
+        /// New parent project. "none" makes it a top-level project
+        #[arg(short, long)]
+        parent: Option<String>,
+        /// New client for project. "none" removes it
+        #[arg(short, long)]
+        client: Option<String>,
+        /// Estimate for the whole project including sub-projects, e.g. 40h or 90m. "none" removes it
+        #[arg(short, long, group = "budgets")]
+        estimate: Option<String>,
+        /// Budget that starts over every week or month, e.g. 20h/month. "none" removes it
+        #[arg(short, long, group = "budgets")]
+        budget: Option<String>,
+    },
+    /// Add an alternative name to refer to a project by
+    Alias {
+        /// Project to alias. Accepts an ID, name, alias or unique prefix
+        project: String,
+        /// The new alias
+        alias: String,
+    },
+    /// Show how a project's estimate or current budget has been used up over time
+    Burndown {
+        /// Project with an estimate or budget. Accepts an ID, name, alias or unique prefix
+        project: String,
     },
 }
 
 #[derive(Subcommand)]
-pub enum LogSubCmds {
-    // Start tracking time. Stops when SIGTERM is received
-    Start {
-        // Project to log time for
-        project: u32,
-        // Optional description for work achieved
-        message: Option<String>,
+pub enum ClientSubCmds {
+    /// Create a new client
+    New {
+        /// Name of the client
+        name: String,
+        /// Contact person or email address
+        #[arg(long)]
+        contact: Option<String>,
+        /// Currency the client is billed in
+        #[arg(long, default_value = "AUD")]
+        currency: String,
+        /// Default hourly rate, e.g. 120.50
+        #[arg(long)]
+        rate: Option<String>,
+        /// Billing address
+        #[arg(long)]
+        address: Option<String>,
+    },
+    /// List clients
+    Ls,
+    /// Edit a client
+    Edit {
+        /// ID or name of the client to edit
+        client: String,
+        /// New name for client
+        #[arg(long)]
+        name: Option<String>,
+        /// New contact person or email address
+        #[arg(long)]
+        contact: Option<String>,
+        /// New currency
+        #[arg(long)]
+        currency: Option<String>,
+        /// New default hourly rate
+        #[arg(long)]
+        rate: Option<String>,
+        /// New billing address
+        #[arg(long)]
+        address: Option<String>,
+    },
+    /// Remove a client that has no projects
+    Rm {
+        /// ID or name of the client to remove
+        client: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RateSubCmds {
+    /// Set an hourly rate from a date onwards. Tag rates beat project rates, which beat client rates
+    Set {
+        /// Hourly rate, e.g. 120.50
+        amount: String,
+        /// Client the rate applies to
+        #[arg(short, long, group = "scope")]
+        client: Option<String>,
+        /// Project the rate applies to, including its sub-projects
+        #[arg(short, long, group = "scope")]
+        project: Option<String>,
+        /// Tag the rate applies to
+        #[arg(short, long, group = "scope")]
+        tag: Option<String>,
+        /// First day the rate applies, e.g. 2026-07-01. Defaults to now
+        #[arg(short, long)]
+        from: Option<String>,
+    },
+    /// List rates
+    Ls,
+    /// Remove a rate
+    Rm {
+        /// ID of the rate to remove
+        id: u32,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GoalSubCmds {
+    /// Set a goal for time logged per day, weekday or week. Counts all work unless scoped
+    Set {
+        /// Time to log each period, e.g. 6h or 90m
+        target: String,
+        /// How often the goal has to be met
+        #[arg(long, value_enum, default_value_t = GoalPeriod::Day)]
+        per: GoalPeriod,
+        /// Only count time logged against this project and its sub-projects
+        #[arg(short, long, group = "scope")]
+        project: Option<String>,
+        /// Only count time logged with this tag
+        #[arg(short, long, group = "scope")]
+        tag: Option<String>,
     },
-    // Add a log to a project after the fact. Allows for manual duration input
+    /// Remove a goal
+    Rm {
+        /// ID of the goal to remove
+        id: u32,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum LogSubCmds {
+    /// Add a log to a project after the fact. The log ends now
     Add {
-        // Project to log time for
-        project: u32,
-        // Optional description for work achieved
-        message: Option<String>,
-        // Duration spent on log in minutes (m). Max value is 65535
+        /// Project to log time for. Accepts an ID, name, alias or unique prefix
+        project: String,
+        /// Description for work achieved
+        message: String,
+        /// Duration spent on log in minutes (m). Max value is 65535
         duration: u16,
+        /// Tag the log. Can be given more than once
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+        /// Record the time as not chargeable to the client
+        #[arg(long)]
+        non_billable: bool,
+    },
+    /// Edit a log. Invoiced logs cannot be edited
+    Edit {
+        /// ID of the log to edit
+        id: u32,
+        /// New description
+        #[arg(short, long)]
+        message: Option<String>,
+        /// New start, e.g. "2026-09-30 09:00"
+        #[arg(short, long)]
+        start: Option<String>,
+        /// New end, e.g. "2026-09-30 17:00"
+        #[arg(short, long)]
+        end: Option<String>,
+        /// Whether the time is chargeable to the client
+        #[arg(short, long)]
+        billable: Option<bool>,
+        /// Replace the log's tags. Can be given more than once
+        #[arg(short, long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Remove a log. Invoiced logs cannot be removed
+    Rm {
+        /// ID of the log to remove
+        id: u32,
+    },
+    /// Find logs whose message matches, across every project or in one
+    Search {
+        /// Text to look for, or a glob or regular expression with --matching
+        text: String,
+        /// Only search this project. Accepts an ID, name, alias or unique prefix
+        #[arg(short, long)]
+        project: Option<String>,
+        /// How the text is matched against the messages
+        #[arg(short, long, value_enum, default_value_t)]
+        matching: Matching,
+        /// Show times in the current zone or the zone each log was recorded in
+        #[arg(long, value_enum, default_value_t)]
+        zone: DisplayZone,
+    },
+    /// Show every change made to a log's message or times, starting with what it first said
+    History {
+        /// ID of the log
+        id: u32,
+        /// Show times in the current zone or the zone each log was recorded in
+        #[arg(long, value_enum, default_value_t)]
+        zone: DisplayZone,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum InvoiceSubCmds {
+    /// Invoice a client for uninvoiced billable time in a month. Invoiced logs are locked
+    Create {
+        /// Client to invoice
+        #[arg(short, long)]
+        client: String,
+        /// Month to invoice, e.g. 2026-09
+        #[arg(short, long)]
+        period: String,
+        /// Format to render the invoice in
+        #[arg(short, long, value_enum, default_value_t = InvoiceFormat::Md)]
+        format: InvoiceFormat,
+        /// File to write the invoice to. Defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// List invoices
+    Ls,
+    /// Render an existing invoice again
+    Show {
+        /// Invoice number, e.g. INV-0001
+        number: String,
+        /// Format to render the invoice in
+        #[arg(short, long, value_enum, default_value_t = InvoiceFormat::Md)]
+        format: InvoiceFormat,
+        /// File to write the invoice to. Defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ImportSubCmds {
+    /// Import intervals from Timewarrior. The first tag names the project unless mapped
+    Timewarrior {
+        /// Timewarrior directory, e.g. ~/.timewarrior, or its data directory
+        dir: PathBuf,
+        /// Project for intervals without a mapped tag. All of their tags are kept
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Log intervals with a tag against a project, e.g. acme=acme/api. Can be given more than once
+        #[arg(short, long = "map", value_name = "TAG=PROJECT")]
+        maps: Vec<String>,
+    },
+    /// Import time entries from a CSV file, such as a Toggl or Clockify export
+    Csv {
+        /// CSV file to import
+        file: PathBuf,
+        /// Layout of the file
+        #[arg(long, value_enum, default_value_t = Preset::Generic)]
+        preset: Preset,
+        /// Read a field from another column, e.g. description=Task. Can be given more than once
+        #[arg(long = "column", value_name = "FIELD=HEADER")]
+        columns: Vec<String>,
+        /// Time zone the file's times are in, e.g. Europe/Berlin. Defaults to local time
+        #[arg(long)]
+        timezone: Option<String>,
+        /// Project for entries without one
+        #[arg(short, long)]
+        project: Option<String>,
+        /// Currency clients named in the file are billed in, if they are created
+        #[arg(long, default_value = "AUD")]
+        currency: String,
+        /// Show what would be imported without saving anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Import calendar events, such as meetings, from an iCalendar (.ics) file
+    Ics {
+        /// iCalendar file to import
+        file: PathBuf,
+        /// Project to log the events against
+        #[arg(short, long)]
+        project: String,
+        /// Only import events in this category
+        #[arg(short, long)]
+        category: Option<String>,
+        /// Only import events whose summary matches this regular expression, e.g. "(?i)standup"
+        #[arg(short, long)]
+        summary: Option<String>,
+        /// Record the time as not chargeable to the client
+        #[arg(long)]
+        non_billable: bool,
+    },
+    /// Import sessions from a ledger or hledger timeclock file. Accounts like client:project name the project
+    Timeclock {
+        /// Timeclock file to import
+        file: PathBuf,
+    },
+    /// Import clock entries from an org mode file. Top level headings name the project
+    Org {
+        /// Org file to import
+        file: PathBuf,
+        /// Project for every entry, instead of the top level headings
+        #[arg(short, long)]
+        project: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ExportSubCmds {
+    /// Export logs as CSV
+    Csv {
+        /// Layout to write
+        #[arg(long, value_enum, default_value_t = Preset::Generic)]
+        preset: Preset,
+        /// First day to include, e.g. 2026-09-01
+        #[arg(short, long)]
+        from: Option<String>,
+        /// Last day to include, e.g. 2026-09-30
+        #[arg(short, long)]
+        to: Option<String>,
+        /// Only include projects billed to this client
+        #[arg(short, long)]
+        client: Option<String>,
+        /// Time zone to write times in, e.g. Europe/Berlin. Defaults to local time
+        #[arg(long)]
+        timezone: Option<String>,
+        /// File to write to. Defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export logs as iCalendar (.ics) events to overlay tracked time on a calendar
+    Ics {
+        /// First day to include, e.g. 2026-09-01
+        #[arg(short, long)]
+        from: Option<String>,
+        /// Last day to include, e.g. 2026-09-30
+        #[arg(short, long)]
+        to: Option<String>,
+        /// Only include projects billed to this client
+        #[arg(short, long)]
+        client: Option<String>,
+        /// File to write to. Defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export logs as a ledger/hledger timeclock file with client:project accounts
+    Timeclock {
+        /// First day to include, e.g. 2026-09-01
+        #[arg(short, long)]
+        from: Option<String>,
+        /// Last day to include, e.g. 2026-09-30
+        #[arg(short, long)]
+        to: Option<String>,
+        /// Only include projects billed to this client
+        #[arg(short, long)]
+        client: Option<String>,
+        /// File to write to. Defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export logs as an org mode file with a heading per project and CLOCK entries
+    Org {
+        /// First day to include, e.g. 2026-09-01
+        #[arg(short, long)]
+        from: Option<String>,
+        /// Last day to include, e.g. 2026-09-30
+        #[arg(short, long)]
+        to: Option<String>,
+        /// Only include projects billed to this client
+        #[arg(short, long)]
+        client: Option<String>,
+        /// File to write to. Defaults to stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DbSubCmds {
+    /// List backups taken before migrations and on schedule, newest first
+    Ls,
+    /// Replace the database with a backup. The current database is backed up first
+    Restore {
+        /// Backup to restore. Accepts a path or the name of a file listed by `arrow db ls`
+        #[arg(long)]
+        from: String,
     },
-    // Remove log from a project
-    // Rm {
-    //     // Project to remove log for
-    //     project: u32,
-    //     // Id of log to remove
-    //     log: u32,
-    // }
 }
 
+/// Runs a command on a single connection to the database, recording the changes it makes in
+/// the journal so they can be undone.
 pub fn handle(cmd: &Cmds) {
-    match cmd {
-        Cmds::Start { project, message } => {
-            log::handlers::new(project.to_owned(), message.to_owned())
+    let backend = match Config::load() {
+        Ok(config) => config.storage.backend,
+        Err(e) => {
+            eprintln!("Error reading config.toml: {}", e);
+            process::exit(1);
         }
-        Cmds::Project { id, sub } => {
-            if let Some(id) = id {
-                // project::handlers::get(id)
-            }
+    };
 
+    match (backend, cmd) {
+        (Backend::Sqlite, Cmds::Db { sub }) => {
+            let mut repo = Sqlite::new().expect("Failed to connect to repository!");
             match sub {
-                ProjectSubCmds::New { name, description } => {
-                    project::handlers::new(name.to_owned(), description.to_owned())
-                }
-                // ProjectSubCmds::Rm { id } => project::handlers::remove(id),
-                ProjectSubCmds::Ls => project::handlers::list(),
-                ProjectSubCmds::Edit {
-                    id,
-                    name,
-                    description,
-                } => project::handlers::update(*id, name.to_owned(), description.to_owned()),
+                DbSubCmds::Ls => db::handlers::list(),
+                DbSubCmds::Restore { from } => db::handlers::restore(&mut repo, from.to_owned()),
             }
         }
-        // Cmds::Log { sub } => {
-        //     match sub {
-        //         LogSubCmds::Start { project, message } => log::handlers::start_logging(project, message.clone()),
-        //         LogSubCmds::Add { project, message, duration } => log::handlers::save_log(project, message.clone(), duration),
-        //         LogSubCmds::Rm { project, log } => log::handlers::remove_log(project, log),
-        //     };
-        // }
+        (Backend::Sqlite, _) => {
+            let repo = Sqlite::new().expect("Failed to connect to repository!");
+            journaled(&repo, cmd);
+        }
+        (Backend::Text, Cmds::Db { .. }) => eprintln!(
+            "Your data is stored in text files, so there is no database to manage. Set `backend = \"sqlite\"` under `[storage]` in config.toml to use it."
+        ),
+        (Backend::Text, _) => {
+            let repo = Text::new().expect("Failed to read the text files!");
+            journaled(&repo, cmd);
+        }
+    }
+}
+
+/// Runs the command, recording its changes in the journal unless it reads or rewrites the
+/// journal itself
+fn journaled(repo: &impl Repository, cmd: &Cmds) {
+    let journaled = !matches!(cmd, Cmds::Undo | Cmds::Redo | Cmds::History { .. });
+    let operation = match journaled {
+        true => journal::handlers::begin(
+            repo,
+            &std::env::args().skip(1).collect::<Vec<_>>().join(" "),
+        ),
+        false => None,
+    };
+
+    run(repo, cmd);
+
+    if let Some(id) = operation {
+        journal::handlers::end(repo, id);
+    }
+}
+
+fn run(repo: &impl Repository, cmd: &Cmds) {
+    let clock = SystemClock;
+
+    match cmd {
+        Cmds::Start {
+            project,
+            message,
+            tags,
+            non_billable,
+        } => log::handlers::new(
+            repo,
+            &clock,
+            project.to_owned(),
+            message.to_owned(),
+            tags.to_owned(),
+            !non_billable,
+        ),
+        Cmds::Project { project, zone, sub } => match sub {
+            Some(ProjectSubCmds::New {
+                name,
+                description,
+                parent,
+                client,
+            }) => project::handlers::new(
+                repo,
+                &clock,
+                name.to_owned(),
+                description.to_owned(),
+                parent.to_owned(),
+                client.to_owned(),
+            ),
+            // Some(ProjectSubCmds::Rm { id }) => project::handlers::remove(repo, id),
+            Some(ProjectSubCmds::Ls) => project::handlers::list(repo, &clock),
+            Some(ProjectSubCmds::Edit {
+                project,
+                name,
+                description,
+                parent,
+                client,
+                estimate,
+                budget,
+            }) => project::handlers::edit(
+                repo,
+                project,
+                ProjectEdits {
+                    name: name.to_owned(),
+                    description: description.to_owned(),
+                    parent: parent.to_owned(),
+                    client: client.to_owned(),
+                    estimate: estimate.to_owned(),
+                    recurring: budget.to_owned(),
+                },
+            ),
+            Some(ProjectSubCmds::Alias { project, alias }) => {
+                project::handlers::alias(repo, project, alias.to_owned())
+            }
+            Some(ProjectSubCmds::Burndown { project }) => {
+                project::handlers::burndown(repo, &clock, project)
+            }
+            None => match project {
+                Some(project) => project::handlers::get(repo, &clock, project, *zone),
+                None => project::handlers::list(repo, &clock),
+            },
+        },
+        Cmds::Report {
+            from,
+            to,
+            client,
+            by,
+            json,
+            zone,
+        } => report::handlers::summary(
+            repo,
+            &clock,
+            Days {
+                from: from.to_owned(),
+                to: to.to_owned(),
+                zone: *zone,
+            },
+            client.to_owned(),
+            *by,
+            *json,
+        ),
+        Cmds::Client { sub } => match sub {
+            ClientSubCmds::New {
+                name,
+                contact,
+                currency,
+                rate,
+                address,
+            } => client::handlers::new(
+                repo,
+                name.to_owned(),
+                contact.to_owned(),
+                currency.to_owned(),
+                rate.to_owned(),
+                address.to_owned(),
+            ),
+            ClientSubCmds::Ls => client::handlers::list(repo),
+            ClientSubCmds::Edit {
+                client,
+                name,
+                contact,
+                currency,
+                rate,
+                address,
+            } => client::handlers::edit(
+                repo,
+                client,
+                name.to_owned(),
+                contact.to_owned(),
+                currency.to_owned(),
+                rate.to_owned(),
+                address.to_owned(),
+            ),
+            ClientSubCmds::Rm { client } => client::handlers::remove(repo, client),
+        },
+        Cmds::Rate { sub } => match sub {
+            RateSubCmds::Set {
+                amount,
+                client,
+                project,
+                tag,
+                from,
+            } => rate::handlers::set(
+                repo,
+                &clock,
+                amount.to_owned(),
+                client.to_owned(),
+                project.to_owned(),
+                tag.to_owned(),
+                from.to_owned(),
+            ),
+            RateSubCmds::Ls => rate::handlers::list(repo),
+            RateSubCmds::Rm { id } => rate::handlers::remove(repo, *id),
+        },
+        Cmds::Goals { sub } => match sub {
+            Some(GoalSubCmds::Set {
+                target,
+                per,
+                project,
+                tag,
+            }) => goal::handlers::set(
+                repo,
+                target.to_owned(),
+                *per,
+                project.to_owned(),
+                tag.to_owned(),
+            ),
+            Some(GoalSubCmds::Rm { id }) => goal::handlers::remove(repo, *id),
+            None => goal::handlers::show(repo, &clock),
+        },
+        Cmds::Log { sub } => match sub {
+            LogSubCmds::Add {
+                project,
+                message,
+                duration,
+                tags,
+                non_billable,
+            } => log::handlers::add(
+                repo,
+                &clock,
+                project.to_owned(),
+                message.to_owned(),
+                *duration,
+                tags.to_owned(),
+                !non_billable,
+            ),
+            LogSubCmds::Edit {
+                id,
+                message,
+                start,
+                end,
+                billable,
+                tags,
+            } => log::handlers::edit(
+                repo,
+                *id,
+                message.to_owned(),
+                start.to_owned(),
+                end.to_owned(),
+                *billable,
+                tags.to_owned(),
+            ),
+            LogSubCmds::Rm { id } => log::handlers::remove(repo, *id),
+            LogSubCmds::Search {
+                text,
+                project,
+                matching,
+                zone,
+            } => log::handlers::search(repo, text.to_owned(), project.to_owned(), *matching, *zone),
+            LogSubCmds::History { id, zone } => log::handlers::history(repo, *id, *zone),
+        },
+        Cmds::Invoice { sub } => match sub {
+            InvoiceSubCmds::Create {
+                client,
+                period,
+                format,
+                output,
+            } => {
+                invoice::handlers::create(repo, &clock, client, period, *format, output.to_owned())
+            }
+            InvoiceSubCmds::Ls => invoice::handlers::list(repo),
+            InvoiceSubCmds::Show {
+                number,
+                format,
+                output,
+            } => invoice::handlers::show(repo, number, *format, output.to_owned()),
+        },
+        Cmds::Import { sub } => match sub {
+            ImportSubCmds::Timewarrior { dir, project, maps } => import::handlers::timewarrior(
+                repo,
+                &clock,
+                dir.to_owned(),
+                project.to_owned(),
+                maps.to_owned(),
+            ),
+            ImportSubCmds::Csv {
+                file,
+                preset,
+                columns,
+                timezone,
+                project,
+                currency,
+                dry_run,
+            } => import::handlers::csv(
+                repo,
+                &clock,
+                CsvFile {
+                    path: file.to_owned(),
+                    preset: *preset,
+                    columns: columns.to_owned(),
+                    timezone: timezone.to_owned(),
+                },
+                project.to_owned(),
+                currency.to_owned(),
+                *dry_run,
+            ),
+            ImportSubCmds::Ics {
+                file,
+                project,
+                category,
+                summary,
+                non_billable,
+            } => import::handlers::ics(
+                repo,
+                &clock,
+                file.to_owned(),
+                project.to_owned(),
+                category.to_owned(),
+                summary.to_owned(),
+                !non_billable,
+            ),
+            ImportSubCmds::Timeclock { file } => {
+                import::handlers::timeclock(repo, &clock, file.to_owned())
+            }
+            ImportSubCmds::Org { file, project } => {
+                import::handlers::org(repo, &clock, file.to_owned(), project.to_owned())
+            }
+        },
+        Cmds::Export { sub } => match sub {
+            ExportSubCmds::Csv {
+                preset,
+                from,
+                to,
+                client,
+                timezone,
+                output,
+            } => export::handlers::csv(
+                repo,
+                &clock,
+                *preset,
+                Selection {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                    client: client.to_owned(),
+                },
+                timezone.to_owned(),
+                output.to_owned(),
+            ),
+            ExportSubCmds::Ics {
+                from,
+                to,
+                client,
+                output,
+            } => export::handlers::ics(
+                repo,
+                &clock,
+                Selection {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                    client: client.to_owned(),
+                },
+                output.to_owned(),
+            ),
+            ExportSubCmds::Timeclock {
+                from,
+                to,
+                client,
+                output,
+            } => export::handlers::timeclock(
+                repo,
+                &clock,
+                Selection {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                    client: client.to_owned(),
+                },
+                output.to_owned(),
+            ),
+            ExportSubCmds::Org {
+                from,
+                to,
+                client,
+                output,
+            } => export::handlers::org(
+                repo,
+                &clock,
+                Selection {
+                    from: from.to_owned(),
+                    to: to.to_owned(),
+                    client: client.to_owned(),
+                },
+                output.to_owned(),
+            ),
+        },
+        Cmds::Backup { output } => backup::handlers::backup(repo, &clock, output.to_owned()),
+        Cmds::Restore { file, mode } => backup::handlers::restore(repo, file.to_owned(), *mode),
+        // the database is managed in `handle`, before it is opened as a repository
+        Cmds::Db { .. } => {}
+        Cmds::Undo => journal::handlers::undo(repo),
+        Cmds::Redo => journal::handlers::redo(repo),
+        Cmds::History { limit } => journal::handlers::history(repo, *limit),
+        Cmds::Verify { seal } => integrity::handlers::verify(repo, *seal),
     }
 }
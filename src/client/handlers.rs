@@ -0,0 +1,233 @@
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+
+use crate::{client::resolve::resolve, model::Client, print::table::Table, repository::Repository};
+
+/// Validates an ISO 4217 style currency code, returning it in upper case.
+pub fn parse_currency(currency: &str) -> Result<String, String> {
+    let code = currency.trim().to_uppercase();
+    if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(code)
+    } else {
+        Err(format!(
+            "Invalid currency \"{}\". Use a three letter code such as AUD",
+            currency
+        ))
+    }
+}
+
+/// Parses an hourly rate such as `85` or `120.50`. Rates cannot be negative.
+pub fn parse_rate(rate: &str) -> Result<Decimal, String> {
+    match Decimal::from_str(rate.trim()) {
+        Ok(r) if r.is_sign_negative() => Err(format!("Rate {} cannot be negative", rate)),
+        Ok(r) => Ok(r),
+        Err(_) => Err(format!(
+            "Invalid rate \"{}\". Rates should look like 85 or 120.50",
+            rate
+        )),
+    }
+}
+
+fn print_clients(clients: &[Client]) {
+    let mut table = Table::new(vec![
+        "ID".to_string(),
+        "Name".to_string(),
+        "Contact".to_string(),
+        "Currency".to_string(),
+        "Rate".to_string(),
+        "Address".to_string(),
+    ]);
+    for client in clients {
+        table.add_row(vec![
+            client.id.to_string(),
+            client.name.clone(),
+            client.contact.clone().unwrap_or_default(),
+            client.currency.clone(),
+            client
+                .hourly_rate
+                .map(|r| r.to_string())
+                .unwrap_or_default(),
+            client.address.clone().unwrap_or_default(),
+        ]);
+    }
+    table.print(&mut std::io::stdout());
+}
+
+/// Create new client.
+///
+/// # Arguments:
+///
+/// * `name` - Name of the client
+/// * `contact` - Optional contact person or email
+/// * `currency` - Currency the client is billed in
+/// * `rate` - Optional default hourly rate
+/// * `address` - Optional billing address
+pub fn new(
+    repo: &impl Repository,
+    name: String,
+    contact: Option<String>,
+    currency: String,
+    rate: Option<String>,
+    address: Option<String>,
+) {
+    let currency = match parse_currency(&currency) {
+        Ok(c) => c,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let hourly_rate = match rate.as_deref().map(parse_rate).transpose() {
+        Ok(r) => r,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let client = Client::new(name, contact, currency, hourly_rate, address);
+    match repo.save_client(&client) {
+        Ok(_) => println!("{} created successfully.\n", client.name),
+        Err(e) => eprintln!("Failed to create new client: {}", e),
+    }
+}
+
+pub fn list(repo: &impl Repository) {
+    match repo.all_clients() {
+        Ok(clients) => print_clients(&clients),
+        Err(e) => eprintln!("Error retrieving your clients: {}", e),
+    }
+}
+
+/// Edit the client the reference resolves to. Only the given fields are changed.
+///
+/// # Arguments:
+///
+/// * `reference` - ID or name of the client
+pub fn edit(
+    repo: &impl Repository,
+    reference: &str,
+    name: Option<String>,
+    contact: Option<String>,
+    currency: Option<String>,
+    rate: Option<String>,
+    address: Option<String>,
+) {
+    let mut client = match resolve(repo, reference) {
+        Ok(c) => c,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    if let Some(name) = name {
+        client.name = name;
+    }
+    if let Some(contact) = contact {
+        client.contact = Some(contact);
+    }
+    if let Some(currency) = currency {
+        match parse_currency(&currency) {
+            Ok(c) => client.currency = c,
+            Err(e) => return eprintln!("{}", e),
+        }
+    }
+    if let Some(rate) = rate {
+        match parse_rate(&rate) {
+            Ok(r) => client.hourly_rate = Some(r),
+            Err(e) => return eprintln!("{}", e),
+        }
+    }
+    if let Some(address) = address {
+        client.address = Some(address);
+    }
+
+    match repo.update_client(&client) {
+        Ok(_) => println!("{} updated successfully.\n", client.name),
+        Err(e) => eprintln!("Failed to update client: {}", e),
+    }
+}
+
+/// Delete the client the reference resolves to. Clients with projects or invoices cannot be
+/// removed.
+///
+/// # Arguments:
+///
+/// * `reference` - ID or name of the client
+pub fn remove(repo: &impl Repository, reference: &str) {
+    let client = match resolve(repo, reference) {
+        Ok(c) => c,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    match repo.all_projects() {
+        Ok(projects) if projects.iter().any(|p| p.client_id == Some(client.id)) => {
+            return eprintln!(
+                "{} still has projects. Move them to another client first.",
+                client.name
+            )
+        }
+        Ok(_) => {}
+        Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+    }
+    match repo.all_invoices() {
+        Ok(invoices) if invoices.iter().any(|i| i.client_id == client.id) => {
+            return eprintln!(
+                "{} has been invoiced, so it cannot be removed.",
+                client.name
+            )
+        }
+        Ok(_) => {}
+        Err(e) => return eprintln!("Error retrieving invoices: {}", e),
+    }
+
+    match repo.delete_client(&client.id) {
+        Ok(_) => println!("Deleted client {}", client.name),
+        Err(e) => eprintln!("Failed to delete client: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{model::Invoice, repository::Sqlite, time::to_datetime};
+
+    #[test]
+    fn parse_currency_uppercases_code() {
+        assert_eq!(Ok("AUD".to_string()), parse_currency("aud"));
+    }
+
+    #[test]
+    fn parse_currency_rejects_non_codes() {
+        assert!(parse_currency("dollars").is_err());
+    }
+
+    #[test]
+    fn parse_rate_keeps_exact_decimal() {
+        assert_eq!(Ok(Decimal::new(12050, 2)), parse_rate("120.50"));
+    }
+
+    #[test]
+    fn parse_rate_rejects_negative() {
+        assert!(parse_rate("-1").is_err());
+    }
+
+    #[test]
+    fn remove_keeps_invoiced_client() {
+        // Arrange
+        let repo = Sqlite::in_memory().unwrap();
+        let client = Client::new("acme".to_owned(), None, "AUD".to_owned(), None, None);
+        let client_id = repo.save_client(&client).unwrap();
+        let invoice = Invoice {
+            id: 0,
+            number: "INV-0001".to_owned(),
+            client_id,
+            period_start: to_datetime(0),
+            period_end: to_datetime(100),
+            created: to_datetime(100),
+            currency: "AUD".to_owned(),
+            lines: Vec::new(),
+            total: Decimal::ZERO,
+        };
+        repo.save_invoice(&invoice, &[]).unwrap();
+
+        // Act
+        remove(&repo, "acme");
+
+        // Assert
+        assert_eq!(client_id, repo.get_client(&client_id).unwrap().id);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{model::Client, repository::Repository};
+
+/// Finds the client a user is referring to by ID, exact name or unique, case-insensitive prefix.
+///
+/// # Arguments
+///
+/// * `repo` - Repository to look the client up in
+/// * `reference` - ID or name typed by the user
+pub fn resolve(repo: &impl Repository, reference: &str) -> Result<Client, String> {
+    let clients = repo
+        .all_clients()
+        .map_err(|e| format!("Error retrieving clients: {}", e))?;
+
+    let mut matched = find(reference, clients);
+    match matched.len() {
+        0 => Err(format!("No client matches \"{}\"", reference)),
+        1 => Ok(matched.remove(0)),
+        _ => {
+            let names: Vec<String> = matched
+                .iter()
+                .map(|c| format!("  {} {}", c.id, c.name))
+                .collect();
+            Err(format!(
+                "\"{}\" matches more than one client:\n{}",
+                reference,
+                names.join("\n")
+            ))
+        }
+    }
+}
+
+fn find(reference: &str, clients: Vec<Client>) -> Vec<Client> {
+    let reference = reference.trim();
+
+    if let Ok(id) = reference.parse::<u32>() {
+        if clients.iter().any(|c| c.id == id) {
+            return clients.into_iter().filter(|c| c.id == id).collect();
+        }
+    }
+
+    if clients.iter().any(|c| c.name == reference) {
+        return clients
+            .into_iter()
+            .filter(|c| c.name == reference)
+            .collect();
+    }
+
+    let lower = reference.to_lowercase();
+    if clients.iter().any(|c| c.name.to_lowercase() == lower) {
+        return clients
+            .into_iter()
+            .filter(|c| c.name.to_lowercase() == lower)
+            .collect();
+    }
+
+    clients
+        .into_iter()
+        .filter(|c| c.name.to_lowercase().starts_with(&lower))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clients(names: &[&str]) -> Vec<Client> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let mut client = Client::new(name.to_string(), None, "AUD".to_owned(), None, None);
+                client.id = i as u32 + 1;
+                client
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_matches_id() {
+        // Act
+        let res = find("2", clients(&["acme", "globex"]));
+
+        // Assert
+        assert_eq!("globex", res[0].name);
+    }
+
+    #[test]
+    fn find_matches_name_case_insensitively() {
+        // Act
+        let res = find("ACME", clients(&["acme", "acme-west"]));
+
+        // Assert
+        assert_eq!(1, res.len());
+        assert_eq!("acme", res[0].name);
+    }
+
+    #[test]
+    fn find_shared_prefix_returns_all_candidates() {
+        // Act
+        let res = find("ac", clients(&["acme", "acme-west", "globex"]));
+
+        // Assert
+        assert_eq!(2, res.len());
+    }
+}
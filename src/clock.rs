@@ -0,0 +1,138 @@
+#[cfg(test)]
+use std::{cell::Cell, rc::Rc};
+
+#[cfg(test)]
+use chrono::Duration;
+use chrono::{DateTime, Local};
+
+/// Tells the time. Handlers and repositories ask a clock rather than the system, so that what
+/// they do at a given time can be tested.
+pub trait Clock {
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// The system's clock, in the local time zone
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// A clock that stands still until it is set or advanced. Clones share their time, so a clock
+/// handed to a repository can still be stepped by whoever kept a clone of it.
+#[cfg(test)]
+#[derive(Clone, Debug)]
+pub struct FixedClock {
+    now: Rc<Cell<DateTime<Local>>>,
+}
+
+#[cfg(test)]
+impl FixedClock {
+    /// A clock stopped at the given time
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Time the clock tells until it is changed
+    pub fn at(now: DateTime<Local>) -> Self {
+        FixedClock {
+            now: Rc::new(Cell::new(now)),
+        }
+    }
+
+    /// Moves the clock, and every clone of it, to the given time
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - New time, which may be earlier than the current one
+    pub fn set(&self, now: DateTime<Local>) {
+        self.now.set(now);
+    }
+
+    /// Moves the clock, and every clone of it, forward
+    ///
+    /// # Arguments
+    ///
+    /// * `by` - How far to move it. Negative durations move it back
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Local> {
+        self.now.get()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Box<C> {
+    fn now(&self) -> DateTime<Local> {
+        (**self).now()
+    }
+}
+
+/// Central European Time as the `TZ` environment variable takes it. Clocks go forward from 02:00
+/// to 03:00 on the last Sunday of March and back from 03:00 to 02:00 on the last Sunday of October
+#[cfg(test)]
+pub const CENTRAL_EUROPE: &str = "CET-1CEST,M3.5.0,M10.5.0/3";
+
+/// A zone where clocks go forward from midnight to 01:00 on the 1st of September, as they have
+/// in parts of South America, so that day has no midnight. It was a Monday in 2025
+#[cfg(test)]
+pub const SKIPPED_MIDNIGHT: &str = "<-04>4<-03>,J244/0,J91/0";
+
+/// Runs a test again in a process of its own, with the local time zone set to `tz`, so it can
+/// check what happens around daylight saving changes wherever the tests are run. Returns `true`
+/// once the test has passed in that process, and `false` in the process itself, where the test
+/// goes on to run.
+///
+/// # Arguments
+///
+/// * `tz` - Time zone as the `TZ` environment variable takes it, e.g. `CENTRAL_EUROPE`
+/// * `test` - Path of the test, e.g. `concat!(module_path!(), "::my_test")`
+#[cfg(test)]
+pub fn in_zone(tz: &str, test: &str) -> bool {
+    if std::env::var("TZ").is_ok_and(|current| current == tz) {
+        return false;
+    }
+
+    let name = test.split_once("::").map_or(test, |(_, path)| path);
+    let output = std::process::Command::new(std::env::current_exe().expect("Failed to find tests"))
+        .args([name, "--exact"])
+        .env("TZ", tz)
+        .output()
+        .expect("Failed to run test");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(
+        output.status.success() && stdout.contains("1 passed"),
+        "{} failed in time zone {}:\n{}",
+        name,
+        tz,
+        stdout
+    );
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::time::to_datetime;
+
+    use super::*;
+
+    #[test]
+    fn advance_should_move_every_clone() {
+        // Arrange
+        let clock = FixedClock::at(to_datetime(1000));
+        let clone = clock.clone();
+
+        // Act
+        clock.advance(Duration::seconds(60));
+
+        // Assert
+        assert_eq!(1060, clone.now().timestamp());
+    }
+}
@@ -0,0 +1,135 @@
+use std::{collections::HashMap, error::Error, fs};
+
+use serde::Deserialize;
+
+use crate::{
+    db::backups::Schedule, integrity::chain::Integrity, repository::Storage, rounding::RoundingRule,
+};
+
+/// Settings read from `$XDG_CONFIG_HOME/arrow/config.toml`. Every setting is optional.
+///
+/// ```toml
+/// [rounding.default]
+/// increment = 15
+/// direction = "up"
+/// scope = "entry"
+/// minimum = 15
+///
+/// [rounding.clients.acme]
+/// increment = 6
+/// direction = "nearest"
+/// scope = "day"
+///
+/// [backups]
+/// every = "day"
+/// keep = 7
+///
+/// [integrity]
+/// chain = true
+///
+/// [storage]
+/// backend = "text"
+/// dir = "/home/me/notes/arrow"
+/// ```
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub rounding: Option<Rounding>,
+    /// Backups are taken daily and the last 7 of each kind kept, unless configured otherwise
+    pub backups: Schedule,
+    /// Logs are only sealed in a hash chain when turned on
+    pub integrity: Integrity,
+    /// Data is stored in SQLite, unless text files are chosen
+    pub storage: Storage,
+}
+
+/// The default rounding rule and overrides for clients that bill differently, keyed by client
+/// name.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rounding {
+    pub default: Option<RoundingRule>,
+    pub clients: HashMap<String, RoundingRule>,
+}
+
+impl Config {
+    /// Reads the config file, if there is one.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let xdg_dirs = xdg::BaseDirectories::with_prefix("arrow")?;
+        match xdg_dirs.find_config_file("config.toml") {
+            Some(path) => Ok(Self::parse(&fs::read_to_string(path)?)?),
+            None => Ok(Config::default()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    /// Rounding rule for time billed to a client, falling back to the default rule.
+    pub fn rounding_for(&self, client: Option<&str>) -> Option<&RoundingRule> {
+        let rounding = self.rounding.as_ref()?;
+        client
+            .and_then(|name| rounding.clients.get(name))
+            .or(rounding.default.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rounding::{Direction, Scope};
+
+    use super::*;
+
+    #[test]
+    fn parse_empty_config_has_no_rounding() {
+        assert_eq!(Config::default(), Config::parse("").unwrap());
+    }
+
+    #[test]
+    fn rounding_for_prefers_client_override() {
+        // Arrange
+        let config = Config::parse(
+            r#"
+            [rounding.default]
+            increment = 15
+            direction = "up"
+
+            [rounding.clients.acme]
+            increment = 6
+            direction = "nearest"
+            scope = "day"
+            "#,
+        )
+        .unwrap();
+
+        // Act
+        let acme = config.rounding_for(Some("acme")).unwrap();
+        let other = config.rounding_for(Some("globex")).unwrap();
+
+        // Assert
+        assert_eq!(
+            (6, Direction::Nearest, Scope::Day),
+            (acme.increment, acme.direction, acme.scope)
+        );
+        assert_eq!(
+            (15, Direction::Up, Scope::Entry),
+            (other.increment, other.direction, other.scope)
+        );
+    }
+
+    #[test]
+    fn parse_unknown_setting_fails() {
+        assert!(
+            Config::parse("[rounding.default]\nincrement = 15\ndirection = \"sideways\"").is_err()
+        );
+    }
+
+    #[test]
+    fn parse_rounding_outside_a_day_fails() {
+        assert!(Config::parse("[rounding.default]\nincrement = 0").is_err());
+        assert!(Config::parse("[rounding.default]\nincrement = 1441").is_err());
+        assert!(Config::parse("[rounding.default]\nincrement = 15\nminimum = 4294967295").is_err());
+        assert!(Config::parse("[rounding.default]\nincrement = 1440\nminimum = 1440").is_ok());
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use rusqlite::{Connection, DatabaseName};
+use serde::Deserialize;
+
+use crate::clock::Clock;
+
+/// How often scheduled backups are taken
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Every {
+    /// On the first command of each day
+    #[default]
+    Day,
+    /// On the first command of each week, starting Monday
+    Week,
+    Never,
+}
+
+/// When the database is backed up, and how many backups of each kind are kept.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Schedule {
+    pub every: Every,
+    pub keep: usize,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule {
+            every: Every::Day,
+            keep: 7,
+        }
+    }
+}
+
+/// Why a backup was taken. Each kind is rotated on its own, so daily backups never push out the
+/// backup taken before a migration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Scheduled,
+    /// Taken before the schema is migrated
+    Migration,
+    /// Taken before the database is overwritten by `arrow db restore`
+    Restore,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Scheduled => "scheduled",
+            Kind::Migration => "pre-migration",
+            Kind::Restore => "pre-restore",
+        }
+    }
+}
+
+/// A copy of the database in the backups directory
+#[derive(Debug, PartialEq)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub taken: NaiveDateTime,
+    pub kind: Kind,
+}
+
+/// How the time a backup was taken is written in its name, down to the millisecond
+const TAKEN: &str = "%Y%m%d-%H%M%S%3f";
+
+/// How it was written before milliseconds were added
+const TAKEN_SECONDS: &str = "%Y%m%d-%H%M%S";
+
+impl Backup {
+    /// Reads when and why a backup was taken from its file name, e.g.
+    /// `arrow-20261019-090000123-scheduled.db`, or `arrow-20261019-090000-scheduled.db` for
+    /// backups named before milliseconds were added
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let rest = name.strip_prefix("arrow-")?.strip_suffix(".db")?;
+        let kind = [Kind::Scheduled, Kind::Migration, Kind::Restore]
+            .into_iter()
+            .find(|k| rest.ends_with(&format!("-{}", k.name())))?;
+        let taken = &rest[..rest.len() - kind.name().len() - 1];
+
+        Some(Backup {
+            taken: NaiveDateTime::parse_from_str(taken, TAKEN)
+                .or_else(|_| NaiveDateTime::parse_from_str(taken, TAKEN_SECONDS))
+                .ok()?,
+            kind,
+            path,
+        })
+    }
+}
+
+/// `$XDG_DATA_HOME/arrow/backups`, created if needed
+pub fn dir() -> Result<PathBuf, Box<dyn Error>> {
+    let xdg_dirs = xdg::BaseDirectories::with_prefix("arrow")?;
+    Ok(xdg_dirs.create_data_directory("backups")?)
+}
+
+/// Backups in a directory, newest first. Other files are ignored.
+pub fn list(dir: &Path) -> io::Result<Vec<Backup>> {
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        if let Some(backup) = Backup::from_path(entry?.path()) {
+            backups.push(backup);
+        }
+    }
+    backups.sort_by_key(|b| std::cmp::Reverse(b.taken));
+
+    Ok(backups)
+}
+
+/// Copies the database with SQLite's online backup API, which is safe while it is in use.
+/// Backups are never overwritten: one taken in the same millisecond as another of its kind is
+/// named a millisecond later.
+///
+/// # Arguments
+///
+/// * `conn` - Connection to the database to back up
+/// * `dir` - Directory to write the backup to
+/// * `kind` - Why the backup is taken
+/// * `now` - When the backup is taken, used to name it
+pub fn take(
+    conn: &Connection,
+    dir: &Path,
+    kind: Kind,
+    now: NaiveDateTime,
+) -> rusqlite::Result<PathBuf> {
+    let mut taken = now;
+    let path = loop {
+        let path = dir.join(format!("arrow-{}-{}.db", taken.format(TAKEN), kind.name()));
+        if !path.exists() {
+            break path;
+        }
+        taken += Duration::milliseconds(1);
+    };
+    conn.backup(DatabaseName::Main, &path, None)?;
+
+    Ok(path)
+}
+
+/// Deletes the oldest backups of a kind, leaving the newest `keep`. Returns how many were
+/// deleted.
+pub fn prune(dir: &Path, kind: Kind, keep: usize) -> io::Result<usize> {
+    let old: Vec<Backup> = list(dir)?
+        .into_iter()
+        .filter(|b| b.kind == kind)
+        .skip(keep)
+        .collect();
+    for backup in &old {
+        fs::remove_file(&backup.path)?;
+    }
+
+    Ok(old.len())
+}
+
+/// Whether a scheduled backup should be taken, because none was taken yet this day or week.
+pub fn due(backups: &[Backup], every: Every, today: NaiveDate) -> bool {
+    let last = backups
+        .iter()
+        .filter(|b| b.kind == Kind::Scheduled)
+        .map(|b| b.taken.date())
+        .max();
+
+    match every {
+        Every::Day => last.is_none_or(|day| day < today),
+        Every::Week => last.is_none_or(|day| day.iso_week() != today.iso_week()),
+        Every::Never => false,
+    }
+}
+
+/// Backs up the database to the backups directory and rotates out the oldest backups of the
+/// same kind. Always keeps the backup just taken.
+pub fn rotate(
+    conn: &Connection,
+    clock: &impl Clock,
+    kind: Kind,
+    keep: usize,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = dir()?;
+    let path = take(conn, &dir, kind, clock.now().naive_local())?;
+    prune(&dir, kind, keep.max(1))?;
+
+    Ok(path)
+}
+
+/// Takes a scheduled backup if one is due. Failing to back up is reported but does not stop the
+/// command being run.
+///
+/// # Arguments
+///
+/// * `conn` - Connection to the database to back up
+/// * `clock` - Clock telling whether a backup is due, and naming it
+/// * `schedule` - Configured schedule
+pub fn on_schedule(conn: &Connection, clock: &impl Clock, schedule: Schedule) {
+    let res = dir().and_then(|dir| {
+        if due(&list(&dir)?, schedule.every, clock.now().date_naive()) {
+            rotate(conn, clock, Kind::Scheduled, schedule.keep)?;
+        }
+        Ok(())
+    });
+    if let Err(e) = res {
+        eprintln!("Failed to back up database: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup(name: &str) -> Backup {
+        Backup::from_path(PathBuf::from(name)).unwrap()
+    }
+
+    #[test]
+    fn from_path_reads_time_and_kind() {
+        assert_eq!(
+            Some(Backup {
+                path: PathBuf::from("/b/arrow-20261019-093000-pre-migration.db"),
+                taken: NaiveDate::from_ymd_opt(2026, 10, 19)
+                    .unwrap()
+                    .and_hms_opt(9, 30, 0)
+                    .unwrap(),
+                kind: Kind::Migration,
+            }),
+            Backup::from_path(PathBuf::from("/b/arrow-20261019-093000-pre-migration.db"))
+        );
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2026, 10, 19)
+                .unwrap()
+                .and_hms_milli_opt(9, 30, 0, 250),
+            Backup::from_path(PathBuf::from("/b/arrow-20261019-093000250-scheduled.db"))
+                .map(|b| b.taken)
+        );
+        assert_eq!(None, Backup::from_path(PathBuf::from("/b/notes.txt")));
+    }
+
+    #[test]
+    fn take_never_overwrites_a_backup_taken_in_the_same_millisecond() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("arrow-backups-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        let now = NaiveDate::from_ymd_opt(2026, 10, 19)
+            .unwrap()
+            .and_hms_milli_opt(9, 30, 0, 250)
+            .unwrap();
+
+        // Act
+        let first = take(&conn, &dir, Kind::Scheduled, now).unwrap();
+        let second = take(&conn, &dir, Kind::Scheduled, now).unwrap();
+        let backups = list(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Assert
+        assert_ne!(first, second);
+        assert_eq!(
+            vec![now + Duration::milliseconds(1), now],
+            backups.iter().map(|b| b.taken).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn due_once_per_period() {
+        // Arrange
+        let backups = [
+            backup("arrow-20261019-090000-pre-migration.db"),
+            backup("arrow-20261016-090000-scheduled.db"),
+        ];
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let friday = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+
+        // Act
+        let daily = due(&backups, Every::Day, monday);
+        let weekly = due(&backups, Every::Week, monday);
+        let same_week = due(&backups, Every::Week, friday);
+
+        // Assert
+        assert!(daily);
+        assert!(weekly);
+        assert!(!same_week);
+        assert!(!due(&[], Every::Never, monday));
+    }
+}
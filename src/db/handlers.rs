@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use rusqlite::{Connection, OpenFlags};
+
+use crate::{
+    config::Config,
+    db::backups::{self, Kind},
+    print::table::Table,
+    repository::Sqlite,
+};
+
+/// List the automatic backups of the database, newest first.
+pub fn list() {
+    let backups = match backups::dir().map(|dir| backups::list(&dir)) {
+        Ok(Ok(backups)) => backups,
+        Ok(Err(e)) => return eprintln!("Failed to read backups: {}", e),
+        Err(e) => return eprintln!("Failed to find backups: {}", e),
+    };
+
+    let mut table = Table::new(vec![
+        "Taken".to_string(),
+        "Kind".to_string(),
+        "File".to_string(),
+    ]);
+    for backup in backups {
+        table.add_row(vec![
+            backup.taken.format("%Y-%m-%d %H:%M:%S").to_string(),
+            match backup.kind {
+                Kind::Scheduled => "scheduled",
+                Kind::Migration => "before migration",
+                Kind::Restore => "before restore",
+            }
+            .to_string(),
+            backup.path.display().to_string(),
+        ]);
+    }
+    table.print(&mut std::io::stdout());
+}
+
+/// Replace the database with a backup. The database is backed up first, so the restore can be
+/// undone by restoring that backup.
+///
+/// # Arguments:
+///
+/// * `from` - Path of the backup, or the name of a file in the backups directory
+pub fn restore(repo: &mut Sqlite, from: String) {
+    let path = match PathBuf::from(&from) {
+        path if path.is_file() => path,
+        _ => match backups::dir().map(|dir| dir.join(&from)) {
+            Ok(path) if path.is_file() => path,
+            _ => {
+                return eprintln!(
+                    "No backup named {} was found. Run `arrow db ls` to see your backups.",
+                    from
+                )
+            }
+        },
+    };
+
+    let is_arrow_db = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .and_then(|conn| {
+            conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'projects')",
+                [],
+                |row| row.get::<_, bool>(0),
+            )
+        })
+        .unwrap_or(false);
+    if !is_arrow_db {
+        return eprintln!("{} is not an arrow database.", path.display());
+    }
+
+    let keep = match Config::load() {
+        Ok(config) => config.backups.keep,
+        Err(e) => return eprintln!("Error reading config.toml: {}", e),
+    };
+    let saved = match repo.back_up(Kind::Restore, keep) {
+        Ok(saved) => saved,
+        Err(e) => return eprintln!("Failed to back up the database before restoring: {}", e),
+    };
+
+    match repo.restore_from(&path) {
+        Ok(_) => println!(
+            "Restored {}. The previous database was saved to {}",
+            path.display(),
+            saved.display()
+        ),
+        Err(e) => eprintln!("Failed to restore {}: {}", path.display(), e),
+    }
+}
@@ -0,0 +1,297 @@
+use std::{fs, path::PathBuf};
+
+use crate::{
+    client,
+    clock::Clock,
+    formats::{
+        csv::{self, Entry, Preset, Zone},
+        ics::{self, Event},
+        org::{self, Section, Task},
+        timeclock,
+    },
+    model::{Client, Log, Project},
+    project::tree,
+    report::handlers::report_range,
+    repository::Repository,
+};
+
+/// Logs chosen for export, as given on the command line
+#[derive(Default)]
+pub struct Selection {
+    /// Optional first day, defaults to the first log
+    pub from: Option<String>,
+    /// Optional last day, defaults to today
+    pub to: Option<String>,
+    /// Optional reference to the only client to include
+    pub client: Option<String>,
+}
+
+/// Logs to export with the projects and clients they belong to
+struct Export {
+    logs: Vec<Log>,
+    projects: Vec<Project>,
+    clients: Vec<Client>,
+}
+
+impl Export {
+    fn project(&self, log: &Log) -> Option<&Project> {
+        self.projects.iter().find(|p| p.id == log.proj_id)
+    }
+
+    /// Path of the log's project, e.g. `acme/api`
+    fn project_path(&self, log: &Log) -> String {
+        self.project(log)
+            .map(|p| tree::path(p, &self.projects))
+            .unwrap_or_default()
+    }
+
+    /// Ledger account for the log's project, e.g. `acme:api:auth` for `acme/api/auth` billed to
+    /// a client called acme
+    fn account(&self, log: &Log) -> String {
+        let path = self.project_path(log).replace('/', ":");
+        match self.client(log) {
+            Some(client) => format!("{}:{}", client.name, path),
+            None => path,
+        }
+    }
+
+    /// Client the log's project, or one of its parents, is billed to
+    fn client(&self, log: &Log) -> Option<&Client> {
+        self.project(log)
+            .and_then(|p| tree::client_id(p, &self.projects))
+            .and_then(|id| self.clients.iter().find(|c| c.id == id))
+    }
+}
+
+/// Logs between two dates, optionally only those billed to a client, with the projects and
+/// clients they belong to.
+///
+/// # Arguments
+///
+/// * `repo` - Repository to read from
+/// * `clock` - Clock to tell the time by
+/// * `selection` - Days and client to export
+fn logs_to_export(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    selection: Selection,
+) -> Result<Export, String> {
+    let (from, to) = report_range(selection.from, selection.to, clock.now())?;
+
+    let client_id = match selection.client {
+        Some(reference) => Some(
+            client::resolve::resolve(repo, &reference)
+                .map_err(|e| e.to_string())?
+                .id,
+        ),
+        None => None,
+    };
+
+    let projects = repo
+        .all_projects()
+        .map_err(|e| format!("Error retrieving your projects: {}", e))?;
+    let clients = repo
+        .all_clients()
+        .map_err(|e| format!("Error retrieving your clients: {}", e))?;
+    let mut logs = repo
+        .logs_between(&from, &to)
+        .map_err(|e| format!("Error retrieving logs: {}", e))?;
+
+    if client_id.is_some() {
+        logs.retain(|log| {
+            projects
+                .iter()
+                .find(|p| p.id == log.proj_id)
+                .and_then(|p| tree::client_id(p, &projects))
+                == client_id
+        });
+    }
+
+    Ok(Export {
+        logs,
+        projects,
+        clients,
+    })
+}
+
+/// Writes an export to a file, or stdout without one.
+fn output(exported: &str, path: Option<PathBuf>) {
+    match path {
+        Some(path) => match fs::write(&path, exported) {
+            Ok(_) => println!("Export written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write export to {}: {}", path.display(), e),
+        },
+        None => print!("{}", exported),
+    }
+}
+
+/// Export logs as CSV in the layout of Toggl, Clockify or a generic one. Projects are
+/// written as paths, e.g. `acme/api`, so importing the file again finds the same projects.
+///
+/// # Arguments:
+///
+/// * `preset` - Layout to write
+/// * `selection` - Days and client to export
+/// * `timezone` - Optional time zone to write times in, defaults to the local time zone
+/// * `path` - Optional file to write to instead of stdout
+pub fn csv(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    preset: Preset,
+    selection: Selection,
+    timezone: Option<String>,
+    path: Option<PathBuf>,
+) {
+    let zone = match timezone.map(|tz| Zone::parse(&tz)).transpose() {
+        Ok(zone) => zone.unwrap_or(Zone::Local),
+        Err(e) => return eprintln!("{}", e),
+    };
+    let export = match logs_to_export(repo, clock, selection) {
+        Ok(export) => export,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let entries: Vec<Entry> = export
+        .logs
+        .iter()
+        .map(|log| Entry {
+            project: export.project_path(log),
+            client: export.client(log).map(|c| c.name.clone()),
+            description: log.message.clone(),
+            start: log.start,
+            end: log.end,
+            billable: log.billable,
+            tags: log.tags.clone(),
+        })
+        .collect();
+
+    match csv::write(&entries, &preset.layout(), zone) {
+        Ok(exported) => output(&exported, path),
+        Err(e) => eprintln!("Failed to write CSV: {}", e),
+    }
+}
+
+/// Export logs as iCalendar events, to show tracked time on a calendar. Each event is titled
+/// with the project's path, described by the log message and keeps the same UID when exported
+/// again, so calendars update events instead of duplicating them.
+///
+/// # Arguments:
+///
+/// * `selection` - Days and client to export
+/// * `path` - Optional file to write to instead of stdout
+pub fn ics(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    selection: Selection,
+    path: Option<PathBuf>,
+) {
+    let export = match logs_to_export(repo, clock, selection) {
+        Ok(export) => export,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let events: Vec<Event> = export
+        .logs
+        .iter()
+        .map(|log| Event {
+            uid: format!("log-{}@arrow", log.id),
+            summary: export.project_path(log),
+            description: Some(log.message.clone()),
+            categories: log.tags.clone(),
+            start: log.start,
+            end: log.end,
+        })
+        .collect();
+
+    output(&ics::write(&events, clock.now()), path);
+}
+
+/// Export logs as a timeclock file for ledger and hledger. Accounts are the client followed by
+/// the project's path, e.g. `acme:api:auth`, and importing the file again finds the same
+/// projects.
+///
+/// # Arguments:
+///
+/// * `selection` - Days and client to export
+/// * `path` - Optional file to write to instead of stdout
+pub fn timeclock(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    selection: Selection,
+    path: Option<PathBuf>,
+) {
+    let export = match logs_to_export(repo, clock, selection) {
+        Ok(export) => export,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let entries: Vec<timeclock::Entry> = export
+        .logs
+        .iter()
+        .map(|log| timeclock::Entry {
+            account: export.account(log),
+            description: log.message.clone(),
+            tags: log.tags.clone(),
+            billable: log.billable,
+            start: log.start,
+            end: log.end,
+        })
+        .collect();
+
+    output(&timeclock::write(&entries), path);
+}
+
+/// Export logs as an org mode file, with a heading per project, a sub-heading per message and
+/// the time logged as `CLOCK` entries.
+///
+/// # Arguments:
+///
+/// * `selection` - Days and client to export
+/// * `path` - Optional file to write to instead of stdout
+pub fn org(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    selection: Selection,
+    path: Option<PathBuf>,
+) {
+    let export = match logs_to_export(repo, clock, selection) {
+        Ok(export) => export,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let mut sections: Vec<Section> = Vec::new();
+    for log in &export.logs {
+        let title = export.project_path(log);
+        let section = match sections.iter().position(|s| s.title == title) {
+            Some(i) => &mut sections[i],
+            None => {
+                sections.push(Section {
+                    title,
+                    tasks: Vec::new(),
+                });
+                sections.last_mut().expect("Section was just added")
+            }
+        };
+
+        let task = match section.tasks.iter().position(|t| t.title == log.message) {
+            Some(i) => &mut section.tasks[i],
+            None => {
+                section.tasks.push(Task {
+                    title: log.message.clone(),
+                    tags: Vec::new(),
+                    clocks: Vec::new(),
+                });
+                section.tasks.last_mut().expect("Task was just added")
+            }
+        };
+        for tag in &log.tags {
+            if !task.tags.contains(tag) {
+                task.tags.push(tag.clone());
+            }
+        }
+        task.clocks.push((log.start, log.end));
+    }
+    sections.sort_by(|a, b| a.title.cmp(&b.title));
+
+    output(&org::write(&sections), path);
+}
@@ -0,0 +1,505 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+
+use crate::time::from_local;
+
+/// Column layouts of the CSV files other time trackers export
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Preset {
+    /// Toggl Track detailed report
+    Toggl,
+    /// Clockify detailed report
+    Clockify,
+    /// One column per log field, with full timestamps
+    Generic,
+}
+
+/// Which column holds each field of an entry, by header. Unset fields are left out.
+pub struct Layout {
+    pub project: Option<String>,
+    pub client: Option<String>,
+    pub description: Option<String>,
+    /// Start date, or the full start when there is no `start_time` column
+    pub start: Option<String>,
+    pub start_time: Option<String>,
+    /// End date, or the full end when there is no `end_time` column
+    pub end: Option<String>,
+    pub end_time: Option<String>,
+    /// Duration as `HH:MM:SS`. Used when an entry has no end
+    pub duration: Option<String>,
+    /// Duration in decimal hours, only written
+    pub hours: Option<String>,
+    pub billable: Option<String>,
+    /// Comma separated tags
+    pub tags: Option<String>,
+    /// Format of the `start` and `end` columns
+    pub date_format: String,
+    /// Format of the `start_time` and `end_time` columns
+    pub time_format: String,
+    /// Every column written on export, in order. Columns without a field are left blank
+    pub headers: Vec<String>,
+}
+
+impl Preset {
+    pub fn layout(&self) -> Layout {
+        let column = |header: &str| Some(header.to_owned());
+        let headers = |headers: &[&str]| headers.iter().map(|h| h.to_string()).collect();
+
+        match self {
+            Preset::Toggl => Layout {
+                project: column("Project"),
+                client: column("Client"),
+                description: column("Description"),
+                start: column("Start date"),
+                start_time: column("Start time"),
+                end: column("End date"),
+                end_time: column("End time"),
+                duration: column("Duration"),
+                hours: None,
+                billable: column("Billable"),
+                tags: column("Tags"),
+                date_format: "%Y-%m-%d".to_owned(),
+                time_format: "%H:%M:%S".to_owned(),
+                headers: headers(&[
+                    "User",
+                    "Email",
+                    "Client",
+                    "Project",
+                    "Task",
+                    "Description",
+                    "Billable",
+                    "Start date",
+                    "Start time",
+                    "End date",
+                    "End time",
+                    "Duration",
+                    "Tags",
+                ]),
+            },
+            Preset::Clockify => Layout {
+                project: column("Project"),
+                client: column("Client"),
+                description: column("Description"),
+                start: column("Start Date"),
+                start_time: column("Start Time"),
+                end: column("End Date"),
+                end_time: column("End Time"),
+                duration: column("Duration (h)"),
+                hours: column("Duration (decimal)"),
+                billable: column("Billable"),
+                tags: column("Tags"),
+                date_format: "%m/%d/%Y".to_owned(),
+                time_format: "%I:%M:%S %p".to_owned(),
+                headers: headers(&[
+                    "Project",
+                    "Client",
+                    "Description",
+                    "Task",
+                    "User",
+                    "Group",
+                    "Email",
+                    "Tags",
+                    "Billable",
+                    "Start Date",
+                    "Start Time",
+                    "End Date",
+                    "End Time",
+                    "Duration (h)",
+                    "Duration (decimal)",
+                ]),
+            },
+            Preset::Generic => Layout {
+                project: column("project"),
+                client: column("client"),
+                description: column("description"),
+                start: column("start"),
+                start_time: None,
+                end: column("end"),
+                end_time: None,
+                duration: None,
+                hours: None,
+                billable: column("billable"),
+                tags: column("tags"),
+                date_format: "%Y-%m-%d %H:%M:%S".to_owned(),
+                time_format: "%H:%M:%S".to_owned(),
+                headers: headers(&[
+                    "project",
+                    "client",
+                    "description",
+                    "start",
+                    "end",
+                    "billable",
+                    "tags",
+                ]),
+            },
+        }
+    }
+}
+
+impl Layout {
+    /// Reads a field from another column, given as `field=Header`, e.g. `description=Task`.
+    pub fn map_column(&mut self, mapping: &str) -> Result<(), String> {
+        let (field, header) = mapping.split_once('=').ok_or_else(|| {
+            format!(
+                "Invalid column mapping \"{}\". Mappings should look like description=Task",
+                mapping
+            )
+        })?;
+
+        let column = match field {
+            "project" => &mut self.project,
+            "client" => &mut self.client,
+            "description" => &mut self.description,
+            "start" => &mut self.start,
+            "start_time" => &mut self.start_time,
+            "end" => &mut self.end,
+            "end_time" => &mut self.end_time,
+            "duration" => &mut self.duration,
+            "billable" => &mut self.billable,
+            "tags" => &mut self.tags,
+            _ => {
+                return Err(format!(
+                    "Unknown field \"{}\". Fields are project, client, description, start, start_time, end, end_time, duration, billable and tags",
+                    field
+                ))
+            }
+        };
+        *column = Some(header.to_owned());
+
+        Ok(())
+    }
+}
+
+/// Time zone that the dates and times in a file are written in
+#[derive(Clone, Copy)]
+pub enum Zone {
+    Local,
+    Named(Tz),
+}
+
+impl Zone {
+    /// Reads an IANA time zone name, e.g. `Europe/Berlin` or `UTC`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        name.parse::<Tz>()
+            .map(Zone::Named)
+            .map_err(|_| format!("Unknown time zone \"{}\", e.g. Europe/Berlin", name))
+    }
+
+    fn localize(self, naive: NaiveDateTime) -> Option<DateTime<Local>> {
+        match self {
+            Zone::Local => from_local(&naive),
+            Zone::Named(tz) => tz
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|t| t.with_timezone(&Local)),
+        }
+    }
+
+    fn in_zone(self, time: DateTime<Local>) -> NaiveDateTime {
+        match self {
+            Zone::Local => time.naive_local(),
+            Zone::Named(tz) => time.with_timezone(&tz).naive_local(),
+        }
+    }
+}
+
+/// A time entry read from, or written to, a CSV file
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    /// Project name or path, e.g. `acme/api`
+    pub project: String,
+    pub client: Option<String>,
+    pub description: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub billable: bool,
+    pub tags: Vec<String>,
+}
+
+/// Reads the entries in a CSV file. Errors name the row they were found on, counting the
+/// header as row 1.
+///
+/// # Arguments
+///
+/// * `text` - Contents of the file
+/// * `layout` - Columns holding each field
+/// * `zone` - Time zone the file's dates and times are in
+pub fn read(text: &str, layout: &Layout, zone: Zone) -> Result<Vec<Entry>, String> {
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+
+    let index = |column: &Option<String>| {
+        column
+            .as_ref()
+            .and_then(|name| headers.iter().position(|h| h.trim() == name))
+    };
+
+    let start = index(&layout.start).ok_or(format!(
+        "Missing start column \"{}\"",
+        layout.start.as_deref().unwrap_or_default()
+    ))?;
+    let start_time = index(&layout.start_time);
+    let project = index(&layout.project);
+    let client = index(&layout.client);
+    let description = index(&layout.description);
+    let end = index(&layout.end);
+    let end_time = index(&layout.end_time);
+    let duration = index(&layout.duration);
+    let billable = index(&layout.billable);
+    let tags = index(&layout.tags);
+
+    let mut entries = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let row = i + 2;
+        let record = record.map_err(|e| format!("row {}: {}", row, e))?;
+        let field = |idx: Option<usize>| {
+            idx.and_then(|i| record.get(i))
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+        };
+
+        let moment = |date: Option<&str>, time: Option<&str>| {
+            date.map(|date| parse_moment(date, time, layout, zone))
+                .transpose()
+                .map_err(|e| format!("row {}: {}", row, e))
+        };
+
+        let start = moment(field(Some(start)), field(start_time))?
+            .ok_or(format!("row {}: missing start", row))?;
+        let end = match (moment(field(end), field(end_time))?, field(duration)) {
+            (Some(end), _) => end,
+            (None, Some(duration)) => {
+                start
+                    + parse_duration(duration)
+                        .ok_or(format!("row {}: invalid duration \"{}\"", row, duration))?
+            }
+            (None, None) => return Err(format!("row {}: missing end or duration", row)),
+        };
+        if end < start {
+            return Err(format!("row {}: ends before it starts", row));
+        }
+
+        entries.push(Entry {
+            project: field(project).unwrap_or_default().to_owned(),
+            client: field(client).map(|c| c.to_owned()),
+            description: field(description).unwrap_or_default().to_owned(),
+            start,
+            end,
+            billable: match field(billable) {
+                Some(value) => parse_bool(value)
+                    .ok_or(format!("row {}: invalid billable value \"{}\"", row, value))?,
+                None => true,
+            },
+            tags: field(tags)
+                .map(|t| {
+                    t.split(',')
+                        .map(|t| t.trim().to_owned())
+                        .filter(|t| !t.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        });
+    }
+
+    Ok(entries)
+}
+
+fn parse_moment(
+    date: &str,
+    time: Option<&str>,
+    layout: &Layout,
+    zone: Zone,
+) -> Result<DateTime<Local>, String> {
+    let naive = match time {
+        Some(time) => NaiveDate::parse_from_str(date, &layout.date_format)
+            .and_then(|d| {
+                NaiveTime::parse_from_str(time, &layout.time_format).map(|t| d.and_time(t))
+            })
+            .map_err(|_| format!("invalid date and time \"{} {}\"", date, time))?,
+        None => NaiveDateTime::parse_from_str(date, &layout.date_format)
+            .map_err(|_| format!("invalid date and time \"{}\"", date))?,
+    };
+
+    zone.localize(naive)
+        .ok_or(format!("\"{}\" does not exist in the time zone", naive))
+}
+
+/// Reads `HH:MM:SS` or `HH:MM` durations.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let parts: Vec<i64> = text
+        .split(':')
+        .map(|p| p.parse::<i64>().ok().filter(|n| *n >= 0))
+        .collect::<Option<_>>()?;
+
+    match parts[..] {
+        [h, m, s] => Some(Duration::seconds(h * 3600 + m * 60 + s)),
+        [h, m] => Some(Duration::seconds(h * 3600 + m * 60)),
+        _ => None,
+    }
+}
+
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.to_lowercase().as_str() {
+        "yes" | "true" | "1" => Some(true),
+        "no" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Writes entries as CSV in the given layout.
+///
+/// # Arguments
+///
+/// * `entries` - Entries to write, one row each
+/// * `layout` - Columns to write and the field each one holds
+/// * `zone` - Time zone to write dates and times in
+pub fn write(entries: &[Entry], layout: &Layout, zone: Zone) -> Result<String, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&layout.headers)?;
+
+    for entry in entries {
+        let start = zone.in_zone(entry.start);
+        let end = zone.in_zone(entry.end);
+        let seconds = (entry.end - entry.start).num_seconds();
+
+        let value = |header: &String| -> String {
+            let is = |column: &Option<String>| column.as_ref() == Some(header);
+
+            if is(&layout.project) {
+                entry.project.clone()
+            } else if is(&layout.client) {
+                entry.client.clone().unwrap_or_default()
+            } else if is(&layout.description) {
+                entry.description.clone()
+            } else if is(&layout.start) {
+                start.format(&layout.date_format).to_string()
+            } else if is(&layout.start_time) {
+                start.format(&layout.time_format).to_string()
+            } else if is(&layout.end) {
+                end.format(&layout.date_format).to_string()
+            } else if is(&layout.end_time) {
+                end.format(&layout.time_format).to_string()
+            } else if is(&layout.duration) {
+                format!(
+                    "{:02}:{:02}:{:02}",
+                    seconds / 3600,
+                    seconds % 3600 / 60,
+                    seconds % 60
+                )
+            } else if is(&layout.hours) {
+                format!("{:.2}", seconds as f64 / 3600.0)
+            } else if is(&layout.billable) {
+                if entry.billable { "Yes" } else { "No" }.to_owned()
+            } else if is(&layout.tags) {
+                entry.tags.join(", ")
+            } else {
+                String::new()
+            }
+        };
+
+        writer.write_record(layout.headers.iter().map(value))?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.into_error())?;
+    Ok(String::from_utf8(bytes).expect("CSV written from strings is UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc() -> Zone {
+        Zone::parse("UTC").unwrap()
+    }
+
+    #[test]
+    fn read_toggl_combines_dates_and_times() {
+        // Arrange
+        let text = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\n\
+            Ann,ann@example.com,Acme,api,,Fix login,No,2026-01-01,09:00:00,2026-01-01,10:30:00,01:30:00,\"bug, urgent\"\n";
+
+        // Act
+        let res = read(text, &Preset::Toggl.layout(), utc()).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![Entry {
+                project: "api".to_owned(),
+                client: Some("Acme".to_owned()),
+                description: "Fix login".to_owned(),
+                start: Local.timestamp_opt(1767258000, 0).unwrap(),
+                end: Local.timestamp_opt(1767263400, 0).unwrap(),
+                billable: false,
+                tags: vec!["bug".to_owned(), "urgent".to_owned()],
+            }],
+            res
+        );
+    }
+
+    #[test]
+    fn read_applies_time_zone_and_duration() {
+        // Arrange
+        let text = "Project,Description,Start Date,Start Time,Duration (h)\n\
+            api,Review,01/01/2026,09:00:00 AM,00:45:00\n";
+
+        // Act
+        let res = read(
+            text,
+            &Preset::Clockify.layout(),
+            Zone::parse("Australia/Sydney").unwrap(),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(1767218400, res[0].start.timestamp());
+        assert_eq!(45 * 60, (res[0].end - res[0].start).num_seconds());
+    }
+
+    #[test]
+    fn read_with_mapped_column() {
+        // Arrange
+        let mut layout = Preset::Generic.layout();
+        layout.map_column("description=Task").unwrap();
+        let text = "project,Task,start,end\napi,Deploy,2026-01-01 09:00:00,2026-01-01 09:10:00\n";
+
+        // Act
+        let res = read(text, &layout, utc()).unwrap();
+
+        // Assert
+        assert_eq!("Deploy", res[0].description);
+        assert!(res[0].billable);
+    }
+
+    #[test]
+    fn read_rejects_entry_ending_before_start() {
+        let text = "project,start,end\napi,2026-01-01 09:00:00,2026-01-01 08:00:00\n";
+
+        let res = read(text, &Preset::Generic.layout(), utc());
+
+        assert_eq!(Err("row 2: ends before it starts".to_owned()), res);
+    }
+
+    #[test]
+    fn write_then_read_round_trips() {
+        // Arrange
+        let entries = vec![Entry {
+            project: "acme/api".to_owned(),
+            client: Some("Acme".to_owned()),
+            description: "Fix, then deploy".to_owned(),
+            start: Local.timestamp_opt(1767258000, 0).unwrap(),
+            end: Local.timestamp_opt(1767263400, 0).unwrap(),
+            billable: true,
+            tags: vec!["bug".to_owned()],
+        }];
+
+        for preset in [Preset::Toggl, Preset::Clockify, Preset::Generic] {
+            // Act
+            let text = write(&entries, &preset.layout(), utc()).unwrap();
+            let res = read(&text, &preset.layout(), utc()).unwrap();
+
+            // Assert
+            assert_eq!(entries, res);
+        }
+    }
+}
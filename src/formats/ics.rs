@@ -0,0 +1,375 @@
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::time::from_local;
+
+/// A calendar event with a start and end time
+#[derive(Debug, PartialEq)]
+pub struct Event {
+    /// Identifies the event, and stays the same when it is changed
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub categories: Vec<String>,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+/// Writes events as an iCalendar (RFC 5545) file, with times in UTC.
+///
+/// # Arguments
+///
+/// * `events` - Events to write
+/// * `now` - When the file was created, written as each event's `DTSTAMP`
+pub fn write(events: &[Event], now: DateTime<Local>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//arrow//arrow//EN".to_owned(),
+    ];
+
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_owned());
+        lines.push(format!("UID:{}", escape(&event.uid)));
+        lines.push(format!("DTSTAMP:{}", timestamp(now)));
+        lines.push(format!("DTSTART:{}", timestamp(event.start)));
+        lines.push(format!("DTEND:{}", timestamp(event.end)));
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape(description)));
+        }
+        if !event.categories.is_empty() {
+            let categories: Vec<String> = event.categories.iter().map(|c| escape(c)).collect();
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+        lines.push("END:VEVENT".to_owned());
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines.iter().map(|l| fold(l) + "\r\n").collect()
+}
+
+fn timestamp(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Splits lines longer than 75 bytes, continuing them on lines starting with a space.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+/// A content line, e.g. `DTSTART;TZID=Europe/Berlin:20260101T090000`
+struct Property<'a> {
+    name: String,
+    params: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.trim_matches('"'))
+    }
+}
+
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    // the value starts at the first colon outside of a quoted parameter
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+
+    let mut parts = line[..colon].split(';');
+    let name = parts.next()?.to_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(n, v)| (n.to_uppercase(), v))
+        .collect();
+
+    Some(Property {
+        name,
+        params,
+        value: &line[colon + 1..],
+    })
+}
+
+/// Reads the events in an iCalendar file. All-day events have no time worked and are skipped.
+/// Repeating events are not expanded, only their first occurrence and any changed occurrences
+/// are read. Errors name the event they were found in.
+pub fn read(text: &str) -> Result<Vec<Event>, String> {
+    let unfolded = text
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+
+    let mut events = Vec::new();
+    let mut event: Option<Vec<Property>> = None;
+    let mut nested = 0;
+
+    for line in unfolded.lines().filter(|l| !l.trim().is_empty()) {
+        let Some(prop) = parse_property(line) else {
+            continue;
+        };
+
+        match (prop.name.as_str(), prop.value.to_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => event = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                if let Some(props) = event.take() {
+                    events.extend(to_event(&props)?);
+                }
+            }
+            // alarms and other components nested inside an event
+            ("BEGIN", _) if event.is_some() => nested += 1,
+            ("END", _) if event.is_some() => nested -= 1,
+            _ => {
+                if let Some(props) = event.as_mut().filter(|_| nested == 0) {
+                    props.push(prop);
+                }
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+fn to_event(props: &[Property]) -> Result<Option<Event>, String> {
+    let find = |name: &str| props.iter().find(|p| p.name == name);
+    let text = |name: &str| find(name).map(|p| unescape(p.value));
+
+    let uid = text("UID").unwrap_or_default();
+    let err = |e: String| format!("event {}: {}", uid, e);
+
+    let Some(start) = find("DTSTART") else {
+        return Err(err("missing DTSTART".to_owned()));
+    };
+    let Some(start) = parse_time(start).map_err(err)? else {
+        return Ok(None);
+    };
+    let end = match (find("DTEND"), find("DURATION")) {
+        (Some(end), _) => parse_time(end).map_err(err)?.unwrap_or(start),
+        (None, Some(duration)) => {
+            start
+                + parse_duration(duration.value)
+                    .ok_or_else(|| err(format!("invalid duration \"{}\"", duration.value)))?
+        }
+        (None, None) => start,
+    };
+    if end < start {
+        return Err(err("ends before it starts".to_owned()));
+    }
+
+    Ok(Some(Event {
+        uid: match find("RECURRENCE-ID") {
+            Some(recurrence) => format!("{} {}", uid, recurrence.value),
+            None => uid.clone(),
+        },
+        summary: text("SUMMARY").unwrap_or_default(),
+        description: text("DESCRIPTION").filter(|d| !d.is_empty()),
+        categories: props
+            .iter()
+            .filter(|p| p.name == "CATEGORIES")
+            .flat_map(|p| split_list(p.value))
+            .collect(),
+        start,
+        end,
+    }))
+}
+
+/// Reads a date-time in UTC, in the zone named by `TZID`, or in local time. `None` for dates
+/// without a time.
+fn parse_time(prop: &Property) -> Result<Option<DateTime<Local>>, String> {
+    if prop.param("VALUE") == Some("DATE") || prop.value.len() == 8 {
+        return Ok(None);
+    }
+
+    let invalid = || format!("invalid date and time \"{}\"", prop.value);
+    if let Some(utc) = prop.value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+        return Ok(Some(Utc.from_utc_datetime(&naive).with_timezone(&Local)));
+    }
+
+    let naive =
+        NaiveDateTime::parse_from_str(prop.value, "%Y%m%dT%H%M%S").map_err(|_| invalid())?;
+    let time = match prop.param("TZID") {
+        Some(name) => {
+            let tz: Tz = name
+                .parse()
+                .map_err(|_| format!("unknown time zone \"{}\"", name))?;
+            tz.from_local_datetime(&naive)
+                .earliest()
+                .map(|t| t.with_timezone(&Local))
+        }
+        None => from_local(&naive),
+    };
+
+    time.map(Some).ok_or_else(invalid)
+}
+
+/// Reads durations such as `PT1H30M` or `P1DT2H`.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.strip_prefix('+').unwrap_or(text).strip_prefix('P')?;
+
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        let unit = match c {
+            '0'..='9' => {
+                number.push(c);
+                continue;
+            }
+            'T' => continue,
+            'W' => 7 * 86400,
+            'D' => 86400,
+            'H' => 3600,
+            'M' => 60,
+            'S' => 1,
+            _ => return None,
+        };
+        seconds += number.parse::<i64>().ok()? * unit;
+        number.clear();
+    }
+
+    Some(Duration::seconds(seconds))
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') | Some('N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => {}
+            },
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Splits a comma separated list of text values, leaving escaped commas in place.
+fn split_list(text: &str) -> Vec<String> {
+    let mut items = vec![String::new()];
+    let mut escaped = false;
+    for c in text.chars() {
+        match c {
+            ',' if !escaped => items.push(String::new()),
+            c => items.last_mut().expect("List has an item").push(c),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+
+    items
+        .iter()
+        .map(|item| unescape(item.trim()))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: i64) -> DateTime<Local> {
+        Local.timestamp_opt(timestamp, 0).unwrap()
+    }
+
+    #[test]
+    fn write_then_read_round_trips() {
+        // Arrange
+        let events = vec![Event {
+            uid: "log-1@arrow".to_owned(),
+            summary: "acme/api".to_owned(),
+            description: Some(
+                "Fixed login; then deployed, and wrote a very long description that needs folding"
+                    .to_owned(),
+            ),
+            categories: vec!["bug".to_owned(), "a, b".to_owned()],
+            start: at(1767258000),
+            end: at(1767263400),
+        }];
+
+        // Act
+        let text = write(&events, at(0));
+        let res = read(&text).unwrap();
+
+        // Assert
+        assert!(text.lines().all(|l| l.len() <= 76));
+        assert_eq!(events, res);
+    }
+
+    #[test]
+    fn read_handles_time_zones_durations_and_alarms() {
+        // Arrange
+        let text = "BEGIN:VCALENDAR\n\
+            BEGIN:VEVENT\n\
+            UID:standup\n\
+            DTSTART;TZID=Australia/Sydney:20260101T090000\n\
+            DURATION:PT15M\n\
+            SUMMARY:Standup\n\
+            CATEGORIES:Meeting\n\
+            BEGIN:VALARM\n\
+            DESCRIPTION:Reminder\n\
+            END:VALARM\n\
+            END:VEVENT\n\
+            BEGIN:VEVENT\n\
+            UID:holiday\n\
+            DTSTART;VALUE=DATE:20260101\n\
+            SUMMARY:New Year\n\
+            END:VEVENT\n\
+            END:VCALENDAR\n";
+
+        // Act
+        let res = read(text).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![Event {
+                uid: "standup".to_owned(),
+                summary: "Standup".to_owned(),
+                description: None,
+                categories: vec!["Meeting".to_owned()],
+                start: at(1767218400),
+                end: at(1767218400 + 15 * 60),
+            }],
+            res
+        );
+    }
+
+    #[test]
+    fn read_rejects_unknown_time_zone() {
+        let text = "BEGIN:VEVENT\nUID:x\nDTSTART;TZID=Mars/Olympus:20260101T090000\nEND:VEVENT\n";
+
+        assert_eq!(
+            Err("event x: unknown time zone \"Mars/Olympus\"".to_owned()),
+            read(text)
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod csv;
+pub mod ics;
+pub mod org;
+pub mod timeclock;
+pub mod timewarrior;
@@ -0,0 +1,249 @@
+use chrono::{DateTime, Local, NaiveDateTime};
+
+use crate::time::from_local;
+
+/// A top level heading, with a sub-heading for each task clocked under it
+#[derive(Debug, PartialEq)]
+pub struct Section {
+    pub title: String,
+    pub tasks: Vec<Task>,
+}
+
+/// A heading with the times clocked on it
+#[derive(Debug, PartialEq)]
+pub struct Task {
+    pub title: String,
+    pub tags: Vec<String>,
+    pub clocks: Vec<(DateTime<Local>, DateTime<Local>)>,
+}
+
+/// A closed clock entry read from an org file
+#[derive(Debug, PartialEq)]
+pub struct Clock {
+    /// Title of the top level heading the clock is under
+    pub section: String,
+    /// Title of the heading the clock belongs to
+    pub task: String,
+    pub tags: Vec<String>,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+/// Writes sections as org mode headings, each task's clocks in a `LOGBOOK` drawer.
+pub fn write(sections: &[Section]) -> String {
+    let mut text = String::new();
+    for section in sections {
+        text.push_str(&format!("* {}\n", section.title));
+
+        for task in &section.tasks {
+            let tags: Vec<String> = task.tags.iter().map(|t| tag(t)).collect();
+            if tags.is_empty() {
+                text.push_str(&format!("** {}\n", task.title));
+            } else {
+                text.push_str(&format!("** {} :{}:\n", task.title, tags.join(":")));
+            }
+
+            text.push_str(":LOGBOOK:\n");
+            for (start, end) in &task.clocks {
+                let minutes = (*end - *start).num_minutes();
+                text.push_str(&format!(
+                    "CLOCK: [{}]--[{}] => {:2}:{:02}\n",
+                    timestamp(*start),
+                    timestamp(*end),
+                    minutes / 60,
+                    minutes % 60
+                ));
+            }
+            text.push_str(":END:\n");
+        }
+    }
+    text
+}
+
+fn timestamp(time: DateTime<Local>) -> String {
+    time.format("%Y-%m-%d %a %H:%M").to_string()
+}
+
+/// Org tags can only hold letters, numbers, `_`, `@`, `#` and `%`.
+fn tag(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() || "_@#%".contains(c) => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Reads the closed clock entries in an org file, with the top level heading and the heading
+/// each one was clocked under. Clocks still running are skipped. Errors name the line they were
+/// found on.
+pub fn read(text: &str) -> Result<Vec<Clock>, String> {
+    let mut clocks = Vec::new();
+    let mut section = String::new();
+    let mut task = (String::new(), Vec::new());
+
+    for (i, line) in text.lines().enumerate() {
+        if let Some((level, title, tags)) = heading(line) {
+            if level == 1 {
+                section = title.clone();
+            }
+            task = (title, tags);
+            continue;
+        }
+
+        let Some(clock) = line.trim().strip_prefix("CLOCK:") else {
+            continue;
+        };
+        let Some((start, rest)) = clock.trim().split_once("]--[") else {
+            // still running
+            continue;
+        };
+        let end = rest.split(']').next().unwrap_or_default();
+
+        let parse = |stamp: &str| {
+            parse_timestamp(stamp.trim_start_matches('[')).ok_or(format!(
+                "line {}: invalid clock \"{}\"",
+                i + 1,
+                line.trim()
+            ))
+        };
+        let (start, end) = (parse(start)?, parse(end)?);
+        if end < start {
+            return Err(format!("line {}: clock ends before it starts", i + 1));
+        }
+
+        clocks.push(Clock {
+            section: section.clone(),
+            task: task.0.clone(),
+            tags: task.1.clone(),
+            start,
+            end,
+        });
+    }
+
+    Ok(clocks)
+}
+
+/// Level, title and tags of a heading such as `** TODO [#A] Fix login :bug:`. Keywords and
+/// priorities are left out of the title.
+fn heading(line: &str) -> Option<(usize, String, Vec<String>)> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    let rest = line[level..].strip_prefix(' ').filter(|_| level > 0)?;
+
+    let mut words: Vec<&str> = rest.split_whitespace().collect();
+    let mut tags = Vec::new();
+    if let Some(last) = words.last() {
+        if last.len() > 1 && last.starts_with(':') && last.ends_with(':') {
+            tags = last
+                .split(':')
+                .filter(|t| !t.is_empty())
+                .map(|t| t.to_owned())
+                .collect();
+            words.pop();
+        }
+    }
+    if words
+        .first()
+        .is_some_and(|w| ["TODO", "DONE", "NEXT", "WAITING", "CANCELLED"].contains(w))
+    {
+        words.remove(0);
+    }
+    if words
+        .first()
+        .is_some_and(|w| w.starts_with("[#") && w.ends_with(']'))
+    {
+        words.remove(0);
+    }
+
+    Some((level, words.join(" "), tags))
+}
+
+/// Reads `2026-10-01 Thu 09:00`, ignoring the day name.
+fn parse_timestamp(text: &str) -> Option<DateTime<Local>> {
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    let (date, time) = match parts[..] {
+        [date, _, time] | [date, time] => (date, time),
+        _ => return None,
+    };
+
+    let naive =
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M").ok()?;
+    from_local(&naive)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::time::parse_datetime;
+
+    use super::*;
+
+    #[test]
+    fn write_formats_clock_lines() {
+        // Arrange
+        let sections = vec![Section {
+            title: "acme/api".to_owned(),
+            tasks: vec![Task {
+                title: "Fix login".to_owned(),
+                tags: vec!["bug".to_owned(), "follow-up".to_owned()],
+                clocks: vec![(
+                    parse_datetime("2026-10-01 09:00").unwrap(),
+                    parse_datetime("2026-10-01 10:30").unwrap(),
+                )],
+            }],
+        }];
+
+        // Act
+        let res = write(&sections);
+
+        // Assert
+        assert_eq!(
+            "* acme/api\n\
+            ** Fix login :bug:follow_up:\n\
+            :LOGBOOK:\n\
+            CLOCK: [2026-10-01 Thu 09:00]--[2026-10-01 Thu 10:30] =>  1:30\n\
+            :END:\n",
+            res
+        );
+    }
+
+    #[test]
+    fn read_takes_section_and_task_from_headings() {
+        // Arrange
+        let text = "#+TITLE: Work\n\
+            * Website\n\
+            ** TODO [#A] Fix login :bug:\n\
+            :LOGBOOK:\n\
+            CLOCK: [2026-10-01 Thu 13:00]\n\
+            CLOCK: [2026-10-01 Thu 09:00]--[2026-10-01 Thu 10:30] =>  1:30\n\
+            :END:\n\
+            *** Notes\n\
+            CLOCK: [2026-10-02 Fri 09:00]--[2026-10-02 Fri 09:15] =>  0:15\n";
+
+        // Act
+        let res = read(text).unwrap();
+
+        // Assert
+        assert_eq!(2, res.len());
+        assert_eq!(
+            Clock {
+                section: "Website".to_owned(),
+                task: "Fix login".to_owned(),
+                tags: vec!["bug".to_owned()],
+                start: parse_datetime("2026-10-01 09:00").unwrap(),
+                end: parse_datetime("2026-10-01 10:30").unwrap(),
+            },
+            res[0]
+        );
+        assert_eq!(
+            ("Website", "Notes"),
+            (res[1].section.as_str(), res[1].task.as_str())
+        );
+    }
+
+    #[test]
+    fn read_rejects_invalid_clock() {
+        let res = read("* a\nCLOCK: [yesterday]--[2026-10-01 Thu 10:30] =>  1:30\n");
+
+        assert!(res.unwrap_err().starts_with("line 2: invalid clock"));
+    }
+}
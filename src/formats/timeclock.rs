@@ -0,0 +1,191 @@
+use chrono::{DateTime, Local, NaiveDateTime};
+
+use crate::time::from_local;
+
+/// A clocked session read from, or written to, a timeclock file
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    /// Account the time is booked to, e.g. `acme:api`
+    pub account: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub billable: bool,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+/// Writes entries in the timeclock format read by ledger and hledger, in local time. Tags and
+/// non-billable time are written as hledger tags in a comment, e.g. `; bug:, billable:no`.
+pub fn write(entries: &[Entry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        let mut comment: Vec<String> = entry.tags.iter().map(|t| format!("{}:", t)).collect();
+        if !entry.billable {
+            comment.push("billable:no".to_owned());
+        }
+
+        let mut clock_in = format!("i {} {}", timestamp(entry.start), entry.account);
+        if !entry.description.is_empty() || !comment.is_empty() {
+            clock_in.push_str(&format!("  {}", entry.description.replace('\n', " ")));
+        }
+        if !comment.is_empty() {
+            clock_in.push_str(&format!(" ; {}", comment.join(", ")));
+        }
+
+        text.push_str(clock_in.trim_end());
+        text.push('\n');
+        text.push_str(&format!("o {}\n", timestamp(entry.end)));
+    }
+    text
+}
+
+fn timestamp(time: DateTime<Local>) -> String {
+    time.format("%Y/%m/%d %H:%M:%S").to_string()
+}
+
+/// Reads the sessions in a timeclock file. Comment lines starting with `;`, `#` or `*` are
+/// skipped, as is a session still clocked in at the end of the file. Errors name the line
+/// they were found on.
+pub fn read(text: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    let mut clocked_in: Option<(DateTime<Local>, &str)> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let err = |e: String| format!("line {}: {}", i + 1, e);
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with([';', '#', '*']) {
+            continue;
+        }
+
+        let (code, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let (time, rest) = split_time(rest.trim_start()).map_err(err)?;
+
+        match (code, clocked_in) {
+            ("i" | "I", None) => clocked_in = Some((time, rest)),
+            ("i" | "I", Some(_)) => return Err(err("clocked in twice".to_owned())),
+            ("o" | "O", Some((start, session))) => {
+                if time < start {
+                    return Err(err("clocked out before clocking in".to_owned()));
+                }
+                entries.push(to_entry(session, start, time));
+                clocked_in = None;
+            }
+            ("o" | "O", None) => return Err(err("clocked out without clocking in".to_owned())),
+            _ => return Err(err(format!("unknown entry \"{}\"", code))),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Splits the date and time off the start of an entry.
+fn split_time(text: &str) -> Result<(DateTime<Local>, &str), String> {
+    let mut parts = text.splitn(3, ' ');
+    let date = parts.next().unwrap_or_default();
+    let time = parts.next().unwrap_or_default();
+    let rest = parts.next().unwrap_or_default();
+
+    let stamp = format!("{} {}", date.replace('-', "/"), time);
+    let naive = NaiveDateTime::parse_from_str(&stamp, "%Y/%m/%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(&stamp, "%Y/%m/%d %H:%M"))
+        .map_err(|_| format!("invalid date and time \"{}\"", stamp))?;
+
+    from_local(&naive).map(|t| (t, rest)).ok_or(format!(
+        "\"{}\" does not exist in the local time zone",
+        stamp
+    ))
+}
+
+/// Reads `account  description ; tag:, billable:no` from a clock in line.
+fn to_entry(session: &str, start: DateTime<Local>, end: DateTime<Local>) -> Entry {
+    let (session, comment) = session.split_once(';').unwrap_or((session, ""));
+    let (account, description) = session.split_once("  ").unwrap_or((session, ""));
+
+    let mut tags = Vec::new();
+    let mut billable = true;
+    for tag in comment.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        match tag.split_once(':') {
+            Some(("billable", value)) => billable = value.trim() != "no",
+            Some((name, _)) => tags.push(name.trim().to_owned()),
+            None => tags.push(tag.to_owned()),
+        }
+    }
+
+    Entry {
+        account: account.trim().to_owned(),
+        description: description.trim().to_owned(),
+        tags,
+        billable,
+        start,
+        end,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::time::parse_datetime;
+
+    use super::*;
+
+    #[test]
+    fn write_then_read_round_trips() {
+        // Arrange
+        let entries = vec![
+            Entry {
+                account: "acme:api".to_owned(),
+                description: "Fix login".to_owned(),
+                tags: vec!["bug".to_owned()],
+                billable: false,
+                start: parse_datetime("2026-01-01 09:00").unwrap(),
+                end: parse_datetime("2026-01-01 10:30").unwrap(),
+            },
+            Entry {
+                account: "admin".to_owned(),
+                description: String::new(),
+                tags: Vec::new(),
+                billable: true,
+                start: parse_datetime("2026-01-01 11:00").unwrap(),
+                end: parse_datetime("2026-01-01 11:15").unwrap(),
+            },
+        ];
+
+        // Act
+        let text = write(&entries);
+        let res = read(&text).unwrap();
+
+        // Assert
+        assert_eq!(
+            "i 2026/01/01 09:00:00 acme:api  Fix login ; bug:, billable:no\n\
+            o 2026/01/01 10:30:00\n\
+            i 2026/01/01 11:00:00 admin\n\
+            o 2026/01/01 11:15:00\n",
+            text
+        );
+        assert_eq!(entries, res);
+    }
+
+    #[test]
+    fn read_skips_comments_and_open_session() {
+        // Arrange
+        let text = "; hledger timeclock\n\
+            i 2026-01-02 09:00 acme:web  Review\n\
+            o 2026-01-02 09:45\n\
+            i 2026/01/02 10:00:00 acme:web\n";
+
+        // Act
+        let res = read(text).unwrap();
+
+        // Assert
+        assert_eq!(1, res.len());
+        assert_eq!("Review", res[0].description);
+        assert_eq!(45 * 60, (res[0].end - res[0].start).num_seconds());
+    }
+
+    #[test]
+    fn read_rejects_clock_out_without_clock_in() {
+        assert_eq!(
+            Err("line 1: clocked out without clocking in".to_owned()),
+            read("o 2026/01/01 10:00:00\n")
+        );
+    }
+}
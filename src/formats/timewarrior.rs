@@ -0,0 +1,157 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+
+/// A tracked interval read from a Timewarrior data file
+#[derive(Debug, PartialEq)]
+pub struct Interval {
+    /// Start as written in the file, e.g. `20260101T090000Z`. Timewarrior intervals never
+    /// overlap, so it identifies the interval
+    pub id: String,
+    pub start: DateTime<Local>,
+    /// `None` while the interval is still being tracked
+    pub end: Option<DateTime<Local>>,
+    pub tags: Vec<String>,
+    pub annotation: Option<String>,
+}
+
+/// Reads the intervals in the contents of a `data/YYYY-MM.data` file, such as
+/// `inc 20260101T090000Z - 20260101T100000Z # tag1 "tag 2" # "annotation"`. Blank lines are
+/// skipped. Errors name the line they were found on.
+pub fn parse(text: &str) -> Result<Vec<Interval>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_line(line).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+fn parse_line(line: &str) -> Result<Interval, String> {
+    let rest = line.trim().strip_prefix("inc ").ok_or_else(|| {
+        format!(
+            "expected an interval starting with \"inc\", found \"{}\"",
+            line
+        )
+    })?;
+
+    let (range, extras) = match rest.split_once(" #") {
+        Some((range, extras)) => (range, extras),
+        None => (rest, ""),
+    };
+
+    let (start, end) = match range.split_once(" - ") {
+        Some((start, end)) => (start.trim(), Some(end.trim())),
+        None => (range.trim(), None),
+    };
+
+    let (tags, annotation) = words(extras)?;
+
+    Ok(Interval {
+        id: start.to_owned(),
+        start: parse_timestamp(start)?,
+        end: end.map(parse_timestamp).transpose()?,
+        tags,
+        annotation,
+    })
+}
+
+/// Splits the tags from the annotation, which follows a second `#`. Tags with spaces are
+/// quoted, with `\"` escaping quotes inside them.
+fn words(text: &str) -> Result<(Vec<String>, Option<String>), String> {
+    let mut tags = Vec::new();
+    let mut annotation = None;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let word = match c {
+            ' ' => continue,
+            '"' => {
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => word.extend(chars.next()),
+                        Some('"') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(format!("unterminated quote in \"{}\"", text)),
+                    }
+                }
+                word
+            }
+            '#' if annotation.is_none() => {
+                annotation = Some(String::new());
+                continue;
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c == ' ' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                word
+            }
+        };
+
+        match annotation.as_mut() {
+            Some(text) if text.is_empty() => *text = word,
+            Some(text) => {
+                text.push(' ');
+                text.push_str(&word);
+            }
+            None => tags.push(word),
+        }
+    }
+
+    Ok((tags, annotation.filter(|a| !a.is_empty())))
+}
+
+fn parse_timestamp(text: &str) -> Result<DateTime<Local>, String> {
+    NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%SZ")
+        .map(|utc| Utc.from_utc_datetime(&utc).with_timezone(&Local))
+        .map_err(|_| format!("invalid timestamp \"{}\"", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_times_tags_and_annotation() {
+        // Act
+        let res = parse(
+            "inc 20260101T090000Z - 20260101T100000Z # tag1 \"tag 2\" # \"fixed the \\\"bug\\\"\"\n",
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(1, res.len());
+        assert_eq!("20260101T090000Z", res[0].id);
+        assert_eq!(1767258000, res[0].start.timestamp());
+        assert_eq!(Some(1767261600), res[0].end.map(|e| e.timestamp()));
+        assert_eq!(vec!["tag1".to_owned(), "tag 2".to_owned()], res[0].tags);
+        assert_eq!(Some("fixed the \"bug\"".to_owned()), res[0].annotation);
+    }
+
+    #[test]
+    fn parse_reads_open_and_untagged_intervals() {
+        // Act
+        let res =
+            parse("inc 20260101T090000Z - 20260101T100000Z\n\ninc 20260101T110000Z # api").unwrap();
+
+        // Assert
+        assert_eq!(2, res.len());
+        assert!(res[0].tags.is_empty());
+        assert_eq!(None, res[1].end);
+        assert_eq!(vec!["api".to_owned()], res[1].tags);
+    }
+
+    #[test]
+    fn parse_reports_line_of_bad_interval() {
+        let res = parse("inc 20260101T090000Z\ninc yesterday - today");
+
+        assert_eq!(
+            Err("line 2: invalid timestamp \"yesterday\"".to_owned()),
+            res
+        );
+    }
+}
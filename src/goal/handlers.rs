@@ -0,0 +1,151 @@
+use crate::{
+    clock::Clock,
+    goal::progress,
+    model::{Goal, GoalPeriod, GoalScope},
+    print::table::Table,
+    project::{resolve::resolve, tree},
+    repository::Repository,
+    time::{duration_hours, parse_duration, to_datetime},
+};
+
+fn period_name(period: GoalPeriod) -> &'static str {
+    match period {
+        GoalPeriod::Day => "day",
+        GoalPeriod::Weekday => "weekday",
+        GoalPeriod::Week => "week",
+    }
+}
+
+/// Set a goal for time logged every day, weekday or week. Goals without a project or tag count
+/// every log.
+///
+/// # Arguments:
+///
+/// * `target` - Time to log each period, e.g. 6h
+/// * `per` - How often the target has to be met
+/// * `project` - Optional reference to the project the goal counts time for
+/// * `tag` - Optional tag the goal counts time for
+pub fn set(
+    repo: &impl Repository,
+    target: String,
+    per: GoalPeriod,
+    project: Option<String>,
+    tag: Option<String>,
+) {
+    let seconds = match parse_duration(&target) {
+        Some(s) => s,
+        None => {
+            return eprintln!(
+                "Invalid target \"{}\". Targets should look like 6h or 90m",
+                target
+            )
+        }
+    };
+
+    let scope = match (project, tag) {
+        (Some(reference), None) => match resolve(repo, &reference) {
+            Ok(p) => GoalScope::Project(p.id),
+            Err(e) => return eprintln!("{}", e),
+        },
+        (None, Some(tag)) => GoalScope::Tag(tag),
+        (None, None) => GoalScope::Overall,
+        _ => return eprintln!("Specify at most one of --project or --tag."),
+    };
+
+    match repo.save_goal(&Goal::new(scope, seconds, per)) {
+        Ok(id) => println!(
+            "Goal {} set: {}h per {}.\n",
+            id,
+            duration_hours(seconds),
+            period_name(per)
+        ),
+        Err(e) => eprintln!("Failed to set goal: {}", e),
+    }
+}
+
+/// Show progress towards every goal in the current period, how often each goal has been met
+/// and the current streak.
+pub fn show(repo: &impl Repository, clock: &impl Clock) {
+    let now = clock.now();
+    let today = now.date_naive();
+
+    let goals = match repo.all_goals() {
+        Ok(goals) => goals,
+        Err(e) => return eprintln!("Error retrieving goals: {}", e),
+    };
+    if goals.is_empty() {
+        return println!("No goals set. Add one with `arrow goals set 6h --per weekday`.");
+    }
+    let projects = match repo.all_projects() {
+        Ok(projects) => projects,
+        Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+    };
+    let logs = match repo.logs_between(&to_datetime(0), &now) {
+        Ok(logs) => logs,
+        Err(e) => return eprintln!("Error retrieving logs: {}", e),
+    };
+
+    let mut table = Table::new(vec![
+        "ID".to_string(),
+        "Goal".to_string(),
+        "For".to_string(),
+        "Progress".to_string(),
+        "Met".to_string(),
+        "Streak".to_string(),
+    ]);
+    for goal in goals {
+        let logged = progress::logged(&goal, &logs, &projects);
+
+        let current = match progress::period_of(goal.period, today) {
+            Some(period) => {
+                let seconds = logged.get(&period).copied().unwrap_or(0);
+                format!(
+                    "{}h ({:.0}%)",
+                    duration_hours(seconds),
+                    seconds as f64 * 100.0 / goal.seconds.max(1) as f64
+                )
+            }
+            None => "weekend".to_string(),
+        };
+        let (met, total) = progress::attainment(&goal, &logged, today);
+        let scope = match &goal.scope {
+            GoalScope::Overall => "all work".to_string(),
+            GoalScope::Project(id) => match projects.iter().find(|p| p.id == *id) {
+                Some(p) => format!("project {}", tree::path(p, &projects)),
+                None => format!("project {}", id),
+            },
+            GoalScope::Tag(tag) => format!("tag {}", tag),
+        };
+
+        table.add_row(vec![
+            goal.id.to_string(),
+            format!(
+                "{}h/{}",
+                duration_hours(goal.seconds),
+                period_name(goal.period)
+            ),
+            scope,
+            current,
+            format!("{} of {}", met, total),
+            format!(
+                "{} {}(s)",
+                progress::streak(&goal, &logged, today),
+                period_name(goal.period)
+            ),
+        ]);
+    }
+    table.print(&mut std::io::stdout());
+}
+
+/// Delete a goal by ID.
+///
+/// # Arguments:
+///
+/// * `id` - ID of the goal to delete
+pub fn remove(repo: &impl Repository, id: u32) {
+    match repo.delete_goal(&id) {
+        Ok(0) => eprintln!("No goal with ID {} was found. Is it the right ID?", id),
+        Ok(_) => println!("Deleted goal {}", id),
+        Err(e) => eprintln!("Failed to delete goal: {}", e),
+    }
+}
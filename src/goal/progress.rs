@@ -0,0 +1,219 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::{
+    model::{Goal, GoalPeriod, GoalScope, Log, Project},
+    project::tree,
+};
+
+/// First day of the period a date falls in. Weekends are not part of any weekday period.
+pub fn period_of(period: GoalPeriod, date: NaiveDate) -> Option<NaiveDate> {
+    match period {
+        GoalPeriod::Day => Some(date),
+        GoalPeriod::Weekday => match date.weekday() {
+            Weekday::Sat | Weekday::Sun => None,
+            _ => Some(date),
+        },
+        GoalPeriod::Week => {
+            Some(date - Duration::days(date.weekday().num_days_from_monday() as i64))
+        }
+    }
+}
+
+/// First day of every period from the one containing `from` to the one containing `to`.
+pub fn periods(period: GoalPeriod, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+    let mut starts = Vec::new();
+    let mut day = from;
+
+    while day <= to {
+        if let Some(start) = period_of(period, day) {
+            if starts.last() != Some(&start) {
+                starts.push(start);
+            }
+        }
+        day += Duration::days(1);
+    }
+
+    starts
+}
+
+/// Seconds logged towards a goal, keyed by the first day of each period. Logs count towards the
+/// period they started in.
+///
+/// # Arguments
+///
+/// * `goal` - Goal to count time for
+/// * `logs` - Logs to count
+/// * `projects` - All projects, so project goals include their sub-projects
+pub fn logged(goal: &Goal, logs: &[Log], projects: &[Project]) -> BTreeMap<NaiveDate, u32> {
+    let ids = match goal.scope {
+        GoalScope::Project(id) => {
+            let mut ids = tree::descendants(id, projects);
+            ids.push(id);
+            ids
+        }
+        _ => Vec::new(),
+    };
+
+    let mut totals = BTreeMap::new();
+    for log in logs {
+        let counts = match &goal.scope {
+            GoalScope::Overall => true,
+            GoalScope::Project(_) => ids.contains(&log.proj_id),
+            GoalScope::Tag(tag) => log.tags.contains(tag),
+        };
+        if !counts {
+            continue;
+        }
+
+        if let Some(start) = period_of(goal.period, log.start.date_naive()) {
+            *totals.entry(start).or_default() += log.seconds();
+        }
+    }
+
+    totals
+}
+
+/// How many completed periods met the goal, out of every completed period since the first one
+/// with time logged towards it.
+pub fn attainment(
+    goal: &Goal,
+    logged: &BTreeMap<NaiveDate, u32>,
+    today: NaiveDate,
+) -> (usize, usize) {
+    let first = match logged.keys().next() {
+        Some(first) => *first,
+        None => return (0, 0),
+    };
+    let current = period_of(goal.period, today);
+
+    let completed: Vec<NaiveDate> = periods(goal.period, first, today)
+        .into_iter()
+        .filter(|p| Some(*p) != current)
+        .collect();
+    let met = completed
+        .iter()
+        .filter(|p| logged.get(p).copied().unwrap_or(0) >= goal.seconds)
+        .count();
+
+    (met, completed.len())
+}
+
+/// Number of periods in a row the goal has been met. The current period only counts once it
+/// has been met, until then the streak carries on from the previous period.
+pub fn streak(goal: &Goal, logged: &BTreeMap<NaiveDate, u32>, today: NaiveDate) -> usize {
+    let first = match logged.keys().next() {
+        Some(first) => *first,
+        None => return 0,
+    };
+    let current = period_of(goal.period, today);
+    let met = |p: &NaiveDate| logged.get(p).copied().unwrap_or(0) >= goal.seconds;
+
+    periods(goal.period, first, today)
+        .iter()
+        .rev()
+        .skip_while(|p| Some(**p) == current && !met(p))
+        .take_while(|p| met(p))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::time::parse_datetime;
+
+    use super::*;
+
+    fn date(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    fn log(proj_id: u32, start: &str, minutes: i64, tags: &[&str]) -> Log {
+        let start = parse_datetime(start).unwrap();
+        let mut log = Log::new(
+            0,
+            proj_id,
+            "work".to_owned(),
+            start,
+            start + Duration::minutes(minutes),
+        );
+        log.tags = tags.iter().map(|t| t.to_string()).collect();
+        log
+    }
+
+    fn hours(days: &[(&str, u32)]) -> BTreeMap<NaiveDate, u32> {
+        days.iter().map(|(d, h)| (date(d), h * 3600)).collect()
+    }
+
+    #[test]
+    fn periods_of_weekdays_skip_weekends() {
+        // Act
+        // 2026-09-11 is a Friday
+        let res = periods(GoalPeriod::Weekday, date("2026-09-11"), date("2026-09-14"));
+
+        // Assert
+        assert_eq!(vec![date("2026-09-11"), date("2026-09-14")], res);
+    }
+
+    #[test]
+    fn periods_of_weeks_start_on_monday() {
+        // Act
+        let res = periods(GoalPeriod::Week, date("2026-09-10"), date("2026-09-14"));
+
+        // Assert
+        assert_eq!(vec![date("2026-09-07"), date("2026-09-14")], res);
+    }
+
+    #[test]
+    fn logged_only_counts_logs_in_scope() {
+        // Arrange
+        let goal = Goal::new(GoalScope::Tag("focus".to_owned()), 3600, GoalPeriod::Day);
+        let logs = vec![
+            log(1, "2026-09-14 09:00", 60, &["focus"]),
+            log(1, "2026-09-14 11:00", 30, &[]),
+            log(2, "2026-09-15 09:00", 15, &["focus"]),
+        ];
+
+        // Act
+        let res = logged(&goal, &logs, &[]);
+
+        // Assert
+        assert_eq!(
+            BTreeMap::from([(date("2026-09-14"), 3600), (date("2026-09-15"), 900)]),
+            res
+        );
+    }
+
+    #[test]
+    fn streak_continues_until_current_period_is_missed() {
+        // Arrange
+        let goal = Goal::new(GoalScope::Overall, 6 * 3600, GoalPeriod::Weekday);
+        let logged = hours(&[
+            ("2026-09-09", 2),
+            ("2026-09-10", 6),
+            ("2026-09-11", 7),
+            ("2026-09-14", 6),
+            ("2026-09-15", 1),
+        ]);
+
+        // Act
+        let res = streak(&goal, &logged, date("2026-09-15"));
+
+        // Assert
+        assert_eq!(3, res);
+        assert_eq!(0, streak(&goal, &logged, date("2026-09-16")));
+    }
+
+    #[test]
+    fn attainment_only_counts_completed_periods() {
+        // Arrange
+        let goal = Goal::new(GoalScope::Overall, 6 * 3600, GoalPeriod::Day);
+        let logged = hours(&[("2026-09-13", 6), ("2026-09-15", 8)]);
+
+        // Act
+        let res = attainment(&goal, &logged, date("2026-09-15"));
+
+        // Assert
+        assert_eq!((1, 2), res);
+    }
+}
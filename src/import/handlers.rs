@@ -0,0 +1,842 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, Local};
+use regex::Regex;
+
+use crate::{
+    client::handlers::parse_currency,
+    clock::Clock,
+    formats::{
+        csv::{self, Preset, Zone},
+        ics, org, timeclock,
+        timewarrior::{self, Interval},
+    },
+    model::{Client, Log, Project},
+    print::table::Table,
+    project::{self, handlers::ProjectChanges, resolve::resolve, tree},
+    repository::Repository,
+    time::{duration_hours, whole_seconds},
+};
+
+/// Project an imported log belongs to
+#[derive(Debug, PartialEq)]
+enum Target {
+    Id(u32),
+    /// Project with this exact name, created if it does not exist yet
+    Name(String),
+}
+
+/// How imported intervals are assigned to projects. A tag with a mapping wins, then the fixed
+/// project, and otherwise the first tag names the project.
+#[derive(Default)]
+struct ProjectRules {
+    /// Project every entry without a mapped tag goes to
+    fixed: Option<u32>,
+    /// Tags and the project each one maps to
+    mapped: Vec<(String, u32)>,
+}
+
+impl ProjectRules {
+    /// Resolves the `--project` and `--map tag=project` options.
+    fn new(
+        repo: &impl Repository,
+        project: Option<String>,
+        maps: Vec<String>,
+    ) -> Result<Self, String> {
+        let fixed = match project {
+            Some(reference) => Some(resolve(repo, &reference).map_err(|e| e.to_string())?.id),
+            None => None,
+        };
+
+        let mut mapped = Vec::new();
+        for map in maps {
+            let (tag, reference) = map.split_once('=').ok_or_else(|| {
+                format!(
+                    "Invalid mapping \"{}\". Mappings should look like tag=project",
+                    map
+                )
+            })?;
+            let proj = resolve(repo, reference).map_err(|e| e.to_string())?;
+            mapped.push((tag.to_owned(), proj.id));
+        }
+
+        Ok(ProjectRules { fixed, mapped })
+    }
+
+    /// Project for an entry with the given tags, and the tags left to keep on the log. `None`
+    /// when nothing decides the project.
+    fn assign(&self, tags: &[String]) -> Option<(Target, Vec<String>)> {
+        let mapped = tags.iter().enumerate().find_map(|(i, tag)| {
+            self.mapped
+                .iter()
+                .find(|(t, _)| t == tag)
+                .map(|(_, id)| (i, *id))
+        });
+
+        if let Some((i, id)) = mapped {
+            let mut rest = tags.to_vec();
+            rest.remove(i);
+            return Some((Target::Id(id), rest));
+        }
+
+        if let Some(id) = self.fixed {
+            return Some((Target::Id(id), tags.to_vec()));
+        }
+
+        let (first, rest) = tags.split_first()?;
+        Some((Target::Name(first.to_owned()), rest.to_vec()))
+    }
+}
+
+/// Saves imported logs, creating the projects and clients they name, and keeps count.
+struct Importer<'a, R: Repository, C: Clock> {
+    repo: &'a R,
+    /// Clock new projects are created by
+    clock: &'a C,
+    /// Currency new clients are billed in. Imports without one only bill projects to clients
+    /// that exist
+    currency: Option<String>,
+    /// Tracker the logs come from, e.g. `timewarrior`
+    source: &'static str,
+    projects: Vec<Project>,
+    clients: Vec<Client>,
+    imported: u32,
+    skipped: u32,
+    /// Projects whose duration needs updating
+    updated: BTreeSet<u32>,
+    /// Whether the import was committed. Imports dropped before then are rolled back
+    committed: bool,
+}
+
+impl<'a, R: Repository, C: Clock> Importer<'a, R, C> {
+    /// Starts an import. Nothing it saves is kept unless it finishes.
+    fn new(repo: &'a R, clock: &'a C, source: &'static str) -> rusqlite::Result<Self> {
+        repo.begin_transaction()?;
+        Ok(Importer {
+            repo,
+            clock,
+            currency: None,
+            source,
+            projects: repo.all_projects()?,
+            clients: repo.all_clients()?,
+            imported: 0,
+            skipped: 0,
+            updated: BTreeSet::new(),
+            committed: false,
+        })
+    }
+
+    /// Creates the clients entries name if they do not exist yet, billed in the given currency
+    fn billing_in(mut self, currency: String) -> Self {
+        self.currency = Some(currency);
+        self
+    }
+
+    /// Existing project with the given path, e.g. `acme/api`, or name.
+    fn find(&self, name: &str) -> Option<u32> {
+        self.projects
+            .iter()
+            .find(|p| tree::path(p, &self.projects) == name)
+            .or_else(|| self.projects.iter().find(|p| p.name == name))
+            .map(|p| p.id)
+    }
+
+    fn path(&self, id: u32) -> String {
+        self.projects
+            .iter()
+            .find(|p| p.id == id)
+            .map(|p| tree::path(p, &self.projects))
+            .unwrap_or_default()
+    }
+
+    /// ID of the project an entry is assigned to. Projects named by an entry are created if
+    /// they do not exist yet, nested under their parents for paths like `acme/api`. New
+    /// projects that would not inherit a client are billed to the named client, which is
+    /// created too if the import bills new clients.
+    fn project_id(&mut self, target: Target, client: Option<&str>) -> rusqlite::Result<u32> {
+        let path = match target {
+            Target::Id(id) => return Ok(id),
+            Target::Name(path) => path,
+        };
+        if let Some(id) = self.find(&path) {
+            return Ok(id);
+        }
+
+        let mut parent_id = None;
+        for name in path.split('/').filter(|s| !s.trim().is_empty()) {
+            let existing = self
+                .projects
+                .iter()
+                .find(|p| p.parent_id == parent_id && p.name == name)
+                .map(|p| p.id);
+            if let Some(id) = existing {
+                parent_id = Some(id);
+                continue;
+            }
+
+            let inherited = parent_id
+                .and_then(|id| self.projects.iter().find(|p| p.id == id))
+                .and_then(|p| tree::client_id(p, &self.projects));
+
+            let now = self.clock.now();
+            let mut proj = Project::new(name.to_owned(), None, now, now);
+            proj.parent_id = parent_id;
+            proj.client_id = match (inherited, client) {
+                (None, Some(client)) => self.client_id(client)?,
+                _ => None,
+            };
+            proj.id = self.repo.save_project(&proj)?;
+            println!("{} created successfully.\n", proj.name);
+
+            parent_id = Some(proj.id);
+            self.projects.push(proj);
+        }
+
+        // only a path without any names leaves no project
+        parent_id.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    /// ID of the client with the given name, ignoring case. The client is created if it does
+    /// not exist and the import bills new clients, otherwise there is none.
+    fn client_id(&mut self, name: &str) -> rusqlite::Result<Option<u32>> {
+        if let Some(c) = self
+            .clients
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+        {
+            return Ok(Some(c.id));
+        }
+        let Some(currency) = self.currency.clone() else {
+            return Ok(None);
+        };
+
+        let mut client = Client::new(name.to_owned(), None, currency, None, None);
+        client.id = self.repo.save_client(&client)?;
+        println!("{} created successfully.\n", client.name);
+
+        let id = client.id;
+        self.clients.push(client);
+        Ok(Some(id))
+    }
+
+    /// Project and client named by a ledger account. The first segment of `client:project:sub`
+    /// accounts is the client when a client with that name exists.
+    fn account(&self, account: &str) -> (Target, Option<String>) {
+        let segments: Vec<&str> = account.split(':').collect();
+        match segments.split_first() {
+            Some((client, project))
+                if !project.is_empty()
+                    && self
+                        .clients
+                        .iter()
+                        .any(|c| c.name.eq_ignore_ascii_case(client)) =>
+            {
+                (Target::Name(project.join("/")), Some(client.to_string()))
+            }
+            _ => (Target::Name(segments.join("/")), None),
+        }
+    }
+
+    /// Saves a log unless it was imported before, or the same log is already recorded, as
+    /// happens when importing a file arrow exported. Formats that only keep minutes count logs
+    /// less than a minute apart as the same.
+    fn import(&mut self, external_id: &str, log: &Log) -> rusqlite::Result<()> {
+        let minute = Duration::minutes(1);
+        let close = |a: DateTime<Local>, b: DateTime<Local>| (a - b).abs() < minute;
+        let same = self
+            .repo
+            .logs_between(&(log.start - minute), &(log.start + minute))?
+            .iter()
+            .any(|l| {
+                l.proj_id == log.proj_id
+                    && l.message == log.message
+                    && close(l.start, log.start)
+                    && close(l.end, log.end)
+            });
+        if same {
+            self.skipped += 1;
+            return Ok(());
+        }
+
+        match self.repo.import_log(self.source, external_id, log)? {
+            Some(_) => {
+                self.imported += 1;
+                self.updated.insert(log.proj_id);
+            }
+            None => self.skipped += 1,
+        }
+        Ok(())
+    }
+
+    /// Updates the durations of the projects logs were added to, commits the import and reports
+    /// what was done.
+    fn finish(mut self, tracker: &str) {
+        for id in &self.updated {
+            if let Err(e) = project::handlers::update(self.repo, *id, ProjectChanges::default()) {
+                return eprintln!("Failed to update project duration: {}", e);
+            }
+        }
+        if let Err(e) = self.repo.commit_transaction() {
+            return eprintln!("Failed to save the import: {}", e);
+        }
+        self.committed = true;
+
+        println!("Imported {} log(s) from {}.", self.imported, tracker);
+        if self.skipped > 0 {
+            println!("Skipped {} entries already recorded.", self.skipped);
+        }
+    }
+}
+
+impl<R: Repository, C: Clock> Drop for Importer<'_, R, C> {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = self.repo.rollback_transaction();
+        }
+    }
+}
+
+/// Reads every interval in a Timewarrior database, oldest file first.
+///
+/// # Arguments
+///
+/// * `dir` - Timewarrior directory, e.g. `~/.timewarrior`, or its `data` directory
+fn read_timewarrior(dir: &Path) -> Result<Vec<Interval>, String> {
+    let data = if dir.join("data").is_dir() {
+        dir.join("data")
+    } else {
+        dir.to_path_buf()
+    };
+
+    let mut files: Vec<PathBuf> = fs::read_dir(&data)
+        .map_err(|e| format!("Failed to read {}: {}", data.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "data"))
+        .collect();
+    files.sort();
+
+    if files.is_empty() {
+        return Err(format!(
+            "No Timewarrior data files found in {}",
+            data.display()
+        ));
+    }
+
+    let mut intervals = Vec::new();
+    for file in files {
+        let text = fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        intervals
+            .extend(timewarrior::parse(&text).map_err(|e| format!("{} {}", file.display(), e))?);
+    }
+
+    Ok(intervals)
+}
+
+/// Import the intervals tracked with Timewarrior as logs. Intervals imported before are
+/// skipped, so importing again only adds what was tracked since.
+///
+/// # Arguments:
+///
+/// * `dir` - Timewarrior directory, e.g. `~/.timewarrior`, or its `data` directory
+/// * `project` - Optional project for intervals without a mapped tag. Without it the first tag
+///   names the project, which is created if needed
+/// * `maps` - Tags that decide the project, written as `tag=project`
+pub fn timewarrior(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    dir: PathBuf,
+    project: Option<String>,
+    maps: Vec<String>,
+) {
+    let rules = match ProjectRules::new(repo, project, maps) {
+        Ok(rules) => rules,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let intervals = match read_timewarrior(&dir) {
+        Ok(intervals) => intervals,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let mut importer = match Importer::new(repo, clock, "timewarrior") {
+        Ok(importer) => importer,
+        Err(e) => return eprintln!("Error retrieving projects: {}", e),
+    };
+
+    let (mut open, mut untagged) = (0, 0);
+    for interval in intervals {
+        let Some(end) = interval.end else {
+            open += 1;
+            continue;
+        };
+        let Some((target, tags)) = rules.assign(&interval.tags) else {
+            untagged += 1;
+            continue;
+        };
+
+        let proj_id = match importer.project_id(target, None) {
+            Ok(id) => id,
+            Err(e) => return eprintln!("Failed to create new project: {}", e),
+        };
+
+        let message = interval
+            .annotation
+            .unwrap_or_else(|| interval.tags.join(" "));
+        let mut log = Log::new(0, proj_id, message, interval.start, end);
+        log.tags = tags;
+
+        if let Err(e) = importer.import(&interval.id, &log) {
+            return eprintln!("Failed to save log: {}", e);
+        }
+    }
+
+    importer.finish("Timewarrior");
+    if open > 0 {
+        println!("Skipped {} interval(s) still being tracked.", open);
+    }
+    if untagged > 0 {
+        println!(
+            "Skipped {} untagged interval(s). Use --project to import them.",
+            untagged
+        );
+    }
+}
+
+/// A CSV file to import, as given on the command line
+pub struct CsvFile {
+    /// File to import
+    pub path: PathBuf,
+    /// Layout of the file
+    pub preset: Preset,
+    /// Fields read from other columns than the preset's, written as `field=Header`
+    pub columns: Vec<String>,
+    /// Optional time zone the file's times are in, defaults to the local time zone
+    pub timezone: Option<String>,
+}
+
+/// Import time entries from a CSV file, such as a Toggl or Clockify export. Entries imported
+/// before are skipped. Projects and clients named in the file are created if needed.
+///
+/// # Arguments:
+///
+/// * `file` - CSV file to import and how to read it
+/// * `project` - Optional project for entries without one
+/// * `currency` - Currency clients named in the file are billed in, if they are created
+/// * `dry_run` - Only show what would be imported
+pub fn csv(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    file: CsvFile,
+    project: Option<String>,
+    currency: String,
+    dry_run: bool,
+) {
+    let CsvFile {
+        path,
+        preset,
+        columns,
+        timezone,
+    } = file;
+    let mut layout = preset.layout();
+    for column in columns {
+        if let Err(e) = layout.map_column(&column) {
+            return eprintln!("{}", e);
+        }
+    }
+    let zone = match timezone.map(|tz| Zone::parse(&tz)).transpose() {
+        Ok(zone) => zone.unwrap_or(Zone::Local),
+        Err(e) => return eprintln!("{}", e),
+    };
+    let currency = match parse_currency(&currency) {
+        Ok(currency) => currency,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let rules = match ProjectRules::new(repo, project, Vec::new()) {
+        Ok(rules) => rules,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let entries = match fs::read_to_string(&path) {
+        Ok(text) => match csv::read(&text, &layout, zone) {
+            Ok(entries) => entries,
+            Err(e) => return eprintln!("{} {}", path.display(), e),
+        },
+        Err(e) => return eprintln!("Failed to read {}: {}", path.display(), e),
+    };
+
+    let mut importer = match Importer::new(repo, clock, "csv") {
+        Ok(importer) => importer.billing_in(currency),
+        Err(e) => return eprintln!("Error retrieving projects: {}", e),
+    };
+
+    let mut preview = Table::new(vec![
+        "Project".to_string(),
+        "Message".to_string(),
+        "Start".to_string(),
+        "End".to_string(),
+        "Duration".to_string(),
+        "Billable".to_string(),
+        "Tags".to_string(),
+        "Status".to_string(),
+    ]);
+    let mut unassigned = 0;
+    for entry in entries {
+        let target = if entry.project.is_empty() {
+            rules.fixed.map(Target::Id)
+        } else {
+            Some(Target::Name(entry.project.clone()))
+        };
+        let Some(target) = target else {
+            unassigned += 1;
+            continue;
+        };
+
+        // entries have no IDs of their own, so they are identified by when they were worked,
+        // on what
+        let entry_id = |proj_id: u32| {
+            format!(
+                "{} {} {} {}",
+                entry.start.timestamp(),
+                entry.end.timestamp(),
+                proj_id,
+                entry.description
+            )
+        };
+
+        if dry_run {
+            let (project, proj_id) = match &target {
+                Target::Id(id) => (importer.path(*id), Some(*id)),
+                Target::Name(name) => (name.clone(), importer.find(name)),
+            };
+            let status = match proj_id.map(|id| repo.is_imported("csv", &entry_id(id))) {
+                None => "new project",
+                Some(Ok(true)) => "imported before",
+                Some(Ok(false)) => "new",
+                Some(Err(e)) => return eprintln!("Error checking for imported logs: {}", e),
+            };
+            preview.add_row(vec![
+                project,
+                entry.description.clone(),
+                entry.start.to_string(),
+                entry.end.to_string(),
+                duration_hours(whole_seconds(entry.end - entry.start)),
+                if entry.billable { "yes" } else { "no" }.to_string(),
+                entry.tags.join(", "),
+                status.to_string(),
+            ]);
+            continue;
+        }
+
+        let proj_id = match importer.project_id(target, entry.client.as_deref()) {
+            Ok(id) => id,
+            Err(e) => return eprintln!("Failed to create new project: {}", e),
+        };
+        let external_id = entry_id(proj_id);
+        let mut log = Log::new(0, proj_id, entry.description, entry.start, entry.end);
+        log.billable = entry.billable;
+        log.tags = entry.tags;
+
+        if let Err(e) = importer.import(&external_id, &log) {
+            return eprintln!("Failed to save log: {}", e);
+        }
+    }
+
+    if dry_run {
+        preview.print(&mut std::io::stdout());
+        println!("Dry run, nothing was imported.");
+    } else {
+        importer.finish(&path.display().to_string());
+    }
+    if unassigned > 0 {
+        println!(
+            "Skipped {} entries without a project. Use --project to import them.",
+            unassigned
+        );
+    }
+}
+
+/// Import calendar events, such as meetings, from an iCalendar file as logs. The summary
+/// becomes the message and categories become tags. Events imported before are skipped.
+///
+/// # Arguments:
+///
+/// * `path` - `.ics` file to import
+/// * `project` - Project to log the events against
+/// * `category` - Optional category events must have, ignoring case
+/// * `summary` - Optional regular expression event summaries must match
+/// * `billable` - Whether the time is chargeable to the client
+pub fn ics(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    path: PathBuf,
+    project: String,
+    category: Option<String>,
+    summary: Option<String>,
+    billable: bool,
+) {
+    let proj = match resolve(repo, &project) {
+        Ok(p) => p,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let pattern = match summary.map(|s| Regex::new(&s)).transpose() {
+        Ok(pattern) => pattern,
+        Err(e) => return eprintln!("Invalid summary pattern: {}", e),
+    };
+
+    let events = match fs::read_to_string(&path) {
+        Ok(text) => match ics::read(&text) {
+            Ok(events) => events,
+            Err(e) => return eprintln!("{} {}", path.display(), e),
+        },
+        Err(e) => return eprintln!("Failed to read {}: {}", path.display(), e),
+    };
+
+    let mut importer = match Importer::new(repo, clock, "ics") {
+        Ok(importer) => importer,
+        Err(e) => return eprintln!("Error retrieving projects: {}", e),
+    };
+
+    for event in events {
+        let in_category = category
+            .as_ref()
+            .is_none_or(|c| event.categories.iter().any(|e| e.eq_ignore_ascii_case(c)));
+        let matches = pattern.as_ref().is_none_or(|p| p.is_match(&event.summary));
+        if !in_category || !matches {
+            continue;
+        }
+
+        let mut log = Log::new(0, proj.id, event.summary, event.start, event.end);
+        log.billable = billable;
+        log.tags = event.categories;
+
+        if let Err(e) = importer.import(&event.uid, &log) {
+            return eprintln!("Failed to save log: {}", e);
+        }
+    }
+
+    importer.finish(&path.display().to_string());
+}
+
+/// Import sessions from a ledger or hledger timeclock file. Accounts name the project, as a
+/// path with `:` separators, optionally starting with the client, e.g. `acme:api:auth`.
+/// Sessions imported before are skipped.
+///
+/// # Arguments:
+///
+/// * `path` - Timeclock file to import
+pub fn timeclock(repo: &impl Repository, clock: &impl Clock, path: PathBuf) {
+    let entries = match fs::read_to_string(&path) {
+        Ok(text) => match timeclock::read(&text) {
+            Ok(entries) => entries,
+            Err(e) => return eprintln!("{} {}", path.display(), e),
+        },
+        Err(e) => return eprintln!("Failed to read {}: {}", path.display(), e),
+    };
+
+    let mut importer = match Importer::new(repo, clock, "timeclock") {
+        Ok(importer) => importer,
+        Err(e) => return eprintln!("Error retrieving projects: {}", e),
+    };
+
+    for entry in entries {
+        let (target, client) = importer.account(&entry.account);
+        let proj_id = match importer.project_id(target, client.as_deref()) {
+            Ok(id) => id,
+            Err(e) => return eprintln!("Failed to create new project: {}", e),
+        };
+
+        let external_id = format!(
+            "{} {} {} {}",
+            entry.start.timestamp(),
+            entry.end.timestamp(),
+            proj_id,
+            entry.description
+        );
+        let mut log = Log::new(0, proj_id, entry.description, entry.start, entry.end);
+        log.billable = entry.billable;
+        log.tags = entry.tags;
+
+        if let Err(e) = importer.import(&external_id, &log) {
+            return eprintln!("Failed to save log: {}", e);
+        }
+    }
+
+    importer.finish(&path.display().to_string());
+}
+
+/// Import clock entries from an org mode file. Each top level heading names a project, such as
+/// `acme/api`, and the heading a clock entry belongs to becomes the message, its tags the log's
+/// tags. Entries imported before are skipped.
+///
+/// # Arguments:
+///
+/// * `path` - Org file to import
+/// * `project` - Optional project for every entry, instead of the top level headings
+pub fn org(repo: &impl Repository, clock: &impl Clock, path: PathBuf, project: Option<String>) {
+    let rules = match ProjectRules::new(repo, project, Vec::new()) {
+        Ok(rules) => rules,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let clocks = match fs::read_to_string(&path) {
+        Ok(text) => match org::read(&text) {
+            Ok(clocks) => clocks,
+            Err(e) => return eprintln!("{} {}", path.display(), e),
+        },
+        Err(e) => return eprintln!("Failed to read {}: {}", path.display(), e),
+    };
+
+    let mut importer = match Importer::new(repo, clock, "org") {
+        Ok(importer) => importer,
+        Err(e) => return eprintln!("Error retrieving projects: {}", e),
+    };
+
+    let mut unassigned = 0;
+    for clock in clocks {
+        let target = match rules.fixed {
+            Some(id) => Target::Id(id),
+            None if clock.section.is_empty() => {
+                unassigned += 1;
+                continue;
+            }
+            None => Target::Name(clock.section),
+        };
+        let proj_id = match importer.project_id(target, None) {
+            Ok(id) => id,
+            Err(e) => return eprintln!("Failed to create new project: {}", e),
+        };
+
+        let external_id = format!(
+            "{} {} {} {}",
+            clock.start.timestamp(),
+            clock.end.timestamp(),
+            proj_id,
+            clock.task
+        );
+        let mut log = Log::new(0, proj_id, clock.task, clock.start, clock.end);
+        log.tags = clock.tags;
+
+        if let Err(e) = importer.import(&external_id, &log) {
+            return eprintln!("Failed to save log: {}", e);
+        }
+    }
+
+    importer.finish(&path.display().to_string());
+    if unassigned > 0 {
+        println!(
+            "Skipped {} entries outside of any heading. Use --project to import them.",
+            unassigned
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clock::FixedClock, repository::Sqlite, time::parse_datetime};
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn assign_uses_first_tag_as_project() {
+        // Arrange
+        let rules = ProjectRules::default();
+
+        // Act
+        let res = rules.assign(&tags(&["api", "meeting", "tag 2"]));
+
+        // Assert
+        assert_eq!(
+            Some((Target::Name("api".to_owned()), tags(&["meeting", "tag 2"]))),
+            res
+        );
+    }
+
+    #[test]
+    fn assign_prefers_mapped_tag_over_fixed_project() {
+        // Arrange
+        let rules = ProjectRules {
+            fixed: Some(1),
+            mapped: vec![("client-x".to_owned(), 7)],
+        };
+
+        // Act
+        let mapped = rules.assign(&tags(&["meeting", "client-x"]));
+        let fixed = rules.assign(&tags(&["meeting"]));
+
+        // Assert
+        assert_eq!(Some((Target::Id(7), tags(&["meeting"]))), mapped);
+        assert_eq!(Some((Target::Id(1), tags(&["meeting"]))), fixed);
+    }
+
+    #[test]
+    fn assign_without_tags_or_project_is_none() {
+        assert_eq!(None, ProjectRules::default().assign(&[]));
+    }
+
+    #[test]
+    fn project_id_creates_projects_at_the_clocks_time() {
+        // Arrange
+        let repo = Sqlite::in_memory().unwrap();
+        let clock = FixedClock::at(parse_datetime("2026-03-29 09:00").unwrap());
+        let mut importer = Importer::new(&repo, &clock, "csv").unwrap();
+
+        // Act
+        let id = importer
+            .project_id(Target::Name("acme/api".to_owned()), None)
+            .unwrap();
+
+        // Assert
+        let proj = importer.projects.iter().find(|p| p.id == id).unwrap();
+        assert_eq!("acme/api", importer.path(id));
+        assert_eq!(clock.now(), proj.created);
+        assert_eq!(clock.now(), proj.updated);
+    }
+
+    #[test]
+    fn project_id_bills_new_clients_in_the_imports_currency() {
+        // Arrange
+        let repo = Sqlite::in_memory().unwrap();
+        let clock = FixedClock::at(parse_datetime("2026-03-29 09:00").unwrap());
+        let mut billing = Importer::new(&repo, &clock, "csv")
+            .unwrap()
+            .billing_in("EUR".to_owned());
+
+        // Act
+        billing
+            .project_id(Target::Name("api".to_owned()), Some("acme"))
+            .unwrap();
+        let id = billing
+            .project_id(Target::Name("web".to_owned()), Some("globex"))
+            .unwrap();
+        billing.currency = None;
+        let unbilled = billing
+            .project_id(Target::Name("docs".to_owned()), Some("initech"))
+            .unwrap();
+
+        // Assert
+        let currencies: Vec<&str> = billing
+            .clients
+            .iter()
+            .map(|c| c.currency.as_str())
+            .collect();
+        assert_eq!(vec!["EUR", "EUR"], currencies);
+        let client_of = |id| {
+            billing
+                .projects
+                .iter()
+                .find(|p| p.id == id)
+                .unwrap()
+                .client_id
+        };
+        assert_eq!(Some(billing.clients[1].id), client_of(id));
+        assert_eq!(None, client_of(unbilled));
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::model::Log;
+
+/// Whether each log is sealed with a hash chained to the log before it, so changes made
+/// without arrow can be found.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Integrity {
+    pub chain: bool,
+}
+
+/// A log with the hash stored for it, `None` if it was never sealed
+pub type Sealed = (Log, Option<String>);
+
+/// Why a log failed verification
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    /// The log no longer matches its hash. Either it was changed, or the log before it was
+    /// changed or removed
+    Changed,
+    /// The log has no hash, although logs before it do
+    Unsealed,
+}
+
+/// A log that failed verification
+#[derive(Debug, PartialEq)]
+pub struct Break {
+    pub log_id: u32,
+    pub problem: Problem,
+}
+
+/// Outcome of verifying the chain
+#[derive(Debug, Default, PartialEq)]
+pub struct Report {
+    /// Logs saved before the chain was turned on, which it does not cover
+    pub unsealed: usize,
+    /// Logs with a hash
+    pub sealed: usize,
+    pub breaks: Vec<Break>,
+}
+
+/// Hash of a log chained to the hash of the log before it, in hex. Covers the ID, project,
+/// message, times, billable flag and tags of the log, but not the invoice it was billed on
+/// nor the zone it was recorded in, so hashes taken before zones were kept still match.
+/// Times are hashed in seconds, with milliseconds only when there are any, so hashes taken
+/// while times were kept to the second still match too.
+///
+/// # Arguments
+///
+/// * `previous` - Hash of the log before, `None` for the first log or one after an unsealed log
+/// * `log` - The log to hash
+pub fn link(previous: Option<&str>, log: &Log) -> String {
+    let mut tags = log.tags.clone();
+    tags.sort();
+
+    let content = json!([
+        previous.unwrap_or_default(),
+        log.id,
+        log.proj_id,
+        log.message,
+        instant(&log.start),
+        instant(&log.end),
+        log.billable,
+        tags
+    ]);
+    Sha256::digest(content.to_string())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// A time as whole seconds, or as seconds with a fraction when it has milliseconds
+fn instant(time: &DateTime<Local>) -> Value {
+    match time.timestamp_subsec_millis() {
+        0 => json!(time.timestamp()),
+        millis => json!(format!("{}.{:03}", time.timestamp(), millis)),
+    }
+}
+
+/// Checks every log against its hash, in the order they were saved. Each log is checked
+/// against the hash stored for the log before it, so a change only breaks the log that was
+/// changed, or the one after a removed log.
+///
+/// # Arguments
+///
+/// * `logs` - Every log with its stored hash, ordered by ID
+pub fn verify(logs: &[Sealed]) -> Report {
+    let mut report = Report::default();
+    let mut previous: Option<&str> = None;
+
+    for (log, hash) in logs {
+        match hash {
+            None if report.sealed == 0 => report.unsealed += 1,
+            None => report.breaks.push(Break {
+                log_id: log.id,
+                problem: Problem::Unsealed,
+            }),
+            Some(hash) => {
+                report.sealed += 1;
+                if *hash != link(previous, log) {
+                    report.breaks.push(Break {
+                        log_id: log.id,
+                        problem: Problem::Changed,
+                    });
+                }
+            }
+        }
+        previous = hash.as_deref();
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::time::parse_datetime;
+
+    use super::*;
+
+    fn sealed(logs: Vec<Log>) -> Vec<Sealed> {
+        let mut previous = None;
+        logs.into_iter()
+            .map(|log| {
+                let hash = link(previous.as_deref(), &log);
+                previous = Some(hash.clone());
+                (log, Some(hash))
+            })
+            .collect()
+    }
+
+    fn test_log(id: u32, message: &str) -> Log {
+        Log::new(
+            id,
+            1,
+            message.to_owned(),
+            parse_datetime("2026-10-01 09:00").unwrap(),
+            parse_datetime("2026-10-01 10:00").unwrap(),
+        )
+    }
+
+    #[test]
+    fn link_ignores_tag_order() {
+        // Arrange
+        let mut a = test_log(1, "Fix login");
+        a.tags = vec!["bug".to_owned(), "api".to_owned()];
+        let mut b = test_log(1, "Fix login");
+        b.tags = vec!["api".to_owned(), "bug".to_owned()];
+
+        // Act
+        let res = (link(Some("x"), &a), link(Some("x"), &b));
+
+        // Assert
+        assert_eq!(64, res.0.len());
+        assert_eq!(res.0, res.1);
+        assert_ne!(res.0, link(None, &a));
+    }
+
+    #[test]
+    fn verify_pinpoints_changed_log() {
+        // Arrange
+        let mut logs = sealed(vec![
+            test_log(1, "Fix login"),
+            test_log(2, "Review"),
+            test_log(3, "Deploy"),
+        ]);
+        logs[1].0.end = parse_datetime("2026-10-01 12:00").unwrap();
+
+        // Act
+        let res = verify(&logs);
+
+        // Assert
+        assert_eq!(
+            Report {
+                unsealed: 0,
+                sealed: 3,
+                breaks: vec![Break {
+                    log_id: 2,
+                    problem: Problem::Changed
+                }],
+            },
+            res
+        );
+    }
+
+    #[test]
+    fn verify_counts_logs_from_before_the_chain() {
+        // Arrange
+        let mut logs = vec![(test_log(1, "Old"), None)];
+        logs.extend(sealed(vec![test_log(2, "Review"), test_log(3, "Deploy")]));
+        logs.push((test_log(4, "Unsealed"), None));
+
+        // Act
+        let res = verify(&logs);
+
+        // Assert
+        assert_eq!((1, 2), (res.unsealed, res.sealed));
+        assert_eq!(
+            vec![Break {
+                log_id: 4,
+                problem: Problem::Unsealed
+            }],
+            res.breaks
+        );
+    }
+}
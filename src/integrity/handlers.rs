@@ -0,0 +1,86 @@
+use crate::{
+    config::Config,
+    integrity::chain::{self, Problem},
+    print::table::Table,
+    repository::Repository,
+};
+
+/// Check the hash chain over the logs, listing every log changed or removed without arrow.
+///
+/// # Arguments:
+///
+/// * `seal` - Seal the logs saved before the chain was turned on, or while it was off
+pub fn verify(repo: &impl Repository, seal: bool) {
+    let chained = match Config::load() {
+        Ok(config) => config.integrity.chain,
+        Err(e) => return eprintln!("Failed to read config: {}", e),
+    };
+
+    if seal {
+        if !chained {
+            return eprintln!(
+                "The hash chain is turned off. Set `chain = true` under `[integrity]` in config.toml first."
+            );
+        }
+        match repo.seal_logs() {
+            Ok(sealed) => println!("Sealed {} log(s).", sealed),
+            Err(e) => return eprintln!("Failed to seal logs: {}", e),
+        }
+    }
+
+    let logs = match repo.log_chain() {
+        Ok(logs) => logs,
+        Err(e) => return eprintln!("Error retrieving logs: {}", e),
+    };
+    let report = chain::verify(&logs);
+
+    if report.sealed == 0 {
+        return match chained {
+            true => println!("No logs have been sealed yet."),
+            false => println!(
+                "The hash chain is turned off. Set `chain = true` under `[integrity]` in config.toml to seal logs."
+            ),
+        };
+    }
+
+    if !report.breaks.is_empty() {
+        let mut table = Table::new(vec![
+            "ID".to_string(),
+            "Message".to_string(),
+            "Start".to_string(),
+            "End".to_string(),
+            "Problem".to_string(),
+        ]);
+        for found in &report.breaks {
+            let Some((log, _)) = logs.iter().find(|(log, _)| log.id == found.log_id) else {
+                continue;
+            };
+            table.add_row(vec![
+                log.id.to_string(),
+                log.message.clone(),
+                log.start.to_string(),
+                log.end.to_string(),
+                match found.problem {
+                    Problem::Changed => "changed, or the log before it changed or removed",
+                    Problem::Unsealed => "not sealed",
+                }
+                .to_string(),
+            ]);
+        }
+        table.print(&mut std::io::stdout());
+        eprintln!(
+            "{} of {} sealed log(s) failed verification. They were changed without arrow.",
+            report.breaks.len(),
+            report.sealed
+        );
+    } else {
+        println!("All {} sealed log(s) are intact.", report.sealed);
+    }
+
+    if report.unsealed > 0 {
+        println!(
+            "{} log(s) saved before the chain was turned on are not covered. Run `arrow verify --seal` to seal them.",
+            report.unsealed
+        );
+    }
+}
@@ -0,0 +1,558 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use chrono::Duration;
+use clap::ValueEnum;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+
+use crate::{
+    client,
+    clock::Clock,
+    config::Config,
+    invoice::template::{self, Context},
+    model::{Client, Invoice, InvoiceLine, Log, Project},
+    money,
+    print::table::Table,
+    project::tree,
+    rate::lookup::RateBook,
+    repository::Repository,
+    rounding::{self, RoundingRule},
+    time::{duration_hours, parse_month},
+};
+
+/// Formats an invoice can be rendered to
+#[derive(Clone, Copy, ValueEnum)]
+pub enum InvoiceFormat {
+    Md,
+    Html,
+    Txt,
+    /// Machine readable, with both the raw and rounded time of each line
+    Json,
+}
+
+impl InvoiceFormat {
+    /// File extension and built in template of formats rendered from a template
+    fn template(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            InvoiceFormat::Md => Some(("md", include_str!("../../templates/invoice.md"))),
+            InvoiceFormat::Html => Some(("html", include_str!("../../templates/invoice.html"))),
+            InvoiceFormat::Txt => Some(("txt", include_str!("../../templates/invoice.txt"))),
+            InvoiceFormat::Json => None,
+        }
+    }
+}
+
+/// Template for a format, preferring `$XDG_CONFIG_HOME/arrow/templates/invoice.<ext>` over the
+/// built in one.
+fn load_template(extension: &str, default: &str) -> String {
+    let file = format!("templates/invoice.{}", extension);
+    xdg::BaseDirectories::with_prefix("arrow")
+        .ok()
+        .and_then(|dirs| dirs.find_config_file(file))
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_else(|| default.to_owned())
+}
+
+#[derive(Serialize)]
+struct JsonInvoice<'a> {
+    number: &'a str,
+    client: &'a str,
+    currency: &'a str,
+    period_start: String,
+    /// Last day of the period, inclusive
+    period_end: String,
+    created: String,
+    raw_seconds: u32,
+    seconds: u32,
+    total: String,
+    lines: Vec<JsonLine<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    description: &'a str,
+    raw_seconds: u32,
+    seconds: u32,
+    rate: String,
+    amount: String,
+}
+
+fn render_json(invoice: &Invoice, client: &Client) -> String {
+    let json = JsonInvoice {
+        number: &invoice.number,
+        client: &client.name,
+        currency: &invoice.currency,
+        period_start: invoice.period_start.format("%Y-%m-%d").to_string(),
+        period_end: (invoice.period_end - Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string(),
+        created: invoice.created.to_rfc3339(),
+        raw_seconds: invoice.lines.iter().map(|l| l.raw_seconds).sum(),
+        seconds: invoice.lines.iter().map(|l| l.seconds).sum(),
+        total: format!("{:.2}", invoice.total),
+        lines: invoice
+            .lines
+            .iter()
+            .map(|l| JsonLine {
+                description: &l.description,
+                raw_seconds: l.raw_seconds,
+                seconds: l.seconds,
+                rate: l.rate.to_string(),
+                amount: format!("{:.2}", l.amount),
+            })
+            .collect(),
+    };
+
+    serde_json::to_string_pretty(&json).expect("Failed to serialise invoice") + "\n"
+}
+
+fn round_cents(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+/// Number for a new invoice, one past the highest `INV-NNNN` number taken. Counting invoices
+/// instead would hand out a taken number once there are gaps, and the database rejects those.
+fn next_number(invoices: &[Invoice]) -> String {
+    let highest = invoices
+        .iter()
+        .filter_map(|i| i.number.strip_prefix("INV-")?.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("INV-{:04}", highest + 1)
+}
+
+/// Groups logs into one line per project and rate, in the order they were first logged. Each
+/// line's time is rounded by the client's rounding rule, and its amount rounded to cents.
+fn build_lines(
+    logs: &[Log],
+    projects: &[Project],
+    book: &RateBook,
+    rule: Option<&RoundingRule>,
+) -> Result<Vec<InvoiceLine>, String> {
+    let mut groups: Vec<(u32, Decimal, Vec<&Log>)> = Vec::new();
+    let mut unpriced: Vec<String> = Vec::new();
+
+    for log in logs {
+        let rate = match book.rate_for(log) {
+            Some(rate) => rate,
+            None => {
+                unpriced.push(log.id.to_string());
+                continue;
+            }
+        };
+
+        match groups
+            .iter_mut()
+            .find(|(proj_id, r, _)| *proj_id == log.proj_id && *r == rate)
+        {
+            Some((_, _, group)) => group.push(log),
+            None => groups.push((log.proj_id, rate, vec![log])),
+        }
+    }
+
+    if !unpriced.is_empty() {
+        return Err(format!(
+            "No rate applies to log(s) {}. Set a rate with `arrow rate set` first.",
+            unpriced.join(", ")
+        ));
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|(proj_id, rate, group)| {
+            let seconds = rounding::rounded(rule, &group);
+            InvoiceLine {
+                description: match projects.iter().find(|p| p.id == proj_id) {
+                    Some(p) => tree::path(p, projects),
+                    None => proj_id.to_string(),
+                },
+                raw_seconds: rounding::rounded(None, &group),
+                seconds,
+                rate,
+                amount: round_cents(money::earnings(seconds, rate)),
+            }
+        })
+        .collect())
+}
+
+/// Renders an invoice in the given format, using the format's template if it has one.
+pub fn render(invoice: &Invoice, client: &Client, format: InvoiceFormat) -> String {
+    let (extension, default) = match format.template() {
+        Some(template) => template,
+        None => return render_json(invoice, client),
+    };
+
+    let mut ctx = Context::default();
+    ctx.set("number", invoice.number.clone());
+    ctx.set("client", client.name.clone());
+    ctx.set("contact", client.contact.clone().unwrap_or_default());
+    ctx.set("address", client.address.clone().unwrap_or_default());
+    ctx.set("currency", invoice.currency.clone());
+    ctx.set(
+        "period_start",
+        invoice.period_start.format("%Y-%m-%d").to_string(),
+    );
+    ctx.set(
+        "period_end",
+        (invoice.period_end - Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string(),
+    );
+    ctx.set("created", invoice.created.format("%Y-%m-%d").to_string());
+    ctx.set("total", format!("{:.2}", invoice.total));
+    ctx.set(
+        "total_hours",
+        duration_hours(invoice.lines.iter().map(|l| l.seconds).sum()),
+    );
+
+    for line in &invoice.lines {
+        ctx.push_row(
+            "lines",
+            HashMap::from([
+                ("description".to_owned(), line.description.clone()),
+                ("hours".to_owned(), duration_hours(line.seconds)),
+                ("raw_hours".to_owned(), duration_hours(line.raw_seconds)),
+                ("rate".to_owned(), format!("{:.2}", line.rate)),
+                ("amount".to_owned(), format!("{:.2}", line.amount)),
+            ]),
+        );
+    }
+
+    let escape = match format {
+        InvoiceFormat::Html => template::escape_html,
+        _ => template::no_escape,
+    };
+    template::render(&load_template(extension, default), &ctx, escape)
+}
+
+fn output(rendered: &str, path: Option<PathBuf>) {
+    match path {
+        Some(path) => match fs::write(&path, rendered) {
+            Ok(_) => println!("Invoice written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write invoice to {}: {}", path.display(), e),
+        },
+        None => print!("{}", rendered),
+    }
+}
+
+/// Invoice a client for all uninvoiced billable time logged in a month. The logs are locked
+/// against further edits once invoiced.
+///
+/// # Arguments:
+///
+/// * `client_ref` - ID or name of the client to invoice
+/// * `period` - Month to invoice, e.g. 2026-09
+/// * `format` - Format to render the invoice in
+/// * `path` - Optional file to write the invoice to instead of stdout
+pub fn create(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    client_ref: &str,
+    period: &str,
+    format: InvoiceFormat,
+    path: Option<PathBuf>,
+) {
+    let client = match client::resolve::resolve(repo, client_ref) {
+        Ok(c) => c,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let (start, end) = match parse_month(period) {
+        Some(range) => range,
+        None => {
+            return eprintln!(
+                "Invalid period \"{}\". Periods should look like 2026-09",
+                period
+            )
+        }
+    };
+
+    let projects = match repo.all_projects() {
+        Ok(projects) => projects,
+        Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+    };
+    let clients = match repo.all_clients() {
+        Ok(clients) => clients,
+        Err(e) => return eprintln!("Error retrieving your clients: {}", e),
+    };
+    let rates = match repo.all_rates() {
+        Ok(rates) => rates,
+        Err(e) => return eprintln!("Error retrieving rates: {}", e),
+    };
+    let invoices = match repo.all_invoices() {
+        Ok(invoices) => invoices,
+        Err(e) => return eprintln!("Error retrieving invoices: {}", e),
+    };
+    let mut logs = match repo.logs_between(&start, &end) {
+        Ok(logs) => logs,
+        Err(e) => return eprintln!("Error retrieving logs: {}", e),
+    };
+
+    logs.retain(|log| {
+        log.billable
+            && log.invoice_id.is_none()
+            && projects
+                .iter()
+                .find(|p| p.id == log.proj_id)
+                .and_then(|p| tree::client_id(p, &projects))
+                == Some(client.id)
+    });
+    if logs.is_empty() {
+        return eprintln!(
+            "No uninvoiced billable time for {} in {}.",
+            client.name, period
+        );
+    }
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => return eprintln!("Error reading config.toml: {}", e),
+    };
+
+    let book = RateBook::new(&projects, &clients, &rates);
+    let rule = config.rounding_for(Some(&client.name));
+    let lines = match build_lines(&logs, &projects, &book, rule) {
+        Ok(lines) => lines,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let mut invoice = Invoice {
+        id: 0,
+        number: next_number(&invoices),
+        client_id: client.id,
+        period_start: start,
+        period_end: end,
+        created: clock.now(),
+        currency: client.currency.clone(),
+        total: lines.iter().map(|l| l.amount).sum(),
+        lines,
+    };
+    let log_ids: Vec<u32> = logs.iter().map(|l| l.id).collect();
+
+    match repo.save_invoice(&invoice, &log_ids) {
+        Ok(id) => {
+            invoice.id = id;
+            eprintln!(
+                "Created invoice {} for {} log(s).",
+                invoice.number,
+                log_ids.len()
+            );
+            output(&render(&invoice, &client, format), path);
+        }
+        Err(e) => eprintln!("Failed to save invoice: {}", e),
+    }
+}
+
+pub fn list(repo: &impl Repository) {
+    let invoices = match repo.all_invoices() {
+        Ok(invoices) => invoices,
+        Err(e) => return eprintln!("Error retrieving invoices: {}", e),
+    };
+    let clients = match repo.all_clients() {
+        Ok(clients) => clients,
+        Err(e) => return eprintln!("Error retrieving your clients: {}", e),
+    };
+
+    let mut table = Table::new(vec![
+        "Number".to_string(),
+        "Client".to_string(),
+        "From".to_string(),
+        "To".to_string(),
+        "Hours".to_string(),
+        "Total".to_string(),
+    ]);
+    for invoice in invoices {
+        let client = clients
+            .iter()
+            .find(|c| c.id == invoice.client_id)
+            .map(|c| c.name.clone())
+            .unwrap_or_default();
+
+        table.add_row(vec![
+            invoice.number.clone(),
+            client,
+            invoice.period_start.format("%Y-%m-%d").to_string(),
+            (invoice.period_end - Duration::days(1))
+                .format("%Y-%m-%d")
+                .to_string(),
+            duration_hours(invoice.lines.iter().map(|l| l.seconds).sum()),
+            money::format_amount(invoice.total, Some(&invoice.currency)),
+        ]);
+    }
+    table.print(&mut std::io::stdout());
+}
+
+/// Render a previously created invoice again.
+///
+/// # Arguments:
+///
+/// * `number` - Invoice number, e.g. INV-0001
+/// * `format` - Format to render the invoice in
+/// * `path` - Optional file to write the invoice to instead of stdout
+pub fn show(repo: &impl Repository, number: &str, format: InvoiceFormat, path: Option<PathBuf>) {
+    let invoice = match repo.all_invoices() {
+        Ok(invoices) => match invoices.into_iter().find(|i| i.number == number) {
+            Some(invoice) => invoice,
+            None => return eprintln!("No invoice numbered {} was found.", number),
+        },
+        Err(e) => return eprintln!("Error retrieving invoices: {}", e),
+    };
+
+    match repo.get_client(&invoice.client_id) {
+        Ok(client) => output(&render(&invoice, &client, format), path),
+        Err(e) => eprintln!("Error retrieving client: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{model::Rate, model::RateScope, time::to_datetime};
+
+    use super::*;
+
+    fn log(id: u32, proj_id: u32, start: i64, seconds: i64) -> Log {
+        Log::new(
+            id,
+            proj_id,
+            "work".to_owned(),
+            to_datetime(start),
+            to_datetime(start + seconds),
+        )
+    }
+
+    fn fixtures() -> (Vec<Project>, Vec<Client>, Vec<Rate>) {
+        let mut acme = Project::load(
+            1,
+            "acme".to_owned(),
+            None,
+            to_datetime(0),
+            to_datetime(0),
+            Duration::zero(),
+        );
+        acme.client_id = Some(1);
+        let mut api = Project::load(
+            2,
+            "api".to_owned(),
+            None,
+            to_datetime(0),
+            to_datetime(0),
+            Duration::zero(),
+        );
+        api.parent_id = Some(1);
+
+        let mut client = Client::new(
+            "acme".to_owned(),
+            None,
+            "AUD".to_owned(),
+            Some(Decimal::new(10001, 2)),
+            None,
+        );
+        client.id = 1;
+
+        let mut rate = Rate::new(RateScope::Project(2), Decimal::from(150), to_datetime(5000));
+        rate.id = 1;
+
+        (vec![acme, api], vec![client], vec![rate])
+    }
+
+    #[test]
+    fn build_lines_groups_by_project_and_rate() {
+        // Arrange
+        let (projects, clients, rates) = fixtures();
+        let book = RateBook::new(&projects, &clients, &rates);
+        let logs = vec![
+            log(1, 1, 0, 1800),
+            log(2, 2, 0, 3600),
+            log(3, 1, 2000, 1800),
+            log(4, 2, 6000, 1200),
+        ];
+
+        // Act
+        let lines = build_lines(&logs, &projects, &book, None).unwrap();
+
+        // Assert
+        let actual: Vec<(&str, u32, Decimal)> = lines
+            .iter()
+            .map(|l| (l.description.as_str(), l.seconds, l.amount))
+            .collect();
+        assert_eq!(
+            vec![
+                ("acme", 3600, Decimal::new(10001, 2)),
+                ("acme/api", 3600, Decimal::new(10001, 2)),
+                ("acme/api", 1200, Decimal::new(5000, 2)),
+            ],
+            actual
+        );
+    }
+
+    #[test]
+    fn build_lines_rounds_each_line_to_cents() {
+        // Arrange
+        let (projects, clients, rates) = fixtures();
+        let book = RateBook::new(&projects, &clients, &rates);
+
+        // Act
+        let lines = build_lines(&[log(1, 1, 0, 1800)], &projects, &book, None).unwrap();
+
+        // Assert
+        assert_eq!(Decimal::new(5001, 2), lines[0].amount);
+    }
+
+    #[test]
+    fn build_lines_rounds_time_but_keeps_raw_seconds() {
+        // Arrange
+        let (projects, clients, rates) = fixtures();
+        let book = RateBook::new(&projects, &clients, &rates);
+        let rule = RoundingRule {
+            increment: 15,
+            direction: rounding::Direction::Up,
+            scope: rounding::Scope::Entry,
+            minimum: 0,
+        };
+
+        // Act
+        let lines = build_lines(&[log(1, 1, 0, 20 * 60)], &projects, &book, Some(&rule)).unwrap();
+
+        // Assert
+        assert_eq!((1200, 1800), (lines[0].raw_seconds, lines[0].seconds));
+        assert_eq!(Decimal::new(5001, 2), lines[0].amount);
+    }
+
+    #[test]
+    fn build_lines_without_rate_fails() {
+        // Arrange
+        let (projects, _, _) = fixtures();
+        let book = RateBook::new(&projects, &[], &[]);
+
+        // Act
+        let res = build_lines(&[log(7, 1, 0, 60)], &projects, &book, None);
+
+        // Assert
+        assert!(res.unwrap_err().contains("7"));
+    }
+
+    #[test]
+    fn next_number_follows_highest_number_taken() {
+        // Arrange
+        let invoice = |number: &str| Invoice {
+            id: 0,
+            number: number.to_owned(),
+            client_id: 1,
+            period_start: to_datetime(0),
+            period_end: to_datetime(0),
+            created: to_datetime(0),
+            currency: "AUD".to_owned(),
+            lines: Vec::new(),
+            total: Decimal::ZERO,
+        };
+
+        // Act
+        let first = next_number(&[]);
+        let gap = next_number(&[invoice("INV-0001"), invoice("INV-0003")]);
+        let other = next_number(&[invoice("INV-0002"), invoice("2026/7")]);
+
+        // Assert
+        assert_eq!("INV-0001", first);
+        assert_eq!("INV-0004", gap);
+        assert_eq!("INV-0003", other);
+    }
+}
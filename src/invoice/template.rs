@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+/// Values available to a template. Plain values are referenced as `{{name}}`, sections repeat
+/// their body once per row: `{{#lines}}{{description}}{{/lines}}`.
+#[derive(Default)]
+pub struct Context {
+    values: HashMap<String, String>,
+    sections: HashMap<String, Vec<HashMap<String, String>>>,
+}
+
+impl Context {
+    pub fn set(&mut self, name: &str, value: impl Into<String>) {
+        self.values.insert(name.to_owned(), value.into());
+    }
+
+    pub fn push_row(&mut self, section: &str, row: HashMap<String, String>) {
+        self.sections
+            .entry(section.to_owned())
+            .or_default()
+            .push(row);
+    }
+}
+
+/// Renders a template, passing every substituted value through `escape`. Unknown names render
+/// as nothing.
+pub fn render(template: &str, ctx: &Context, escape: fn(&str) -> String) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{#") {
+        out.push_str(&substitute(&rest[..start], &ctx.values, escape));
+
+        let after_open = &rest[start + 3..];
+        let name_end = match after_open.find("}}") {
+            Some(i) => i,
+            None => break,
+        };
+        let name = after_open[..name_end].trim();
+        let body_start = &after_open[name_end + 2..];
+
+        let close = format!("{{{{/{}}}}}", name);
+        let body_end = match body_start.find(&close) {
+            Some(i) => i,
+            None => {
+                rest = body_start;
+                continue;
+            }
+        };
+        let body = &body_start[..body_end];
+
+        for row in ctx.sections.get(name).into_iter().flatten() {
+            let mut values = ctx.values.clone();
+            values.extend(row.iter().map(|(k, v)| (k.clone(), v.clone())));
+            out.push_str(&substitute(body, &values, escape));
+        }
+
+        rest = &body_start[body_end + close.len()..];
+    }
+
+    out.push_str(&substitute(rest, &ctx.values, escape));
+    out
+}
+
+fn substitute(text: &str, values: &HashMap<String, String>, escape: fn(&str) -> String) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        match rest[start + 2..].find("}}") {
+            Some(end) => {
+                let name = rest[start + 2..start + 2 + end].trim();
+                if let Some(value) = values.get(name) {
+                    out.push_str(&escape(value));
+                }
+                rest = &rest[start + 2 + end + 2..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Leaves values as they are, for plain text and Markdown.
+pub fn no_escape(value: &str) -> String {
+    value.to_owned()
+}
+
+/// Escapes values for use in HTML text and attributes.
+pub fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_substitutes_values_and_sections() {
+        // Arrange
+        let mut ctx = Context::default();
+        ctx.set("number", "INV-0001");
+        ctx.set("currency", "AUD");
+        for (desc, amount) in [("api", "10.00"), ("web", "5.00")] {
+            ctx.push_row(
+                "lines",
+                HashMap::from([
+                    ("description".to_owned(), desc.to_owned()),
+                    ("amount".to_owned(), amount.to_owned()),
+                ]),
+            );
+        }
+
+        // Act
+        let res = render(
+            "# {{number}}\n{{#lines}}- {{description}} {{currency}} {{amount}}\n{{/lines}}{{missing}}end",
+            &ctx,
+            no_escape,
+        );
+
+        // Assert
+        assert_eq!("# INV-0001\n- api AUD 10.00\n- web AUD 5.00\nend", res);
+    }
+
+    #[test]
+    fn render_escapes_values_only() {
+        // Arrange
+        let mut ctx = Context::default();
+        ctx.set("client", "Smith & <Sons>");
+
+        // Act
+        let res = render("<p>{{client}}</p>", &ctx, escape_html);
+
+        // Assert
+        assert_eq!("<p>Smith &amp; &lt;Sons&gt;</p>", res);
+    }
+}
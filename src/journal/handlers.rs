@@ -0,0 +1,94 @@
+use crate::{
+    model::{Operation, OperationState},
+    print::table::Table,
+    repository::Repository,
+};
+
+/// Start recording the changes a command makes, so it can be undone. Returns the ID of the
+/// operation, or `None` if it could not be started.
+///
+/// # Arguments:
+///
+/// * `command` - Command line being run
+pub fn begin(repo: &impl Repository, command: &str) -> Option<u32> {
+    match repo.begin_operation(command) {
+        Ok(id) => Some(id),
+        Err(e) => {
+            eprintln!("Failed to record command in the journal: {}", e);
+            None
+        }
+    }
+}
+
+/// Stop recording the changes of a command.
+///
+/// # Arguments:
+///
+/// * `id` - ID of the operation returned by `begin`
+pub fn end(repo: &impl Repository, id: u32) {
+    if let Err(e) = repo.end_operation(&id) {
+        eprintln!("Failed to record command in the journal: {}", e);
+    }
+}
+
+fn describe(operation: &Operation) -> String {
+    format!(
+        "`arrow {}` from {} ({} change(s))",
+        operation.command,
+        operation.created.format("%Y-%m-%d %H:%M"),
+        operation.changes
+    )
+}
+
+/// Revert the most recent command that changed anything.
+pub fn undo(repo: &impl Repository) {
+    match repo.undo() {
+        Ok(Some(operation)) => println!("Undid {}", describe(&operation)),
+        Ok(None) => eprintln!("Nothing to undo."),
+        Err(e) => eprintln!("Failed to undo: {}", e),
+    }
+}
+
+/// Replay the command undone last.
+pub fn redo(repo: &impl Repository) {
+    match repo.redo() {
+        Ok(Some(operation)) => println!("Redid {}", describe(&operation)),
+        Ok(None) => eprintln!("Nothing to redo."),
+        Err(e) => eprintln!("Failed to redo: {}", e),
+    }
+}
+
+/// List the commands that changed data, newest first.
+///
+/// # Arguments:
+///
+/// * `limit` - Most commands to list
+pub fn history(repo: &impl Repository, limit: usize) {
+    let operations = match repo.all_operations() {
+        Ok(operations) => operations,
+        Err(e) => return eprintln!("Error retrieving history: {}", e),
+    };
+
+    let mut table = Table::new(vec![
+        "ID".to_string(),
+        "When".to_string(),
+        "Command".to_string(),
+        "Changes".to_string(),
+        "State".to_string(),
+    ]);
+    for operation in operations.iter().rev().take(limit) {
+        table.add_row(vec![
+            operation.id.to_string(),
+            operation.created.format("%Y-%m-%d %H:%M").to_string(),
+            operation.command.clone(),
+            operation.changes.to_string(),
+            match operation.state {
+                OperationState::Recording => "running",
+                OperationState::Done => "done",
+                OperationState::Undone => "undone",
+            }
+            .to_string(),
+        ]);
+    }
+    table.print(&mut std::io::stdout());
+}
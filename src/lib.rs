@@ -1,7 +1,23 @@
+pub mod backup;
 pub mod cli;
+pub mod client;
+pub mod clock;
+pub mod config;
+pub mod db;
+pub mod export;
+pub mod formats;
+pub mod goal;
+pub mod import;
+pub mod integrity;
+pub mod invoice;
+pub mod journal;
 pub mod log;
 pub mod model;
+pub mod money;
 pub mod print;
 pub mod project;
+pub mod rate;
+pub mod report;
 pub mod repository;
-pub mod time;
\ No newline at end of file
+pub mod rounding;
+pub mod time;
@@ -3,19 +3,24 @@ use std::sync::{
     Arc,
 };
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Duration, Local};
 
 use crate::{
-    model::Log,
-    project,
-    repository::{Repository, Sqlite},
+    clock::Clock,
+    log::{
+        print,
+        search::{Matching, Pattern},
+    },
+    model::{Log, Project},
+    project::{self, budget, handlers::ProjectChanges, resolve::resolve},
+    repository::{atomically, Repository},
+    time::{
+        parse_datetime,
+        zone::{DisplayZone, RecordedZone},
+    },
 };
 
-fn repo_conn() -> impl Repository {
-    Sqlite::new().expect("Failed to connect to repository!")
-}
-
-fn track_work() -> (DateTime<Local>, DateTime<Local>) {
+fn track_work(clock: &impl Clock) -> (DateTime<Local>, DateTime<Local>) {
     let interrupted = Arc::new(AtomicBool::new(false));
     let interrupted_clone = interrupted.clone();
 
@@ -24,59 +29,606 @@ fn track_work() -> (DateTime<Local>, DateTime<Local>) {
     })
     .expect("Error setting Ctrl+C handler");
 
-    let start = Local::now();
+    let start = clock.now();
     println!("Started log at {}", start);
 
     // hold thread until ctrl c is pressed
     while !interrupted.load(Ordering::SeqCst) {}
 
-    let end = Local::now();
+    let end = clock.now();
     println!("Finished log at {}", end);
 
     (start, end)
 }
 
-pub fn new(proj_name: String, msg: String) {
-    let repo = repo_conn();
+/// Prints a warning for every budget the project, or a project it is nested under, has used up.
+fn warn_over_budget(repo: &impl Repository, clock: &impl Clock, proj: &Project) {
+    let now = clock.now();
+    let projects = match repo.all_projects() {
+        Ok(projects) => projects,
+        Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+    };
+    let logs = match project::handlers::budget_logs(repo, &projects, now) {
+        Ok(logs) => logs,
+        Err(e) => return eprintln!("Error retrieving logs: {}", e),
+    };
 
-    match repo.get_project_by_name(&proj_name) {
-        Err(e) => match e {
-            rusqlite::Error::QueryReturnedNoRows => eprintln!(
-                "Project {} not found. Would you like to create it? (Y/N)",
-                proj_name
-            ),
-            _ => eprintln!("Error retrieving project: {}", e),
-        },
+    for warning in budget::overruns(proj, &projects, &logs, now) {
+        eprintln!("{}", warning);
+    }
+}
+
+/// Saves a new log and recalculates the duration of its project, as one transaction.
+fn save(repo: &impl Repository, log: &Log) {
+    let saved = atomically(repo, || {
+        repo.save_log(&log.proj_id, log)?;
+        project::handlers::update(repo, log.proj_id, ProjectChanges::default())
+    });
+
+    match saved {
+        Ok(_) => println!("Created log {}. Project duration updated.", log.message),
+        Err(e) => eprintln!("Failed to save log: {}", e),
+    }
+}
+
+/// Track time against a project until interrupted, then save it as a log.
+///
+/// # Arguments:
+///
+/// * `proj_ref` - ID, name, alias or abbreviation of the project
+/// * `msg` - Description of the work
+/// * `tags` - Tags to attach to the log
+/// * `billable` - Whether the time can be charged to the client
+pub fn new(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    proj_ref: String,
+    msg: String,
+    tags: Vec<String>,
+    billable: bool,
+) {
+    match resolve(repo, &proj_ref) {
+        Err(e) => eprintln!("{}", e),
         Ok(p) => {
-            let work_time = track_work();
-            let log = Log::new(0, p.id, msg, work_time.0, work_time.1);
-
-            match repo.save_log(&p.id, &log) {
-                Err(e) => eprintln!("Failed to save log: {}", e),
-                Ok(_) => {
-                    println!("Created log {}. Updating project duration...", log.message);
-                    project::handlers::update(p.id, None, None);
+            warn_over_budget(repo, clock, &p);
+            let work_time = track_work(clock);
+            let mut log = Log::new(0, p.id, msg, work_time.0, work_time.1);
+            log.tags = tags;
+            log.billable = billable;
+            log.zone = Some(RecordedZone::current(work_time.0));
+
+            save(repo, &log);
+        }
+    };
+}
+
+/// Add a log that finished just now, for time that was not tracked live.
+///
+/// # Arguments:
+///
+/// * `proj_ref` - ID, name, alias or abbreviation of the project
+/// * `msg` - Description of the work
+/// * `minutes` - How long the work took
+/// * `tags` - Tags to attach to the log
+/// * `billable` - Whether the time can be charged to the client
+pub fn add(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    proj_ref: String,
+    msg: String,
+    minutes: u16,
+    tags: Vec<String>,
+    billable: bool,
+) {
+    let p = match resolve(repo, &proj_ref) {
+        Ok(p) => p,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let end = clock.now();
+    let mut log = Log::new(0, p.id, msg, end - Duration::minutes(minutes as i64), end);
+    log.tags = tags;
+    log.billable = billable;
+    log.zone = Some(RecordedZone::current(end));
+
+    save(repo, &log);
+}
+
+/// Edit a log. Only the given fields are changed. Logs that have been invoiced are locked.
+///
+/// # Arguments:
+///
+/// * `id` - ID of the log to edit
+/// * `msg` - New description of the work
+/// * `start` - New start, e.g. 2026-09-30 09:00
+/// * `end` - New end, e.g. 2026-09-30 17:00
+/// * `billable` - Whether the time can be charged to the client
+/// * `tags` - Replacement tags. Existing tags are kept when empty
+pub fn edit(
+    repo: &impl Repository,
+    id: u32,
+    msg: Option<String>,
+    start: Option<String>,
+    end: Option<String>,
+    billable: Option<bool>,
+    tags: Vec<String>,
+) {
+    let mut log = match repo.get_log(&id) {
+        Ok(log) => log,
+        Err(_) => return eprintln!("No log with ID {} was found. Is it the right ID?", id),
+    };
+    if log.invoice_id.is_some() {
+        return eprintln!("Log {} has been invoiced and can no longer be changed.", id);
+    }
+
+    if let Some(msg) = msg {
+        log.message = msg;
+    }
+    for (value, field) in [(start, &mut log.start), (end, &mut log.end)] {
+        if let Some(value) = value {
+            match parse_datetime(&value) {
+                Some(dt) => *field = dt,
+                None => {
+                    return eprintln!(
+                        "Invalid time \"{}\". Times should look like 2026-09-30 17:00",
+                        value
+                    )
                 }
             }
         }
+    }
+    if log.end < log.start {
+        return eprintln!("A log cannot end before it starts.");
+    }
+    if let Some(billable) = billable {
+        log.billable = billable;
+    }
+    if !tags.is_empty() {
+        log.tags = tags;
+    }
+
+    let updated = atomically(repo, || {
+        let updated = repo.update_log(&log)?;
+        if updated > 0 {
+            project::handlers::update(repo, log.proj_id, ProjectChanges::default())?;
+        }
+        Ok::<_, rusqlite::Error>(updated)
+    });
+
+    match updated {
+        Ok(0) => eprintln!("Log {} has been invoiced and can no longer be changed.", id),
+        Ok(_) => println!("Updated log {}. Project duration updated.", id),
+        Err(e) => eprintln!("Failed to update log: {}", e),
+    }
+}
+
+/// Delete a log. Logs that have been invoiced are locked.
+///
+/// # Arguments:
+///
+/// * `id` - ID of the log to delete
+pub fn remove(repo: &impl Repository, id: u32) {
+    let log = match repo.get_log(&id) {
+        Ok(log) => log,
+        Err(_) => return eprintln!("No log with ID {} was found. Is it the right ID?", id),
     };
+    if log.invoice_id.is_some() {
+        return eprintln!("Log {} has been invoiced and can no longer be changed.", id);
+    }
+
+    let removed = atomically(repo, || {
+        let deleted = repo.delete_log(&log.proj_id, &id)?;
+        project::handlers::update(repo, log.proj_id, ProjectChanges::default())?;
+        Ok::<_, rusqlite::Error>(deleted)
+    });
+
+    match removed {
+        Ok(0) => eprintln!("No log with ID {} was found. Is it the right ID?", id),
+        Ok(_) => println!("Deleted log {}. Project duration updated.", id),
+        Err(e) => eprintln!("Failed to delete log: {}", e),
+    }
 }
 
-// pub fn save_log(proj_id: &u32, msg: Option<String>, dur: &u16) -> Option<Log> {
-//     let repo = Repository::new();
+/// Show what a log said before each change to its message or times, and who changed it.
+///
+/// # Arguments:
+///
+/// * `id` - ID of the log
+/// * `zone` - Zone to show the times in
+pub fn history(repo: &impl Repository, id: u32, zone: DisplayZone) {
+    let log = match repo.get_log(&id) {
+        Ok(log) => log,
+        Err(_) => return eprintln!("No log with ID {} was found. Is it the right ID?", id),
+    };
+    let revisions = match repo.log_history(&id) {
+        Ok(revisions) => revisions,
+        Err(e) => return eprintln!("Error retrieving log history: {}", e),
+    };
 
-//     let mut log = Log::new_no_timestamp(0, *proj_id, msg, *dur as i64);
-//     log.id = repo.save_log(&log).expect("Failed to save log!");
+    if revisions.is_empty() {
+        return println!("Log {} has not been edited.", id);
+    }
+    print::print_history(&revisions, &log, zone);
+}
 
-//     // println!("Added log {}", log);
-//     Some(log)
-// }
+/// Find the logs whose message matches, in every project or one, and show them under their
+/// projects.
+///
+/// # Arguments:
+///
+/// * `text` - Text, glob or regular expression to look for
+/// * `proj_ref` - Optional ID, name, alias or abbreviation of the only project to search
+/// * `matching` - How `text` is matched against the messages
+/// * `zone` - Zone to show the times in
+pub fn search(
+    repo: &impl Repository,
+    text: String,
+    proj_ref: Option<String>,
+    matching: Matching,
+    zone: DisplayZone,
+) {
+    let pattern = match Pattern::new(&text, matching) {
+        Ok(pattern) => pattern,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let proj = match proj_ref.map(|r| resolve(repo, &r)).transpose() {
+        Ok(proj) => proj,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let logs = match repo.search_logs(proj.map(|p| p.id), &pattern) {
+        Ok(logs) => logs,
+        Err(e) => return eprintln!("Error searching logs: {}", e),
+    };
+    if logs.is_empty() {
+        return println!("No logs match \"{}\".", text);
+    }
 
-// pub fn remove_log(proj_id: &u32, log_id: &u32) -> Option<Log> {
-//     let repo = Repository::new();
-//     repo.remove_log(proj_id, log_id);
-//     None
-// }
+    let projects = match repo.all_projects() {
+        Ok(projects) => projects,
+        Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+    };
+    for (_, proj) in project::tree::flatten(&projects) {
+        let found: Vec<Log> = logs
+            .iter()
+            .filter(|l| l.proj_id == proj.id)
+            .cloned()
+            .collect();
+        if !found.is_empty() {
+            println!("{}", project::tree::path(proj, &projects));
+            print::print_logs(&found, zone);
+            println!();
+        }
+    }
+}
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use crate::{
+        clock::{in_zone, FixedClock, CENTRAL_EUROPE},
+        repository::MockRepository,
+        time::to_datetime,
+    };
+
+    use super::*;
+
+    fn test_log(invoice_id: Option<u32>) -> Log {
+        let mut log = Log::new(1, 2, "work".to_owned(), to_datetime(100), to_datetime(400));
+        log.invoice_id = invoice_id;
+        log
+    }
+
+    /// A repository with one project, web, that expects a log to be added to it and passes the
+    /// log to `check`
+    fn repo_expecting_log(check: fn(&Log) -> bool) -> MockRepository {
+        let mut repo = MockRepository::new();
+        repo.expect_all_projects().returning(|| {
+            let mut proj = Project::new("web".to_owned(), None, to_datetime(0), to_datetime(0));
+            proj.id = 2;
+            Ok(vec![proj])
+        });
+        repo.expect_all_aliases().returning(|| Ok(Vec::new()));
+        repo.expect_begin_transaction().returning(|| Ok(()));
+        repo.expect_save_log()
+            .withf(move |_, log| check(log))
+            .times(1)
+            .returning(|_, _| Ok(1));
+        repo.expect_get_project().returning(|_| {
+            Ok((
+                Project::new("web".to_owned(), None, to_datetime(0), to_datetime(0)),
+                Vec::new(),
+            ))
+        });
+        repo.expect_update_project().returning(|_| Ok(1));
+        repo.expect_commit_transaction().returning(|| Ok(()));
+        repo
+    }
+
+    #[test]
+    fn search_in_project_should_only_search_that_project() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_all_projects().returning(|| {
+            let mut proj = Project::new("web".to_owned(), None, to_datetime(0), to_datetime(0));
+            proj.id = 2;
+            Ok(vec![proj])
+        });
+        repo.expect_all_aliases().returning(|| Ok(Vec::new()));
+        repo.expect_search_logs()
+            .withf(|proj_id, pattern| *proj_id == Some(2) && pattern.matches("Deploy api"))
+            .times(1)
+            .returning(|_, _| Ok(vec![test_log(None)]));
+
+        // Act
+        search(
+            &repo,
+            "deploy".to_owned(),
+            Some("web".to_owned()),
+            Matching::Text,
+            DisplayZone::Current,
+        );
+    }
+
+    #[test]
+    fn search_with_invalid_regex_should_not_search() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_search_logs().times(0);
+
+        // Act
+        search(
+            &repo,
+            "(deploy".to_owned(),
+            None,
+            Matching::Regex,
+            DisplayZone::Current,
+        );
+    }
+
+    #[test]
+    fn add_should_end_log_now() {
+        // Arrange
+        let clock = FixedClock::at(to_datetime(1_000_000));
+        let repo = repo_expecting_log(|log| {
+            (log.start.timestamp(), log.end.timestamp()) == (1_000_000 - 5400, 1_000_000)
+        });
+
+        // Act
+        add(
+            &repo,
+            &clock,
+            "web".to_owned(),
+            "work".to_owned(),
+            90,
+            Vec::new(),
+            true,
+        );
+    }
+
+    #[test]
+    fn add_in_zone_without_name_should_record_offset() {
+        if in_zone(
+            CENTRAL_EUROPE,
+            concat!(
+                module_path!(),
+                "::add_in_zone_without_name_should_record_offset"
+            ),
+        ) {
+            return;
+        }
+
+        // Arrange
+        let clock = FixedClock::at(parse_datetime("2026-10-20 09:00").unwrap());
+        let repo =
+            repo_expecting_log(|log| log.zone.is_some_and(|zone| zone.to_string() == "+02:00"));
+
+        // Act
+        add(
+            &repo,
+            &clock,
+            "web".to_owned(),
+            "work".to_owned(),
+            30,
+            Vec::new(),
+            true,
+        );
+    }
+
+    #[test]
+    fn add_across_midnight_should_start_the_day_before() {
+        // Arrange
+        let clock = FixedClock::at(parse_datetime("2026-10-20 00:20").unwrap());
+        let repo = repo_expecting_log(|log| {
+            log.start == parse_datetime("2026-10-19 23:20").unwrap()
+                && log.end == parse_datetime("2026-10-20 00:20").unwrap()
+        });
+
+        // Act
+        add(
+            &repo,
+            &clock,
+            "web".to_owned(),
+            "work".to_owned(),
+            60,
+            Vec::new(),
+            true,
+        );
+    }
+
+    #[test]
+    fn add_across_clocks_going_forward_should_last_the_minutes_given() {
+        if in_zone(
+            CENTRAL_EUROPE,
+            concat!(
+                module_path!(),
+                "::add_across_clocks_going_forward_should_last_the_minutes_given"
+            ),
+        ) {
+            return;
+        }
+
+        // Arrange
+        let clock = FixedClock::at(parse_datetime("2026-03-29 03:15").unwrap());
+        let repo = repo_expecting_log(|log| {
+            log.start == parse_datetime("2026-03-29 01:45").unwrap()
+                && log.end - log.start == Duration::minutes(30)
+        });
+
+        // Act
+        add(
+            &repo,
+            &clock,
+            "web".to_owned(),
+            "work".to_owned(),
+            30,
+            Vec::new(),
+            true,
+        );
+    }
+
+    #[test]
+    fn edit_invoiced_log_should_not_update_log() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_get_log().returning(|_| Ok(test_log(Some(3))));
+        repo.expect_begin_transaction().never();
+        repo.expect_update_log().never();
+
+        // Act
+        edit(
+            &repo,
+            1,
+            Some("changed".to_owned()),
+            None,
+            None,
+            None,
+            Vec::new(),
+        );
+    }
+
+    #[test]
+    fn edit_end_before_start_should_not_update_log() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_get_log().returning(|_| Ok(test_log(None)));
+        repo.expect_begin_transaction().never();
+        repo.expect_update_log().never();
+
+        // Act
+        edit(
+            &repo,
+            1,
+            None,
+            Some("2099-01-01 09:00".to_owned()),
+            None,
+            None,
+            Vec::new(),
+        );
+    }
+
+    #[test]
+    fn edit_should_update_log_and_project_duration_in_one_transaction() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_get_log().returning(|_| Ok(test_log(None)));
+        repo.expect_begin_transaction()
+            .times(1)
+            .returning(|| Ok(()));
+        repo.expect_update_log()
+            .withf(|log| log.message == "changed" && log.tags == vec!["calls".to_owned()])
+            .times(1)
+            .returning(|_| Ok(1));
+        repo.expect_get_project().returning(|id| {
+            let mut log = test_log(None);
+            log.message = "changed".to_owned();
+            Ok((
+                Project::new("web".to_owned(), None, to_datetime(0), to_datetime(0)),
+                vec![Log {
+                    proj_id: *id,
+                    ..log
+                }],
+            ))
+        });
+        repo.expect_update_project()
+            .withf(|proj| proj.duration == Duration::seconds(300))
+            .times(1)
+            .returning(|_| Ok(1));
+        repo.expect_commit_transaction()
+            .times(1)
+            .returning(|| Ok(()));
+        repo.expect_rollback_transaction().never();
+
+        // Act
+        edit(
+            &repo,
+            1,
+            Some("changed".to_owned()),
+            None,
+            None,
+            None,
+            vec!["calls".to_owned()],
+        );
+    }
+
+    #[test]
+    fn remove_invoiced_log_should_not_delete_log() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_get_log().returning(|_| Ok(test_log(Some(3))));
+        repo.expect_delete_log().never();
+
+        // Act
+        remove(&repo, 1);
+    }
+
+    #[test]
+    fn remove_should_delete_log_and_update_project_duration() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_get_log().returning(|_| Ok(test_log(None)));
+        repo.expect_begin_transaction()
+            .times(1)
+            .returning(|| Ok(()));
+        repo.expect_delete_log()
+            .withf(|proj_id, log_id| (*proj_id, *log_id) == (2, 1))
+            .times(1)
+            .returning(|_, _| Ok(1));
+        repo.expect_get_project().returning(|_| {
+            Ok((
+                Project::new("web".to_owned(), None, to_datetime(0), to_datetime(0)),
+                Vec::new(),
+            ))
+        });
+        repo.expect_update_project()
+            .withf(|proj| proj.duration == Duration::zero())
+            .times(1)
+            .returning(|_| Ok(1));
+        repo.expect_commit_transaction()
+            .times(1)
+            .returning(|| Ok(()));
+
+        // Act
+        remove(&repo, 1);
+    }
+
+    #[test]
+    fn remove_failing_should_roll_back_without_updating_project() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_get_log().returning(|_| Ok(test_log(None)));
+        repo.expect_begin_transaction()
+            .times(1)
+            .returning(|| Ok(()));
+        repo.expect_delete_log()
+            .times(1)
+            .returning(|_, _| Err(rusqlite::Error::QueryReturnedNoRows));
+        repo.expect_update_project().never();
+        repo.expect_commit_transaction().never();
+        repo.expect_rollback_transaction()
+            .times(1)
+            .returning(|| Ok(()));
+
+        // Act
+        remove(&repo, 1);
+    }
+}
@@ -1,3 +1,4 @@
 pub mod handlers;
 pub mod print;
 mod repository;
+pub mod search;
@@ -1,19 +1,68 @@
-// use prettytable::{Table, row};
+use crate::{
+    model::{Log, LogRevision},
+    print::table::Table,
+    time::{duration_hours, zone::DisplayZone},
+};
 
-// use crate::model::Log;
+/// Prints the logs with their times shown in the given zone.
+pub fn print_logs(logs: &[Log], zone: DisplayZone) {
+    let mut table = Table::new(vec![
+        "ID".to_string(),
+        "Message".to_string(),
+        "Start".to_string(),
+        "End".to_string(),
+        "Duration".to_string(),
+        "Billable".to_string(),
+        "Tags".to_string(),
+    ]);
+    for log in logs {
+        table.add_row(vec![
+            log.id.to_string(),
+            match log.edited {
+                true => format!("{} (edited)", log.message),
+                false => log.message.clone(),
+            },
+            zone.show(&log.start, log.zone),
+            zone.show(&log.end, log.zone),
+            duration_hours(log.seconds()),
+            if log.billable { "yes" } else { "no" }.to_string(),
+            log.tags.join(", "),
+        ]);
+    }
+    table.print(&mut std::io::stdout());
+}
 
-// pub fn print_table(logs: &Vec<Log>) {
-//     let mut table = Table::new();
-
-//     table.add_row(row!["ID", "MESSAGE", "START", "END", "DURATION (s)"]);
-
-//     for l in logs {
-//         let msg = l.message.clone().unwrap_or(String::default());
-//         let start_str = l.unwrap_start_str();
-//         let end_str = l.unwrap_end_str();
-
-//         table.add_row(row![l.id, msg, start_str, end_str, l.duration]);
-//     }
-
-//     table.printstd();
-// }
+/// Prints what a log said before each change, oldest first, then what it says now. Earlier
+/// times are shown in the zone the log was recorded in when asked to, like its current times.
+pub fn print_history(revisions: &[LogRevision], current: &Log, zone: DisplayZone) {
+    let mut table = Table::new(vec![
+        "Revision".to_string(),
+        "Changed".to_string(),
+        "By".to_string(),
+        "Message".to_string(),
+        "Start".to_string(),
+        "End".to_string(),
+    ]);
+    for (i, revision) in revisions.iter().enumerate() {
+        table.add_row(vec![
+            match i {
+                0 => "original".to_string(),
+                i => i.to_string(),
+            },
+            revision.changed.format("%Y-%m-%d %H:%M").to_string(),
+            revision.changed_by.clone(),
+            revision.message.clone(),
+            zone.show(&revision.start, current.zone),
+            zone.show(&revision.end, current.zone),
+        ]);
+    }
+    table.add_row(vec![
+        "current".to_string(),
+        String::new(),
+        String::new(),
+        current.message.clone(),
+        zone.show(&current.start, current.zone),
+        zone.show(&current.end, current.zone),
+    ]);
+    table.print(&mut std::io::stdout());
+}
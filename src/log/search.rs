@@ -0,0 +1,218 @@
+use clap::ValueEnum;
+use regex::{Regex, RegexBuilder};
+use regex_syntax::hir::{Hir, HirKind};
+
+/// How the text searched for is matched against the messages of logs
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Matching {
+    /// Messages containing the text anywhere, ignoring case
+    #[default]
+    Text,
+    /// Whole messages matching a glob, where `*` is any text, `?` any character and `[a-z]` any
+    /// of a set, e.g. "deploy*". Case matters
+    Glob,
+    /// Messages a regular expression matches part of, e.g. "^(fix|bug)". Case matters unless
+    /// the expression starts with (?i)
+    Regex,
+}
+
+/// Text to search the messages of logs for, ready to be matched
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Text(String),
+    /// The glob, and the regular expression it is matched with
+    Glob(String, Regex),
+    Regex(Regex),
+}
+
+impl Pattern {
+    /// # Arguments
+    ///
+    /// * `text` - Text, glob or regular expression to search for
+    /// * `matching` - How `text` is matched
+    pub fn new(text: &str, matching: Matching) -> Result<Self, String> {
+        match matching {
+            Matching::Text => Ok(Pattern::Text(text.to_owned())),
+            Matching::Glob => Ok(Pattern::Glob(text.to_owned(), glob_regex(text))),
+            Matching::Regex => Regex::new(text)
+                .map(Pattern::Regex)
+                .map_err(|e| format!("Invalid regular expression: {}", e)),
+        }
+    }
+
+    /// Whether a message matches
+    pub fn matches(&self, message: &str) -> bool {
+        match self {
+            Pattern::Text(text) => message.to_lowercase().contains(&text.to_lowercase()),
+            Pattern::Glob(_, regex) | Pattern::Regex(regex) => regex.is_match(message),
+        }
+    }
+
+    /// The longest text every message a regular expression matches contains, exactly as
+    /// written, when it is at least 3 characters long, so the search index can find it. None
+    /// for expressions that match text in other ways, e.g. `(?i)deploy` or `fix|bug`
+    pub fn required_text(&self) -> Option<String> {
+        let Pattern::Regex(regex) = self else {
+            return None;
+        };
+        let hir = regex_syntax::parse(regex.as_str()).ok()?;
+
+        required(&hir)
+            .into_iter()
+            .filter(|text| text.chars().count() >= 3)
+            .max_by_key(|text| text.chars().count())
+    }
+}
+
+/// Texts every match of an expression contains. Runs of literals next to each other are joined
+fn required(hir: &Hir) -> Vec<String> {
+    match hir.kind() {
+        HirKind::Literal(literal) => std::str::from_utf8(&literal.0)
+            .map(str::to_owned)
+            .into_iter()
+            .collect(),
+        HirKind::Capture(capture) => required(&capture.sub),
+        HirKind::Repetition(repetition) if repetition.min > 0 => required(&repetition.sub),
+        HirKind::Concat(subs) => {
+            let mut texts = Vec::new();
+            let mut run = String::new();
+            for sub in subs {
+                let literal = match sub.kind() {
+                    HirKind::Literal(literal) => std::str::from_utf8(&literal.0).ok(),
+                    _ => None,
+                };
+                match literal {
+                    Some(text) => run.push_str(text),
+                    None => {
+                        texts.push(std::mem::take(&mut run));
+                        texts.extend(required(sub));
+                    }
+                }
+            }
+            texts.push(run);
+            texts
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// A set no character is in
+const NOTHING: &str = "[^\\s\\S]";
+
+/// A regular expression matching what SQLite's GLOB operator matches
+fn glob_regex(glob: &str) -> Regex {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            '[' => {
+                let mut set = String::from("[");
+                if chars.next_if_eq(&'^').is_some() {
+                    set.push('^');
+                }
+                // a `]` straight after the opening bracket is part of the set
+                if chars.next_if_eq(&']').is_some() {
+                    set.push_str("\\]");
+                }
+                let mut closed = false;
+                for c in chars.by_ref() {
+                    match c {
+                        ']' => {
+                            closed = true;
+                            break;
+                        }
+                        '-' => set.push('-'),
+                        c => set.push_str(&regex::escape(&c.to_string())),
+                    }
+                }
+                set.push(']');
+
+                // an unclosed set matches nothing, as it does in SQLite
+                match closed {
+                    true => pattern.push_str(&set),
+                    false => pattern.push_str(NOTHING),
+                }
+            }
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    // sets with ranges running backwards, e.g. [z-a], match nothing either
+    RegexBuilder::new(&pattern)
+        .dot_matches_new_line(true)
+        .build()
+        .unwrap_or_else(|_| Regex::new(NOTHING).expect("Failed to build empty set"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_should_match_anywhere_ignoring_case() {
+        // Arrange
+        let pattern = Pattern::new("LOGIN", Matching::Text).unwrap();
+
+        // Assert
+        assert!(pattern.matches("fix login page"));
+        assert!(!pattern.matches("fix log in page"));
+    }
+
+    #[test]
+    fn glob_should_match_whole_message() {
+        // Arrange
+        let pattern = Pattern::new("[dD]eploy v?.* (*)", Matching::Glob).unwrap();
+
+        // Assert
+        assert!(pattern.matches("Deploy v2.1 (prod)"));
+        assert!(pattern.matches("deploy v3. (a\nb)"));
+        assert!(!pattern.matches("deploy v2.1 (prod) again"));
+        assert!(!pattern.matches("redeploy v2.1 (prod)"));
+    }
+
+    #[test]
+    fn glob_sets_should_match_like_sqlite() {
+        // Act
+        let negated = Pattern::new("[^a-c]x", Matching::Glob).unwrap();
+        let bracket = Pattern::new("[]]", Matching::Glob).unwrap();
+        let unclosed = Pattern::new("[ab", Matching::Glob).unwrap();
+        let backwards = Pattern::new("[z-a]", Matching::Glob).unwrap();
+
+        // Assert
+        assert!(negated.matches("dx") && !negated.matches("bx"));
+        assert!(bracket.matches("]"));
+        assert!(!unclosed.matches("[ab") && !unclosed.matches("a"));
+        assert!(!backwards.matches("m"));
+    }
+
+    #[test]
+    fn required_text_is_longest_literal_every_match_contains() {
+        // Arrange
+        let required = |regex: &str| {
+            Pattern::new(regex, Matching::Regex)
+                .unwrap()
+                .required_text()
+        };
+
+        // Assert
+        assert_eq!(Some("deploy v".to_owned()), required("^deploy v[0-9]+"));
+        assert_eq!(Some("login".to_owned()), required("(fix )?(login)+ page?"));
+        assert_eq!(Some("fix login".to_owned()), required("^fix login\\b"));
+        assert_eq!(None, required("(?i)deploy"));
+        assert_eq!(None, required("fix|bug"));
+        assert_eq!(None, required("ab.cd"));
+    }
+
+    #[test]
+    fn invalid_regex_should_be_rejected() {
+        // Act
+        let res = Pattern::new("(unclosed", Matching::Regex);
+
+        // Assert
+        assert!(res.unwrap_err().starts_with("Invalid regular expression"));
+    }
+}
@@ -5,8 +5,7 @@ use arrow::cli::*;
 fn main() {
     let cli = Cli::parse();
 
-    match &cli.cmd {
-        Some(cmd) => handle(&cmd),
-        None => {}
+    if let Some(cmd) = &cli.cmd {
+        handle(cmd)
     }
 }
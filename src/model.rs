@@ -1,12 +1,42 @@
 use chrono::{DateTime, Duration, Local};
+use clap::ValueEnum;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 
+use crate::time::{self, zone::RecordedZone};
+
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Project {
     pub id: u32,
     pub name: String,
     pub description: Option<String>,
     pub created: DateTime<Local>,
     pub updated: DateTime<Local>,
-    pub duration: u32
+    /// Time logged directly against the project, excluding sub-projects
+    #[serde(with = "time::millis")]
+    pub duration: Duration,
+    /// Project this one is nested under, if any
+    pub parent_id: Option<u32>,
+    /// Client the project is billed to, if any. Sub-projects without one inherit their parent's
+    pub client_id: Option<u32>,
+    /// Time the project, including its sub-projects, is expected to take
+    pub budget: Option<Budget>,
+}
+
+/// How often a recurring budget starts over
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Week,
+    Month,
+}
+
+/// An estimate for the whole life of a project, or a budget that recurs every period.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Budget {
+    pub seconds: u32,
+    /// `None` for a one-off estimate
+    pub period: Option<BudgetPeriod>,
 }
 
 impl Project {
@@ -17,12 +47,15 @@ impl Project {
         updated: DateTime<Local>,
     ) -> Self {
         Project {
-            id: 0, // ID is generated by db  
+            id: 0, // ID is generated by db
             name,
             description,
             created,
             updated,
-            duration: 0
+            duration: Duration::zero(),
+            parent_id: None,
+            client_id: None,
+            budget: None,
         }
     }
 
@@ -32,7 +65,7 @@ impl Project {
         description: Option<String>,
         created: DateTime<Local>,
         updated: DateTime<Local>,
-        duration: u32
+        duration: Duration,
     ) -> Self {
         Project {
             id,
@@ -40,17 +73,32 @@ impl Project {
             description,
             created,
             updated,
-            duration
+            duration,
+            parent_id: None,
+            client_id: None,
+            budget: None,
         }
     }
 }
 
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Log {
     pub id: u32,
     pub proj_id: u32,
     pub message: String,
     pub start: DateTime<Local>,
     pub end: DateTime<Local>,
+    /// Whether the time can be charged to the client
+    pub billable: bool,
+    pub tags: Vec<String>,
+    /// Invoice the log was billed on. Invoiced logs can no longer be changed
+    pub invoice_id: Option<u32>,
+    /// Time zone the log was recorded in. Unknown for logs recorded before zones were kept
+    #[serde(default)]
+    pub zone: Option<RecordedZone>,
+    /// Whether the message or times were changed after the log was saved
+    #[serde(skip)]
+    pub edited: bool,
 }
 
 impl Log {
@@ -67,6 +115,189 @@ impl Log {
             message,
             start,
             end,
+            billable: true,
+            tags: Vec::new(),
+            invoice_id: None,
+            zone: None,
+            edited: false,
+        }
+    }
+
+    /// Time between the start and the end of the log. Logs saved before logs ending before they
+    /// start were rejected may still do so, and last no time at all
+    pub fn duration(&self) -> Duration {
+        (self.end - self.start).max(Duration::zero())
+    }
+
+    /// Whole seconds the log lasted, as time is billed, budgeted and set as goals
+    pub fn seconds(&self) -> u32 {
+        time::whole_seconds(self.duration())
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Client {
+    pub id: u32,
+    pub name: String,
+    pub contact: Option<String>,
+    /// ISO 4217 code, e.g. AUD
+    pub currency: String,
+    /// Rate charged per hour when no more specific rate applies
+    pub hourly_rate: Option<Decimal>,
+    pub address: Option<String>,
+}
+
+impl Client {
+    pub fn new(
+        name: String,
+        contact: Option<String>,
+        currency: String,
+        hourly_rate: Option<Decimal>,
+        address: Option<String>,
+    ) -> Self {
+        Client {
+            id: 0, // ID is generated by db
+            name,
+            contact,
+            currency,
+            hourly_rate,
+            address,
+        }
+    }
+}
+
+/// What a rate applies to. More specific scopes take precedence: tag, then project, then client.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RateScope {
+    Client(u32),
+    Project(u32),
+    Tag(String),
+}
+
+/// An hourly rate that applies to logs started on or after `effective_from`, until a newer rate
+/// for the same scope takes effect.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Rate {
+    pub id: u32,
+    pub scope: RateScope,
+    pub amount: Decimal,
+    pub effective_from: DateTime<Local>,
+}
+
+impl Rate {
+    pub fn new(scope: RateScope, amount: Decimal, effective_from: DateTime<Local>) -> Self {
+        Rate {
+            id: 0, // ID is generated by db
+            scope,
+            amount,
+            effective_from,
         }
     }
 }
+
+/// What a goal counts time for.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalScope {
+    /// Every log
+    Overall,
+    /// Logs against a project or its sub-projects
+    Project(u32),
+    /// Logs with a tag
+    Tag(String),
+}
+
+/// How often a goal has to be met.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalPeriod {
+    /// Every day
+    Day,
+    /// Monday to Friday. Weekends neither count towards nor break a streak
+    Weekday,
+    /// Every week, starting Monday
+    Week,
+}
+
+/// A personal target, such as 6 hours of focused work per weekday.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Goal {
+    pub id: u32,
+    pub scope: GoalScope,
+    pub seconds: u32,
+    pub period: GoalPeriod,
+}
+
+impl Goal {
+    pub fn new(scope: GoalScope, seconds: u32, period: GoalPeriod) -> Self {
+        Goal {
+            id: 0, // ID is generated by db
+            scope,
+            seconds,
+            period,
+        }
+    }
+}
+
+/// A line of an invoice, summarising the logs of one project billed at one rate
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InvoiceLine {
+    pub description: String,
+    /// Exact time logged
+    pub raw_seconds: u32,
+    /// Time billed, after rounding
+    pub seconds: u32,
+    pub rate: Decimal,
+    pub amount: Decimal,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Invoice {
+    pub id: u32,
+    /// Sequential, human facing invoice number, e.g. INV-0001
+    pub number: String,
+    pub client_id: u32,
+    pub period_start: DateTime<Local>,
+    /// Exclusive end of the billed period
+    pub period_end: DateTime<Local>,
+    pub created: DateTime<Local>,
+    pub currency: String,
+    pub lines: Vec<InvoiceLine>,
+    pub total: Decimal,
+}
+
+/// What a log said before a change to its message or times. Revisions are never changed, so the
+/// original values of a log are always kept
+#[derive(Clone, Deserialize, Serialize)]
+pub struct LogRevision {
+    pub id: u32,
+    pub log_id: u32,
+    /// When the change was made
+    pub changed: DateTime<Local>,
+    /// User who made the change
+    pub changed_by: String,
+    pub message: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+/// A command that changed data, recorded in the journal so it can be undone and redone
+#[derive(Clone)]
+pub struct Operation {
+    pub id: u32,
+    /// Command line that made the changes, e.g. `project edit web -n site`
+    pub command: String,
+    pub created: DateTime<Local>,
+    /// Number of rows the command inserted, updated or deleted
+    pub changes: u32,
+    pub state: OperationState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OperationState {
+    /// The command is still running, or was stopped before it finished
+    Recording,
+    Done,
+    Undone,
+}
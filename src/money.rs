@@ -0,0 +1,45 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Amount earned for working the given number of seconds at an hourly rate. The result is exact;
+/// round it only when presenting or invoicing.
+pub fn earnings(seconds: u32, hourly_rate: Decimal) -> Decimal {
+    hourly_rate * Decimal::from(seconds) / Decimal::from(3600)
+}
+
+/// Formats an amount to cents, rounding half away from zero, prefixed by its currency if known.
+pub fn format_amount(amount: Decimal, currency: Option<&str>) -> String {
+    let rounded = amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+    match currency {
+        Some(code) => format!("{} {:.2}", code, rounded),
+        None => format!("{:.2}", rounded),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn earnings_is_exact_for_partial_hours() {
+        // Act
+        let res = earnings(90 * 60, Decimal::new(12050, 2));
+
+        // Assert
+        assert_eq!(Decimal::new(18075, 2), res);
+    }
+
+    #[test]
+    fn earnings_does_not_round_intermediate_values() {
+        // Act
+        let res = earnings(1, Decimal::from(3600)) * Decimal::from(3);
+
+        // Assert
+        assert_eq!(Decimal::from(3), res);
+    }
+
+    #[test]
+    fn format_amount_rounds_half_away_from_zero() {
+        assert_eq!("AUD 0.13", format_amount(Decimal::new(125, 3), Some("AUD")));
+        assert_eq!("2.00", format_amount(Decimal::from(2), None));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Debug, io::Write};
+use std::io::Write;
 
 pub struct Table {
     header: Vec<String>,
@@ -36,7 +36,7 @@ impl Table {
             ));
         }
 
-        match write!(output, "{}\n", header) {
+        match writeln!(output, "{}", header) {
             Ok(_) => {}
             Err(e) => eprintln!("Error writing to output: {}", e),
         }
@@ -46,7 +46,7 @@ impl Table {
             for (i, cell) in row.iter().enumerate() {
                 row_str.push_str(&format!("{:width$} ", cell, width = max_widths[i]));
             }
-            match write!(output, "{}\n", row_str) {
+            match writeln!(output, "{}", row_str) {
                 Ok(_) => {}
                 Err(e) => eprintln!("Error writing to output: {}", e),
             }
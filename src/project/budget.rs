@@ -0,0 +1,410 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate};
+
+use crate::{
+    model::{Budget, BudgetPeriod, Log, Project},
+    project::tree,
+    time::{duration_hours, parse_duration, start_of_day, whole_seconds},
+};
+
+/// Parses a budget such as `40h`, `90m` or `1.5h`, optionally recurring per `/week` or `/month`,
+/// e.g. `20h/month`. `none` removes the budget.
+pub fn parse(text: &str) -> Result<Option<Budget>, String> {
+    let text = text.trim().to_lowercase();
+    if text == "none" {
+        return Ok(None);
+    }
+
+    let invalid = || {
+        format!(
+            "Invalid budget \"{}\". Budgets should look like 40h, 90m or 20h/month",
+            text
+        )
+    };
+
+    let (amount, period) = match text.split_once('/') {
+        Some((amount, "week")) => (amount, Some(BudgetPeriod::Week)),
+        Some((amount, "month")) => (amount, Some(BudgetPeriod::Month)),
+        Some(_) => return Err(invalid()),
+        None => (text.as_str(), None),
+    };
+
+    match parse_duration(amount) {
+        Some(seconds) => Ok(Some(Budget { seconds, period })),
+        None => Err(invalid()),
+    }
+}
+
+/// Human readable budget, e.g. `40.00h` or `20.00h/month`.
+pub fn describe(budget: &Budget) -> String {
+    let hours = format!("{}h", duration_hours(budget.seconds));
+    match budget.period {
+        Some(BudgetPeriod::Week) => format!("{}/week", hours),
+        Some(BudgetPeriod::Month) => format!("{}/month", hours),
+        None => hours,
+    }
+}
+
+/// Half-open range of the week (starting Monday) or month that `now` falls in. Periods start at
+/// midnight, or the first minute after it on days clocks go forward at midnight.
+pub fn period_range(
+    period: BudgetPeriod,
+    now: DateTime<Local>,
+) -> (DateTime<Local>, DateTime<Local>) {
+    match period {
+        BudgetPeriod::Month => {
+            let first = now.date_naive() - Duration::days(now.day0() as i64);
+            (start_of_day(first), start_of_day(first + Months::new(1)))
+        }
+        BudgetPeriod::Week => {
+            let monday =
+                now.date_naive() - Duration::days(now.weekday().num_days_from_monday() as i64);
+            (
+                start_of_day(monday),
+                start_of_day(monday + Duration::days(7)),
+            )
+        }
+    }
+}
+
+/// IDs of a project and all of its sub-projects, whose time counts against its budget.
+fn budget_ids(proj: &Project, projects: &[Project]) -> Vec<u32> {
+    let mut ids = tree::descendants(proj.id, projects);
+    ids.push(proj.id);
+    ids
+}
+
+/// Seconds counted against a project's budget. Estimates count all time logged against the
+/// project and its sub-projects, recurring budgets only count logs started in the current
+/// period.
+///
+/// # Arguments
+///
+/// * `proj` - Project with the budget
+/// * `projects` - All projects, used to find sub-projects
+/// * `logs` - Logs for the current period. Unused for estimates, which use project durations
+/// * `now` - Point in time that decides the current period
+pub fn used(proj: &Project, projects: &[Project], logs: &[Log], now: DateTime<Local>) -> u32 {
+    let ids = budget_ids(proj, projects);
+
+    match proj.budget.and_then(|b| b.period) {
+        None => whole_seconds(
+            projects
+                .iter()
+                .filter(|p| ids.contains(&p.id))
+                .map(|p| p.duration)
+                .sum(),
+        ),
+        Some(period) => {
+            let (start, end) = period_range(period, now);
+            logs.iter()
+                .filter(|l| ids.contains(&l.proj_id) && l.start >= start && l.start < end)
+                .map(Log::seconds)
+                .sum()
+        }
+    }
+}
+
+/// Time left in a budget, e.g. `12.50` or `-3.00` when over budget.
+pub fn remaining(budget: &Budget, used: u32) -> String {
+    if used > budget.seconds {
+        format!("-{}", duration_hours(used - budget.seconds))
+    } else {
+        duration_hours(budget.seconds - used)
+    }
+}
+
+/// Share of a budget used, e.g. `75%`.
+pub fn percent_used(budget: &Budget, used: u32) -> String {
+    format!("{:.0}%", used as f64 * 100.0 / budget.seconds.max(1) as f64)
+}
+
+/// Seconds logged per day, keyed by the local date the log started on.
+pub fn daily(logs: &[Log]) -> BTreeMap<NaiveDate, u32> {
+    let mut days = BTreeMap::new();
+    for log in logs {
+        *days.entry(log.start.date_naive()).or_default() += log.seconds();
+    }
+    days
+}
+
+/// Warnings for every budget the project or one of its parents has run over. Time logged
+/// against a sub-project counts towards its parents' budgets too.
+///
+/// # Arguments
+///
+/// * `proj` - Project about to be worked on
+/// * `projects` - All projects
+/// * `logs` - Logs for the current budget periods
+/// * `now` - Point in time that decides the current period
+pub fn overruns(
+    proj: &Project,
+    projects: &[Project],
+    logs: &[Log],
+    now: DateTime<Local>,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut current = Some(proj);
+    let mut depth = 0;
+
+    while let Some(p) = current {
+        if let Some(budget) = &p.budget {
+            let used = used(p, projects, logs, now);
+            if used >= budget.seconds {
+                warnings.push(format!(
+                    "Warning: {} is over budget, {}h of {} used ({}).",
+                    tree::path(p, projects),
+                    duration_hours(used),
+                    describe(budget),
+                    percent_used(budget, used)
+                ));
+            }
+        }
+        if depth > projects.len() {
+            break;
+        }
+        current = p
+            .parent_id
+            .and_then(|id| projects.iter().find(|parent| parent.id == id));
+        depth += 1;
+    }
+
+    warnings
+}
+
+/// Earliest start of the current period of any recurring budget, so the logs needed to check
+/// every budget can be loaded at once.
+pub fn earliest_period_start(
+    projects: &[Project],
+    now: DateTime<Local>,
+) -> Option<DateTime<Local>> {
+    projects
+        .iter()
+        .filter_map(|p| p.budget.and_then(|b| b.period))
+        .map(|period| period_range(period, now).0)
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        clock::{in_zone, CENTRAL_EUROPE, SKIPPED_MIDNIGHT},
+        time::{parse_date, parse_datetime, to_datetime},
+    };
+
+    use super::*;
+
+    fn project(id: u32, parent_id: Option<u32>, duration: u32, budget: Option<Budget>) -> Project {
+        let mut proj = Project::load(
+            id,
+            format!("p{}", id),
+            None,
+            to_datetime(0),
+            to_datetime(0),
+            Duration::seconds(duration.into()),
+        );
+        proj.parent_id = parent_id;
+        proj.budget = budget;
+        proj
+    }
+
+    fn log(proj_id: u32, start: &str, minutes: i64) -> Log {
+        let start = parse_datetime(start).unwrap();
+        Log::new(
+            0,
+            proj_id,
+            "work".to_owned(),
+            start,
+            start + Duration::minutes(minutes),
+        )
+    }
+
+    #[test]
+    fn parse_reads_estimates_and_recurring_budgets() {
+        assert_eq!(
+            Ok(Some(Budget {
+                seconds: 40 * 3600,
+                period: None
+            })),
+            parse("40h")
+        );
+        assert_eq!(
+            Ok(Some(Budget {
+                seconds: 90 * 60,
+                period: Some(BudgetPeriod::Week)
+            })),
+            parse("1.5h/week")
+        );
+        assert_eq!(Ok(None), parse("none"));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_units_and_periods() {
+        assert!(parse("40").is_err());
+        assert!(parse("40h/year").is_err());
+        assert!(parse("-2h").is_err());
+    }
+
+    #[test]
+    fn used_for_estimate_includes_sub_projects() {
+        // Arrange
+        let estimate = Some(Budget {
+            seconds: 3600,
+            period: None,
+        });
+        let projects = vec![
+            project(1, None, 600, estimate),
+            project(2, Some(1), 1200, None),
+            project(3, None, 5000, None),
+        ];
+
+        // Act
+        let res = used(&projects[0], &projects, &[], Local::now());
+
+        // Assert
+        assert_eq!(1800, res);
+    }
+
+    #[test]
+    fn used_for_recurring_budget_only_counts_current_period() {
+        // Arrange
+        let monthly = Some(Budget {
+            seconds: 3600,
+            period: Some(BudgetPeriod::Month),
+        });
+        let projects = vec![project(1, None, 0, monthly), project(2, Some(1), 0, None)];
+        let logs = vec![
+            log(1, "2026-08-31 23:00", 30),
+            log(1, "2026-09-01 09:00", 30),
+            log(2, "2026-09-30 09:00", 15),
+        ];
+        let now = parse_datetime("2026-09-15 12:00").unwrap();
+
+        // Act
+        let res = used(&projects[0], &projects, &logs, now);
+
+        // Assert
+        assert_eq!(45 * 60, res);
+    }
+
+    #[test]
+    fn period_range_of_week_starts_on_monday() {
+        // Arrange
+        let now = parse_datetime("2026-09-17 12:00").unwrap();
+
+        // Act
+        let (start, end) = period_range(BudgetPeriod::Week, now);
+
+        // Assert
+        assert_eq!(parse_date("2026-09-14").unwrap(), start);
+        assert_eq!(parse_date("2026-09-21").unwrap(), end);
+    }
+
+    #[test]
+    fn remaining_is_negative_when_over_budget() {
+        let budget = Budget {
+            seconds: 3600,
+            period: None,
+        };
+
+        assert_eq!("0.50", remaining(&budget, 1800));
+        assert_eq!("-1.00", remaining(&budget, 7200));
+        assert_eq!("200%", percent_used(&budget, 7200));
+    }
+
+    #[test]
+    fn overruns_warns_about_parent_budgets() {
+        // Arrange
+        let estimate = Some(Budget {
+            seconds: 3600,
+            period: None,
+        });
+        let projects = vec![
+            project(1, None, 0, estimate),
+            project(2, Some(1), 4000, None),
+        ];
+
+        // Act
+        let res = overruns(&projects[1], &projects, &[], Local::now());
+
+        // Assert
+        assert_eq!(1, res.len());
+        assert!(res[0].contains("p1"));
+    }
+
+    #[test]
+    fn period_range_of_week_clocks_go_forward_is_an_hour_short() {
+        if in_zone(
+            CENTRAL_EUROPE,
+            concat!(
+                module_path!(),
+                "::period_range_of_week_clocks_go_forward_is_an_hour_short"
+            ),
+        ) {
+            return;
+        }
+
+        // Act
+        let (start, end) = period_range(
+            BudgetPeriod::Week,
+            parse_datetime("2026-03-29 12:00").unwrap(),
+        );
+
+        // Assert
+        assert_eq!(parse_datetime("2026-03-23 00:00").unwrap(), start);
+        assert_eq!(parse_datetime("2026-03-30 00:00").unwrap(), end);
+        assert_eq!(Duration::hours(7 * 24 - 1), end - start);
+    }
+
+    #[test]
+    fn period_range_of_month_clocks_go_back_is_an_hour_long() {
+        if in_zone(
+            CENTRAL_EUROPE,
+            concat!(
+                module_path!(),
+                "::period_range_of_month_clocks_go_back_is_an_hour_long"
+            ),
+        ) {
+            return;
+        }
+
+        // Act
+        let (start, end) = period_range(
+            BudgetPeriod::Month,
+            parse_datetime("2026-10-31 23:59").unwrap(),
+        );
+
+        // Assert
+        assert_eq!(parse_datetime("2026-10-01 00:00").unwrap(), start);
+        assert_eq!(parse_datetime("2026-11-01 00:00").unwrap(), end);
+        assert_eq!(Duration::hours(31 * 24 + 1), end - start);
+    }
+
+    #[test]
+    fn period_range_starts_after_skipped_midnight() {
+        if in_zone(
+            SKIPPED_MIDNIGHT,
+            concat!(
+                module_path!(),
+                "::period_range_starts_after_skipped_midnight"
+            ),
+        ) {
+            return;
+        }
+
+        // Arrange
+        let now = parse_datetime("2025-09-03 12:00").unwrap();
+
+        // Act
+        let week = period_range(BudgetPeriod::Week, now);
+        let month = period_range(BudgetPeriod::Month, now);
+
+        // Assert
+        let start = parse_datetime("2025-09-01 01:00").unwrap();
+        assert_eq!(start, week.0);
+        assert_eq!(start, month.0);
+        assert_eq!(parse_datetime("2025-09-08 00:00").unwrap(), week.1);
+        assert_eq!(parse_datetime("2025-10-01 00:00").unwrap(), month.1);
+    }
+}
@@ -1,41 +1,120 @@
-use chrono::Local;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Local};
 
 use crate::{
-    model::{Log, Project},
+    client,
+    clock::Clock,
+    log,
+    model::{Budget, Log, Project},
     print::table::Table,
-    repository::{Repository, Sqlite}, time::duration_hours,
+    project::{budget, resolve::resolve, tree},
+    repository::Repository,
+    time::{duration_hours, whole_seconds, zone::DisplayZone},
 };
 
-fn repo_conn() -> impl Repository {
-    Sqlite::new().expect("Failed to connect to repository!")
+/// Fields to change when updating a project. `None` leaves the field as it is.
+#[derive(Default)]
+pub struct ProjectChanges {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// `Some(None)` makes the project a top-level project
+    pub parent_id: Option<Option<u32>>,
+    /// `Some(None)` removes the client
+    pub client_id: Option<Option<u32>>,
+    /// `Some(None)` removes the budget
+    pub budget: Option<Option<Budget>>,
+}
+
+/// Fields to change when editing a project, as given on the command line. `None` leaves the
+/// field as it is.
+#[derive(Default)]
+pub struct ProjectEdits {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Reference to a new parent project, or `none`
+    pub parent: Option<String>,
+    /// Reference to a new client, or `none`
+    pub client: Option<String>,
+    /// Estimate for the whole project, e.g. 40h, or `none`
+    pub estimate: Option<String>,
+    /// Budget that recurs every week or month, e.g. 20h/month, or `none`
+    pub recurring: Option<String>,
+}
+
+fn calculate_total_duration(logs: &[Log]) -> Duration {
+    logs.iter().map(Log::duration).sum()
 }
 
-fn calculate_total_duration(logs: &Vec<Log>) -> u32 {
-    let mut total = 0;
-    for log in logs.iter() {
-        total += log.end.timestamp() - log.start.timestamp();
+/// Logs needed to check the recurring budgets of the given projects in their current period.
+pub fn budget_logs(
+    repo: &impl Repository,
+    projects: &[Project],
+    now: DateTime<Local>,
+) -> rusqlite::Result<Vec<Log>> {
+    match budget::earliest_period_start(projects, now) {
+        Some(start) => repo.logs_between(&start, &(now + Duration::days(31))),
+        None => Ok(Vec::new()),
     }
+}
+
+/// Time used of each budget set on the listed projects, keyed by project ID.
+fn budget_usage(
+    repo: &impl Repository,
+    listed: &[Project],
+    projects: &[Project],
+    now: DateTime<Local>,
+) -> rusqlite::Result<HashMap<u32, u32>> {
+    let logs = budget_logs(repo, listed, now)?;
 
-    total as u32
+    Ok(listed
+        .iter()
+        .filter(|p| p.budget.is_some())
+        .map(|p| (p.id, budget::used(p, projects, &logs, now)))
+        .collect())
 }
 
-fn print_projects(projects: &Vec<Project>) {
+/// Prints projects as a tree, children indented under their parents. The total column includes
+/// the durations of all sub-projects.
+fn print_projects(projects: &[Project], used: &HashMap<u32, u32>) {
+    let own = projects.iter().map(|p| (p.id, p.duration)).collect();
+    let totals = tree::rollup(projects, &own);
+
     let mut table = Table::new(vec![
         "ID".to_string(),
         "Name".to_string(),
         "Description".to_string(),
         "Created".to_string(),
         "Updated".to_string(),
-        "Duration".to_string()
+        "Duration".to_string(),
+        "Total".to_string(),
+        "Budget".to_string(),
+        "Remaining".to_string(),
+        "Used".to_string(),
     ]);
-    for proj in projects {
+    for (depth, proj) in tree::flatten(projects) {
+        let (budget, remaining, percent) = match (&proj.budget, used.get(&proj.id)) {
+            (Some(b), Some(used)) => (
+                budget::describe(b),
+                budget::remaining(b, *used),
+                budget::percent_used(b, *used),
+            ),
+            _ => Default::default(),
+        };
+
         table.add_row(vec![
             proj.id.to_string(),
-            proj.name.clone(),
+            format!("{}{}", "  ".repeat(depth), proj.name),
             proj.description.clone().unwrap_or("".to_string()),
             proj.created.to_string(),
             proj.updated.to_string(),
-            duration_hours(proj.duration)
+            duration_hours(whole_seconds(proj.duration)),
+            duration_hours(whole_seconds(
+                totals.get(&proj.id).copied().unwrap_or_default(),
+            )),
+            budget,
+            remaining,
+            percent,
         ]);
     }
     table.print(&mut std::io::stdout());
@@ -43,19 +122,52 @@ fn print_projects(projects: &Vec<Project>) {
 
 /// Create new project.
 ///
-/// # Arguments: 
+/// # Arguments:
 ///
 /// * `name` - Name of project
-/// * `description` - Optional description of project 
-pub fn new(name: String, description: Option<String>) {
-    let repo = repo_conn();
-    match repo.get_project_by_name(&name) {
-        Ok(_) => eprintln!(
-            "A project with the name \"{}\" already exists. Skipping...",
-            name
-        ),
-        Err(e) if e == rusqlite::Error::QueryReturnedNoRows => {
-            let new_proj = Project::new(name, description, Local::now(), Local::now());
+/// * `description` - Optional description of project
+/// * `parent` - Optional reference to the project to nest the new project under
+/// * `client` - Optional reference to the client the project is billed to
+pub fn new(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    name: String,
+    description: Option<String>,
+    parent: Option<String>,
+    client: Option<String>,
+) {
+    let client_id = match client {
+        Some(reference) => match client::resolve::resolve(repo, &reference) {
+            Ok(c) => Some(c.id),
+            Err(e) => return eprintln!("{}", e),
+        },
+        None => None,
+    };
+
+    let parent_id = match parent {
+        Some(reference) => match resolve(repo, &reference) {
+            Ok(p) => Some(p.id),
+            Err(e) => return eprintln!("{}", e),
+        },
+        None => None,
+    };
+
+    match repo.all_projects() {
+        Ok(projects)
+            if projects
+                .iter()
+                .any(|p| p.name == name && p.parent_id == parent_id) =>
+        {
+            eprintln!(
+                "A project with the name \"{}\" already exists. Skipping...",
+                name
+            )
+        }
+        Ok(_) => {
+            let now = clock.now();
+            let mut new_proj = Project::new(name, description, now, now);
+            new_proj.parent_id = parent_id;
+            new_proj.client_id = client_id;
 
             match repo.save_project(&new_proj) {
                 Ok(_) => println!("{} created successfully.\n", new_proj.name),
@@ -66,49 +178,294 @@ pub fn new(name: String, description: Option<String>) {
     }
 }
 
-pub fn list() {
-    let repo = repo_conn();
-
+pub fn list(repo: &impl Repository, clock: &impl Clock) {
     match repo.all_projects() {
-        Ok(projects) => {
-            print_projects(&projects);
-        }
+        Ok(projects) => match budget_usage(repo, &projects, &projects, clock.now()) {
+            Ok(used) => print_projects(&projects, &used),
+            Err(e) => eprintln!("Error retrieving logs: {}", e),
+        },
         Err(e) => eprintln!("Error retrieving your projects: {}", e),
     }
 }
 
-pub fn update(id: u32, name: Option<String>, description: Option<String>) {
-    let repo = repo_conn();
+/// Show a project and the logs recorded against it.
+///
+/// # Arguments:
+///
+/// * `reference` - ID, name, alias or abbreviation of the project
+/// * `zone` - Zone to show the times of the logs in
+pub fn get(repo: &impl Repository, clock: &impl Clock, reference: &str, zone: DisplayZone) {
+    let proj = match resolve(repo, reference) {
+        Ok(p) => p,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let projects = match repo.all_projects() {
+        Ok(projects) => projects,
+        Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+    };
 
-    match repo.get_project(&id) {
-        Ok((mut proj, logs)) => {
-            if let Some(new_name) = name {
-                proj.name = new_name;
+    match repo.get_project(&proj.id) {
+        Ok((proj, logs)) => {
+            let proj = [proj];
+            match budget_usage(repo, &proj, &projects, clock.now()) {
+                Ok(used) => print_projects(&proj, &used),
+                Err(e) => return eprintln!("Error retrieving logs: {}", e),
             }
-            if let Some(new_desc) = description {
-                proj.description = Some(new_desc);
+            println!();
+            log::print::print_logs(&logs, zone);
+        }
+        Err(e) => eprintln!("Error retrieving project: {}", e),
+    }
+}
+
+/// Edit the project the reference resolves to.
+///
+/// # Arguments:
+///
+/// * `reference` - ID, name, alias or abbreviation of the project
+/// * `edits` - Fields to change, as given on the command line
+pub fn edit(repo: &impl Repository, reference: &str, edits: ProjectEdits) {
+    let ProjectEdits {
+        name,
+        description,
+        parent,
+        client,
+        estimate,
+        recurring,
+    } = edits;
+    let proj = match resolve(repo, reference) {
+        Ok(p) => p,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let parent_id = match parent.as_deref() {
+        Some("none") => Some(None),
+        Some(reference) => match resolve(repo, reference) {
+            Ok(parent) => Some(Some(parent.id)),
+            Err(e) => return eprintln!("{}", e),
+        },
+        None => None,
+    };
+
+    if parent_id.is_some() || name.is_some() {
+        let projects = match repo.all_projects() {
+            Ok(projects) => projects,
+            Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+        };
+
+        if let Some(Some(parent_id)) = parent_id {
+            if parent_id == proj.id || tree::descendants(proj.id, &projects).contains(&parent_id) {
+                return eprintln!(
+                    "{} cannot be nested under itself or one of its sub-projects.",
+                    proj.name
+                );
             }
+        }
+
+        let new_name = name.as_ref().unwrap_or(&proj.name);
+        let new_parent_id = parent_id.unwrap_or(proj.parent_id);
+        if projects
+            .iter()
+            .any(|p| p.id != proj.id && &p.name == new_name && p.parent_id == new_parent_id)
+        {
+            return eprintln!(
+                "A project with the name \"{}\" already exists there. Skipping...",
+                new_name
+            );
+        }
+    }
 
-            proj.duration = calculate_total_duration(&logs);
-
-            match repo.update_project(&proj) {
-                Ok(updated_rows) => {
-                    if updated_rows > 0 {
-                        println!("{} updated successfully.\n", proj.name);
-                    } else {
-                        eprintln!("No project with ID {} was found. Is it the right ID?", id);
-                    }
-                }
-                Err(e) => eprintln!("Failed to update project: {}", e),
+    let client_id = match client.as_deref() {
+        Some("none") => Some(None),
+        Some(reference) => match client::resolve::resolve(repo, reference) {
+            Ok(c) => Some(Some(c.id)),
+            Err(e) => return eprintln!("{}", e),
+        },
+        None => None,
+    };
+
+    let budget = match (estimate, recurring) {
+        (Some(text), _) => match budget::parse(&text) {
+            Ok(Some(b)) if b.period.is_some() => {
+                return eprintln!("Use --budget for budgets that recur every week or month.")
+            }
+            Ok(b) => Some(b),
+            Err(e) => return eprintln!("{}", e),
+        },
+        (None, Some(text)) => match budget::parse(&text) {
+            Ok(Some(b)) if b.period.is_none() => {
+                return eprintln!("Use --estimate for one-off estimates, or add /week or /month.")
             }
+            Ok(b) => Some(b),
+            Err(e) => return eprintln!("{}", e),
+        },
+        (None, None) => None,
+    };
+
+    let changes = ProjectChanges {
+        name,
+        description,
+        parent_id,
+        client_id,
+        budget,
+    };
+    match update(repo, proj.id, changes) {
+        Ok(Some(proj)) => println!("{} updated successfully.\n", proj.name),
+        Ok(None) => eprintln!(
+            "No project with ID {} was found. Is it the right ID?",
+            proj.id
+        ),
+        Err(e) => eprintln!("Failed to update project: {}", e),
+    }
+}
+
+/// Add an alternative name to refer to a project by.
+///
+/// # Arguments:
+///
+/// * `reference` - ID, name, alias or abbreviation of the project
+/// * `alias` - New alias. Must not already be in use
+pub fn alias(repo: &impl Repository, reference: &str, alias: String) {
+    let proj = match resolve(repo, reference) {
+        Ok(p) => p,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    match repo.save_alias(&proj.id, &alias) {
+        Ok(_) => println!("{} can now be referred to as {}.\n", proj.name, alias),
+        Err(e) => eprintln!("Failed to add alias \"{}\": {}", alias, e),
+    }
+}
+
+/// Show how a project's estimate or current budget period has been used up, day by day.
+///
+/// # Arguments:
+///
+/// * `reference` - ID, name, alias or abbreviation of the project
+pub fn burndown(repo: &impl Repository, clock: &impl Clock, reference: &str) {
+    const BAR_WIDTH: f64 = 30.0;
+
+    let proj = match resolve(repo, reference) {
+        Ok(p) => p,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let proj_budget = match proj.budget {
+        Some(b) => b,
+        None => {
+            return eprintln!(
+            "{} has no estimate or budget. Set one with `arrow project edit {} --estimate 40h`.",
+            proj.name, proj.id
+        )
         }
-        Err(e) => eprintln!("Error retrieving project: {}", e),
+    };
+    let projects = match repo.all_projects() {
+        Ok(projects) => projects,
+        Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+    };
+
+    let mut ids = tree::descendants(proj.id, &projects);
+    ids.push(proj.id);
+
+    let logs = match proj_budget.period {
+        Some(period) => {
+            let (start, end) = budget::period_range(period, clock.now());
+            repo.logs_between(&start, &end).map(|logs| {
+                logs.into_iter()
+                    .filter(|l| ids.contains(&l.proj_id))
+                    .collect()
+            })
+        }
+        None => ids
+            .iter()
+            .map(|id| repo.get_project(id).map(|(_, logs)| logs))
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map(|logs| logs.into_iter().flatten().collect::<Vec<_>>()),
+    };
+    let logs = match logs {
+        Ok(logs) => logs,
+        Err(e) => return eprintln!("Error retrieving logs: {}", e),
+    };
+
+    println!(
+        "{}: {}\n",
+        tree::path(&proj, &projects),
+        budget::describe(&proj_budget)
+    );
+
+    let mut table = Table::new(vec![
+        "Date".to_string(),
+        "Logged".to_string(),
+        "Used".to_string(),
+        "Remaining".to_string(),
+        "".to_string(),
+    ]);
+    let mut used = 0;
+    for (day, seconds) in budget::daily(&logs) {
+        used += seconds;
+        let left = proj_budget.seconds.saturating_sub(used) as f64;
+        let bar = (left / proj_budget.seconds.max(1) as f64 * BAR_WIDTH).round() as usize;
+
+        table.add_row(vec![
+            day.format("%Y-%m-%d").to_string(),
+            duration_hours(seconds),
+            budget::percent_used(&proj_budget, used),
+            budget::remaining(&proj_budget, used),
+            "#".repeat(bar),
+        ]);
+    }
+    table.print(&mut std::io::stdout());
+}
+
+/// Apply changes to a project and recalculate its duration from its logs. Returns the updated
+/// project, or `None` if there is no project with the ID.
+///
+/// # Arguments:
+///
+/// * `id` - ID of the project to update
+/// * `changes` - Fields to change
+pub fn update(
+    repo: &impl Repository,
+    id: u32,
+    changes: ProjectChanges,
+) -> rusqlite::Result<Option<Project>> {
+    let (mut proj, logs) = match repo.get_project(&id) {
+        Ok(found) => found,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    if let Some(new_name) = changes.name {
+        proj.name = new_name;
+    }
+    if let Some(new_desc) = changes.description {
+        proj.description = Some(new_desc);
+    }
+    if let Some(new_parent) = changes.parent_id {
+        proj.parent_id = new_parent;
+    }
+    if let Some(new_client) = changes.client_id {
+        proj.client_id = new_client;
+    }
+    if let Some(new_budget) = changes.budget {
+        proj.budget = new_budget;
+    }
+
+    proj.duration = calculate_total_duration(&logs);
+
+    match repo.update_project(&proj)? {
+        0 => Ok(None),
+        _ => Ok(Some(proj)),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::time::to_datetime;
+    use crate::{
+        clock::{in_zone, FixedClock, CENTRAL_EUROPE},
+        repository::MockRepository,
+        time::{parse_datetime, to_datetime},
+    };
 
     use super::*;
 
@@ -130,7 +487,7 @@ mod tests {
         let result = calculate_total_duration(&logs);
 
         // Assert
-        assert_eq!(result, 20);
+        assert_eq!(result, Duration::seconds(20));
     }
 
     #[test]
@@ -142,7 +499,247 @@ mod tests {
         let result = calculate_total_duration(&logs);
 
         // Assert
-        assert_eq!(result, 0);
+        assert_eq!(result, Duration::zero());
+    }
+
+    #[test]
+    fn update_should_apply_changes_and_recalculate_duration() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_get_project().returning(|id| {
+            let mut proj = Project::new("web".to_owned(), None, to_datetime(0), to_datetime(0));
+            proj.id = *id;
+            let logs = vec![
+                Log::new(
+                    1,
+                    *id,
+                    "Log 1".to_owned(),
+                    to_datetime(100),
+                    to_datetime(160),
+                ),
+                Log::new(
+                    2,
+                    *id,
+                    "Log 2".to_owned(),
+                    to_datetime(200),
+                    to_datetime(230),
+                ),
+            ];
+            Ok((proj, logs))
+        });
+        repo.expect_update_project()
+            .withf(|proj| proj.name == "site" && proj.duration == Duration::seconds(90))
+            .times(1)
+            .returning(|_| Ok(1));
+        let changes = ProjectChanges {
+            name: Some("site".to_owned()),
+            ..ProjectChanges::default()
+        };
+
+        // Act
+        let result = update(&repo, 4, changes).unwrap();
+
+        // Assert
+        let proj = result.unwrap();
+        assert_eq!((4, Duration::seconds(90)), (proj.id, proj.duration));
+    }
+
+    #[test]
+    fn update_across_clocks_going_back_should_count_the_repeated_hour() {
+        if in_zone(
+            CENTRAL_EUROPE,
+            concat!(
+                module_path!(),
+                "::update_across_clocks_going_back_should_count_the_repeated_hour"
+            ),
+        ) {
+            return;
+        }
+
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_get_project().returning(|id| {
+            // 02:30 happens twice, an hour apart
+            let first = parse_datetime("2026-10-25 02:30").unwrap();
+            let logs = vec![
+                Log::new(
+                    1,
+                    *id,
+                    "Log 1".to_owned(),
+                    parse_datetime("2026-10-25 01:30").unwrap(),
+                    parse_datetime("2026-10-25 03:30").unwrap(),
+                ),
+                Log::new(
+                    2,
+                    *id,
+                    "Log 2".to_owned(),
+                    first,
+                    first + Duration::hours(1),
+                ),
+            ];
+            Ok((
+                Project::new("web".to_owned(), None, to_datetime(0), to_datetime(0)),
+                logs,
+            ))
+        });
+        repo.expect_update_project()
+            .withf(|proj| proj.duration == Duration::hours(4))
+            .times(1)
+            .returning(|_| Ok(1));
+
+        // Act
+        let result = update(&repo, 4, ProjectChanges::default()).unwrap();
+
+        // Assert
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn update_missing_project_should_return_none() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_get_project()
+            .returning(|_| Err(rusqlite::Error::QueryReturnedNoRows));
+        repo.expect_update_project().never();
+
+        // Act
+        let result = update(&repo, 4, ProjectChanges::default()).unwrap();
+
+        // Assert
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn new_with_name_in_use_should_not_save_project() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_all_projects().returning(|| {
+            Ok(vec![Project::new(
+                "web".to_owned(),
+                None,
+                to_datetime(0),
+                to_datetime(0),
+            )])
+        });
+        repo.expect_save_project().never();
+
+        // Act
+        new(
+            &repo,
+            &FixedClock::at(to_datetime(0)),
+            "web".to_owned(),
+            None,
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn new_should_save_project_under_resolved_parent() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_all_projects().returning(|| {
+            let mut parent = Project::new("acme".to_owned(), None, to_datetime(0), to_datetime(0));
+            parent.id = 3;
+            Ok(vec![parent])
+        });
+        repo.expect_all_aliases().returning(|| Ok(Vec::new()));
+        repo.expect_save_project()
+            .withf(|proj| {
+                let now = parse_datetime("2026-10-19 09:00").unwrap();
+                (proj.name.as_str(), proj.parent_id) == ("web", Some(3))
+                    && (proj.created, proj.updated) == (now, now)
+            })
+            .times(1)
+            .returning(|_| Ok(4));
+
+        // Act
+        new(
+            &repo,
+            &FixedClock::at(parse_datetime("2026-10-19 09:00").unwrap()),
+            "web".to_owned(),
+            None,
+            Some("acme".to_owned()),
+            None,
+        );
+    }
+
+    fn nested_projects() -> Vec<Project> {
+        let mut acme = Project::new("acme".to_owned(), None, to_datetime(0), to_datetime(0));
+        acme.id = 3;
+        acme.client_id = Some(2);
+        let mut web = Project::new("web".to_owned(), None, to_datetime(0), to_datetime(0));
+        web.id = 4;
+        web.parent_id = Some(3);
+        web.client_id = Some(2);
+        let mut api = Project::new("api".to_owned(), None, to_datetime(0), to_datetime(0));
+        api.id = 5;
+        api.parent_id = Some(3);
+        vec![acme, web, api]
+    }
+
+    #[test]
+    fn edit_with_none_should_clear_parent_and_client() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_all_projects()
+            .returning(|| Ok(nested_projects()));
+        repo.expect_all_aliases().returning(|| Ok(Vec::new()));
+        repo.expect_get_project().returning(|id| {
+            let proj = nested_projects().into_iter().find(|p| p.id == *id).unwrap();
+            Ok((proj, Vec::new()))
+        });
+        repo.expect_update_project()
+            .withf(|proj| (proj.id, proj.parent_id, proj.client_id) == (4, None, None))
+            .times(1)
+            .returning(|_| Ok(1));
+        let edits = ProjectEdits {
+            parent: Some("none".to_owned()),
+            client: Some("none".to_owned()),
+            ..ProjectEdits::default()
+        };
+
+        // Act
+        edit(&repo, "4", edits);
+    }
+
+    #[test]
+    fn edit_renaming_to_sibling_name_should_not_update_project() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_all_projects()
+            .returning(|| Ok(nested_projects()));
+        repo.expect_all_aliases().returning(|| Ok(Vec::new()));
+        repo.expect_update_project().never();
+        let edits = ProjectEdits {
+            name: Some("api".to_owned()),
+            ..ProjectEdits::default()
+        };
+
+        // Act
+        edit(&repo, "4", edits);
+    }
+
+    #[test]
+    fn edit_moving_next_to_project_with_same_name_should_not_update_project() {
+        // Arrange
+        let mut repo = MockRepository::new();
+        repo.expect_all_projects().returning(|| {
+            let mut projects = nested_projects();
+            let mut web = Project::new("web".to_owned(), None, to_datetime(0), to_datetime(0));
+            web.id = 6;
+            projects.push(web);
+            Ok(projects)
+        });
+        repo.expect_all_aliases().returning(|| Ok(Vec::new()));
+        repo.expect_update_project().never();
+        let edits = ProjectEdits {
+            parent: Some("none".to_owned()),
+            ..ProjectEdits::default()
+        };
+
+        // Act
+        edit(&repo, "4", edits);
     }
 }
 
@@ -1,2 +1,5 @@
+pub mod budget;
 pub mod handlers;
 mod print;
+pub mod resolve;
+pub mod tree;
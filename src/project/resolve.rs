@@ -0,0 +1,382 @@
+use std::fmt;
+
+use crate::{model::Project, project::tree, repository::Repository};
+
+/// Reasons a project reference could not be turned into a single project.
+pub enum ResolveError {
+    /// Nothing matched the reference
+    NotFound(String),
+    /// More than one project matched the reference equally well. Candidates are ID and path pairs
+    Ambiguous(String, Vec<(u32, String)>),
+    Repository(rusqlite::Error),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::NotFound(reference) => {
+                write!(f, "No project matches \"{}\"", reference)
+            }
+            ResolveError::Ambiguous(reference, candidates) => {
+                write!(f, "\"{}\" matches more than one project:", reference)?;
+                for (id, path) in candidates {
+                    write!(f, "\n  {} {}", id, path)?;
+                }
+                write!(f, "\nUse the project ID or a longer name.")
+            }
+            ResolveError::Repository(e) => write!(f, "Error retrieving projects: {}", e),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for ResolveError {
+    fn from(e: rusqlite::Error) -> Self {
+        ResolveError::Repository(e)
+    }
+}
+
+/// Finds the project a user is referring to.
+///
+/// A reference is tried, in order, as a numeric ID, an exact name, an alias, a case-insensitive
+/// name, a unique name or alias prefix and finally a fuzzy match where the reference's
+/// characters appear in order in the name.
+///
+/// References containing `/` are treated as paths through the project hierarchy, e.g.
+/// `acme/api`, where the first segment is matched among root projects and each other segment
+/// among the children of the projects the previous one matched.
+///
+/// # Arguments
+///
+/// * `repo` - Repository to look the project up in
+/// * `reference` - ID, name, alias or abbreviation typed by the user
+pub fn resolve(repo: &impl Repository, reference: &str) -> Result<Project, ResolveError> {
+    let projects = repo.all_projects()?;
+    let aliases = repo.all_aliases()?;
+
+    let ids = find_path(reference, &projects, &aliases);
+
+    match ids.len() {
+        0 => Err(ResolveError::NotFound(reference.to_owned())),
+        1 => Ok(projects
+            .into_iter()
+            .find(|p| p.id == ids[0])
+            .expect("Matched project should exist")),
+        _ => {
+            let candidates = projects
+                .iter()
+                .filter(|p| ids.contains(&p.id))
+                .map(|p| (p.id, tree::path(p, &projects)))
+                .collect();
+            Err(ResolveError::Ambiguous(reference.to_owned(), candidates))
+        }
+    }
+}
+
+/// Matches each `/` separated segment of the reference among the children of every project the
+/// previous segment matched, so a later segment can settle which of several parents was meant.
+/// The first segment of a path is matched among root projects, while a reference without `/`
+/// can match any project.
+fn find_path(reference: &str, projects: &[Project], aliases: &[(String, u32)]) -> Vec<u32> {
+    let segments: Vec<&str> = reference
+        .split('/')
+        .filter(|s| !s.trim().is_empty())
+        .collect();
+
+    let Some((first, rest)) = segments.split_first() else {
+        return vec![];
+    };
+    let mut matched = match rest.is_empty() {
+        true => find(first, &projects.iter().collect::<Vec<_>>(), aliases),
+        false => among(first, projects, aliases, |p| p.parent_id.is_none()),
+    };
+
+    for segment in rest {
+        if matched.is_empty() {
+            break;
+        }
+        let parents = matched;
+        matched = among(segment, projects, aliases, |p| {
+            p.parent_id.is_some_and(|id| parents.contains(&id))
+        });
+    }
+
+    matched
+}
+
+/// Matches a reference among the projects that pass a filter, and their aliases.
+fn among(
+    reference: &str,
+    projects: &[Project],
+    aliases: &[(String, u32)],
+    filter: impl Fn(&Project) -> bool,
+) -> Vec<u32> {
+    let projects: Vec<&Project> = projects.iter().filter(|p| filter(p)).collect();
+    let aliases: Vec<(String, u32)> = aliases
+        .iter()
+        .filter(|(_, id)| projects.iter().any(|p| p.id == *id))
+        .cloned()
+        .collect();
+
+    find(reference, &projects, &aliases)
+}
+
+/// Returns the IDs of the projects matched by the first strategy that matches anything.
+fn find(reference: &str, projects: &[&Project], aliases: &[(String, u32)]) -> Vec<u32> {
+    let reference = reference.trim();
+    if reference.is_empty() {
+        return vec![];
+    }
+
+    if let Ok(id) = reference.parse::<u32>() {
+        if projects.iter().any(|p| p.id == id) {
+            return vec![id];
+        }
+    }
+
+    let by_name = |pred: &dyn Fn(&str) -> bool| -> Vec<u32> {
+        projects
+            .iter()
+            .filter(|p| pred(&p.name))
+            .map(|p| p.id)
+            .collect()
+    };
+
+    let exact = by_name(&|name| name == reference);
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    if let Some((_, id)) = aliases.iter().find(|(alias, _)| alias == reference) {
+        return vec![*id];
+    }
+
+    let lower = reference.to_lowercase();
+
+    let insensitive = by_name(&|name| name.to_lowercase() == lower);
+    if !insensitive.is_empty() {
+        return insensitive;
+    }
+
+    let mut prefixed = by_name(&|name| name.to_lowercase().starts_with(&lower));
+    for (alias, id) in aliases {
+        if alias.to_lowercase().starts_with(&lower) && !prefixed.contains(id) {
+            prefixed.push(*id);
+        }
+    }
+    if !prefixed.is_empty() {
+        return prefixed;
+    }
+
+    by_name(&|name| is_subsequence(&lower, &name.to_lowercase()))
+}
+
+/// True if every character of `needle` appears in `haystack` in the same order.
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|n| chars.any(|h| h == n))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use super::*;
+
+    fn projects(names: &[&str]) -> Vec<Project> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                Project::load(
+                    i as u32 + 1,
+                    name.to_string(),
+                    None,
+                    Local::now(),
+                    Local::now(),
+                    Duration::zero(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_numeric_reference_matches_id() {
+        // Arrange
+        let projects = projects(&["website", "api"]);
+
+        // Act
+        let res = find_path("2", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![2], res);
+    }
+
+    #[test]
+    fn find_numeric_reference_without_id_falls_back_to_name() {
+        // Arrange
+        let projects = projects(&["2024 planning"]);
+
+        // Act
+        let res = find_path("2024", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![1], res);
+    }
+
+    #[test]
+    fn find_exact_name_beats_prefix() {
+        // Arrange
+        let projects = projects(&["api-v2", "api"]);
+
+        // Act
+        let res = find_path("api", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![2], res);
+    }
+
+    #[test]
+    fn find_alias_matches_project() {
+        // Arrange
+        let projects = projects(&["website", "api"]);
+        let aliases = vec![("web".to_owned(), 1)];
+
+        // Act
+        let res = find_path("web", &projects, &aliases);
+
+        // Assert
+        assert_eq!(vec![1], res);
+    }
+
+    #[test]
+    fn find_unique_prefix_is_case_insensitive() {
+        // Arrange
+        let projects = projects(&["Website", "api"]);
+
+        // Act
+        let res = find_path("web", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![1], res);
+    }
+
+    #[test]
+    fn find_shared_prefix_returns_all_candidates() {
+        // Arrange
+        let projects = projects(&["website", "webhooks", "api"]);
+
+        // Act
+        let res = find_path("web", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![1, 2], res);
+    }
+
+    #[test]
+    fn find_fuzzy_matches_characters_in_order() {
+        // Arrange
+        let projects = projects(&["customer-portal", "api"]);
+
+        // Act
+        let res = find_path("cportal", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![1], res);
+    }
+
+    #[test]
+    fn find_path_matches_segments_among_children() {
+        // Arrange
+        let mut projects = projects(&["acme", "globex", "api", "api"]);
+        projects[2].parent_id = Some(1);
+        projects[3].parent_id = Some(2);
+
+        // Act
+        let res = find_path("globex/api", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![4], res);
+    }
+
+    #[test]
+    fn find_path_segments_can_be_prefixes() {
+        // Arrange
+        let mut projects = projects(&["acme", "api"]);
+        projects[1].parent_id = Some(1);
+
+        // Act
+        let res = find_path("ac/a", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![2], res);
+    }
+
+    #[test]
+    fn find_path_unknown_child_returns_no_ids() {
+        // Arrange
+        let mut projects = projects(&["acme", "api"]);
+        projects[1].parent_id = Some(1);
+
+        // Act
+        let res = find_path("api/acme", &projects, &[]);
+
+        // Assert
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn find_nothing_matching_returns_no_ids() {
+        // Arrange
+        let projects = projects(&["website"]);
+
+        // Act
+        let res = find_path("xyz", &projects, &[]);
+
+        // Assert
+        assert!(res.is_empty());
+    }
+
+    #[test]
+    fn find_path_child_settles_ambiguous_parent() {
+        // Arrange
+        let mut projects = projects(&["acme", "acorn", "api", "web"]);
+        projects[2].parent_id = Some(1);
+        projects[3].parent_id = Some(2);
+
+        // Act
+        let res = find_path("ac/api", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![3], res);
+    }
+
+    #[test]
+    fn find_path_keeps_every_match_under_ambiguous_parents() {
+        // Arrange
+        let mut projects = projects(&["acme", "acorn", "api", "api"]);
+        projects[2].parent_id = Some(1);
+        projects[3].parent_id = Some(2);
+
+        // Act
+        let res = find_path("ac/api", &projects, &[]);
+
+        // Assert
+        assert_eq!(vec![3, 4], res);
+    }
+
+    #[test]
+    fn find_path_first_segment_only_matches_root_projects() {
+        // Arrange
+        let mut projects = projects(&["acme", "api", "auth"]);
+        projects[1].parent_id = Some(1);
+        projects[2].parent_id = Some(2);
+
+        // Act
+        let nested = find_path("api/auth", &projects, &[]);
+        let alone = find_path("api", &projects, &[]);
+
+        // Assert
+        assert!(nested.is_empty());
+        assert_eq!(vec![2], alone);
+    }
+}
@@ -0,0 +1,225 @@
+use std::{collections::HashMap, ops::AddAssign};
+
+use crate::model::Project;
+
+/// Orders projects depth first, parents before their children, pairing each with its depth.
+///
+/// Projects whose parent is missing from `projects` are treated as roots.
+pub fn flatten(projects: &[Project]) -> Vec<(usize, &Project)> {
+    let ids: Vec<u32> = projects.iter().map(|p| p.id).collect();
+    let mut ordered = Vec::with_capacity(projects.len());
+
+    for root in projects
+        .iter()
+        .filter(|p| p.parent_id.is_none_or(|parent| !ids.contains(&parent)))
+    {
+        push_subtree(root, 0, projects, &mut ordered);
+    }
+
+    ordered
+}
+
+fn push_subtree<'a>(
+    proj: &'a Project,
+    depth: usize,
+    projects: &'a [Project],
+    ordered: &mut Vec<(usize, &'a Project)>,
+) {
+    ordered.push((depth, proj));
+    for child in projects.iter().filter(|p| p.parent_id == Some(proj.id)) {
+        push_subtree(child, depth + 1, projects, ordered);
+    }
+}
+
+/// Full path of a project from its root, e.g. `acme/api`.
+pub fn path(proj: &Project, projects: &[Project]) -> String {
+    let mut names = vec![proj.name.as_str()];
+    let mut parent_id = proj.parent_id;
+
+    while let Some(parent) = parent_id.and_then(|id| projects.iter().find(|p| p.id == id)) {
+        // guard against cycles in hand-edited databases
+        if names.len() > projects.len() {
+            break;
+        }
+        names.push(parent.name.as_str());
+        parent_id = parent.parent_id;
+    }
+
+    names.reverse();
+    names.join("/")
+}
+
+/// The client a project is billed to, inherited from the nearest ancestor that has one.
+pub fn client_id(proj: &Project, projects: &[Project]) -> Option<u32> {
+    let mut current = Some(proj);
+    let mut depth = 0;
+
+    while let Some(p) = current {
+        if p.client_id.is_some() || depth > projects.len() {
+            return p.client_id;
+        }
+        current = p
+            .parent_id
+            .and_then(|id| projects.iter().find(|parent| parent.id == id));
+        depth += 1;
+    }
+
+    None
+}
+
+/// IDs of every project nested under the given project, at any depth.
+pub fn descendants(id: u32, projects: &[Project]) -> Vec<u32> {
+    let mut found = Vec::new();
+    let mut pending = vec![id];
+
+    while let Some(current) = pending.pop() {
+        for child in projects.iter().filter(|p| p.parent_id == Some(current)) {
+            if !found.contains(&child.id) && child.id != id {
+                found.push(child.id);
+                pending.push(child.id);
+            }
+        }
+    }
+
+    found
+}
+
+/// Adds the value of every project, such as its duration, to its own and all of its ancestors'
+/// totals.
+///
+/// # Arguments
+///
+/// * `projects` - All projects, used to walk up the hierarchy
+/// * `own` - Value logged directly against each project ID
+pub fn rollup<T>(projects: &[Project], own: &HashMap<u32, T>) -> HashMap<u32, T>
+where
+    T: Copy + Default + AddAssign,
+{
+    let mut totals: HashMap<u32, T> = HashMap::new();
+
+    for (id, duration) in own {
+        *totals.entry(*id).or_default() += *duration;
+
+        let mut parent_id = projects
+            .iter()
+            .find(|p| p.id == *id)
+            .and_then(|p| p.parent_id);
+        let mut depth = 0;
+        while let Some(parent) = parent_id {
+            if depth > projects.len() {
+                break;
+            }
+            *totals.entry(parent).or_default() += *duration;
+            parent_id = projects
+                .iter()
+                .find(|p| p.id == parent)
+                .and_then(|p| p.parent_id);
+            depth += 1;
+        }
+    }
+
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use super::*;
+
+    fn project(id: u32, name: &str, parent_id: Option<u32>) -> Project {
+        let mut proj = Project::load(
+            id,
+            name.to_owned(),
+            None,
+            Local::now(),
+            Local::now(),
+            Duration::zero(),
+        );
+        proj.parent_id = parent_id;
+        proj
+    }
+
+    fn hierarchy() -> Vec<Project> {
+        vec![
+            project(1, "acme", None),
+            project(2, "globex", None),
+            project(3, "api", Some(1)),
+            project(4, "docs", Some(3)),
+            project(5, "web", Some(1)),
+        ]
+    }
+
+    #[test]
+    fn flatten_orders_children_after_parents() {
+        // Arrange
+        let projects = hierarchy();
+
+        // Act
+        let flat = flatten(&projects);
+
+        // Assert
+        let actual: Vec<(usize, u32)> = flat.iter().map(|(d, p)| (*d, p.id)).collect();
+        assert_eq!(vec![(0, 1), (1, 3), (2, 4), (1, 5), (0, 2)], actual);
+    }
+
+    #[test]
+    fn path_joins_ancestor_names() {
+        // Arrange
+        let projects = hierarchy();
+
+        // Act
+        let res = path(&projects[3], &projects);
+
+        // Assert
+        assert_eq!("acme/api/docs", res);
+    }
+
+    #[test]
+    fn descendants_includes_all_depths() {
+        // Arrange
+        let projects = hierarchy();
+
+        // Act
+        let mut res = descendants(1, &projects);
+        res.sort();
+
+        // Assert
+        assert_eq!(vec![3, 4, 5], res);
+    }
+
+    #[test]
+    fn client_id_is_inherited_from_nearest_ancestor() {
+        // Arrange
+        let mut projects = hierarchy();
+        projects[0].client_id = Some(7);
+        projects[2].client_id = Some(8);
+
+        // Act
+        let docs = client_id(&projects[3], &projects);
+        let web = client_id(&projects[4], &projects);
+        let globex = client_id(&projects[1], &projects);
+
+        // Assert
+        assert_eq!(Some(8), docs);
+        assert_eq!(Some(7), web);
+        assert_eq!(None, globex);
+    }
+
+    #[test]
+    fn rollup_adds_child_durations_to_ancestors() {
+        // Arrange
+        let projects = hierarchy();
+        let own = HashMap::from([(1, 10), (3, 20), (4, 30), (2, 5)]);
+
+        // Act
+        let totals = rollup(&projects, &own);
+
+        // Assert
+        assert_eq!(Some(&60), totals.get(&1));
+        assert_eq!(Some(&50), totals.get(&3));
+        assert_eq!(Some(&30), totals.get(&4));
+        assert_eq!(Some(&5), totals.get(&2));
+        assert_eq!(None, totals.get(&5));
+    }
+}
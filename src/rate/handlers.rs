@@ -0,0 +1,123 @@
+use crate::{
+    client::{self, handlers::parse_rate},
+    clock::Clock,
+    model::{Rate, RateScope},
+    print::table::Table,
+    project::{resolve::resolve, tree},
+    repository::Repository,
+    time::parse_date,
+};
+
+/// Set an hourly rate for a client, project or tag. Exactly one of the scopes must be given.
+///
+/// # Arguments:
+///
+/// * `amount` - Hourly rate, e.g. 120.50
+/// * `client` - Reference to the client the rate applies to
+/// * `project` - Reference to the project the rate applies to, including its sub-projects
+/// * `tag` - Tag the rate applies to
+/// * `from` - Optional first day the rate applies, defaults to now
+pub fn set(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    amount: String,
+    client: Option<String>,
+    project: Option<String>,
+    tag: Option<String>,
+    from: Option<String>,
+) {
+    let amount = match parse_rate(&amount) {
+        Ok(a) => a,
+        Err(e) => return eprintln!("{}", e),
+    };
+    let effective_from = match from {
+        Some(date) => match parse_date(&date) {
+            Some(d) => d,
+            None => {
+                return eprintln!(
+                    "Invalid date \"{}\". Dates should look like 2026-09-30",
+                    date
+                )
+            }
+        },
+        None => clock.now(),
+    };
+
+    let scope = match (client, project, tag) {
+        (Some(reference), None, None) => match client::resolve::resolve(repo, &reference) {
+            Ok(c) => RateScope::Client(c.id),
+            Err(e) => return eprintln!("{}", e),
+        },
+        (None, Some(reference), None) => match resolve(repo, &reference) {
+            Ok(p) => RateScope::Project(p.id),
+            Err(e) => return eprintln!("{}", e),
+        },
+        (None, None, Some(tag)) => RateScope::Tag(tag),
+        _ => return eprintln!("Specify exactly one of --client, --project or --tag."),
+    };
+
+    match repo.save_rate(&Rate::new(scope, amount, effective_from)) {
+        Ok(id) => println!(
+            "Rate {} set from {}.\n",
+            id,
+            effective_from.format("%Y-%m-%d")
+        ),
+        Err(e) => eprintln!("Failed to set rate: {}", e),
+    }
+}
+
+pub fn list(repo: &impl Repository) {
+    let rates = match repo.all_rates() {
+        Ok(rates) => rates,
+        Err(e) => return eprintln!("Error retrieving rates: {}", e),
+    };
+    let projects = match repo.all_projects() {
+        Ok(projects) => projects,
+        Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+    };
+    let clients = match repo.all_clients() {
+        Ok(clients) => clients,
+        Err(e) => return eprintln!("Error retrieving your clients: {}", e),
+    };
+
+    let mut table = Table::new(vec![
+        "ID".to_string(),
+        "Applies to".to_string(),
+        "Rate".to_string(),
+        "From".to_string(),
+    ]);
+    for rate in rates {
+        let scope = match &rate.scope {
+            RateScope::Client(id) => match clients.iter().find(|c| c.id == *id) {
+                Some(c) => format!("client {}", c.name),
+                None => format!("client {}", id),
+            },
+            RateScope::Project(id) => match projects.iter().find(|p| p.id == *id) {
+                Some(p) => format!("project {}", tree::path(p, &projects)),
+                None => format!("project {}", id),
+            },
+            RateScope::Tag(tag) => format!("tag {}", tag),
+        };
+
+        table.add_row(vec![
+            rate.id.to_string(),
+            scope,
+            rate.amount.to_string(),
+            rate.effective_from.format("%Y-%m-%d").to_string(),
+        ]);
+    }
+    table.print(&mut std::io::stdout());
+}
+
+/// Delete a rate by ID.
+///
+/// # Arguments:
+///
+/// * `id` - ID of the rate to delete
+pub fn remove(repo: &impl Repository, id: u32) {
+    match repo.delete_rate(&id) {
+        Ok(0) => eprintln!("No rate with ID {} was found. Is it the right ID?", id),
+        Ok(_) => println!("Deleted rate {}", id),
+        Err(e) => eprintln!("Failed to delete rate: {}", e),
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{DateTime, Local};
+use rust_decimal::Decimal;
+
+use crate::{
+    model::{Client, Log, Project, Rate, RateScope},
+    money,
+    project::tree,
+};
+
+/// Prices logs using every known rate. Load it once per command and reuse it for each log.
+pub struct RateBook<'a> {
+    projects: &'a [Project],
+    clients: &'a [Client],
+    rates: &'a [Rate],
+}
+
+impl<'a> RateBook<'a> {
+    pub fn new(projects: &'a [Project], clients: &'a [Client], rates: &'a [Rate]) -> Self {
+        RateBook {
+            projects,
+            clients,
+            rates,
+        }
+    }
+
+    /// The rate in effect for the given scope at a point in time
+    fn latest(&self, scope: &RateScope, at: &DateTime<Local>) -> Option<Decimal> {
+        self.rates
+            .iter()
+            .filter(|r| &r.scope == scope && r.effective_from <= *at)
+            .max_by_key(|r| (r.effective_from, r.id))
+            .map(|r| r.amount)
+    }
+
+    fn client_of(&self, proj_id: u32) -> Option<&Client> {
+        let proj = self.projects.iter().find(|p| p.id == proj_id)?;
+        let client_id = tree::client_id(proj, self.projects)?;
+        self.clients.iter().find(|c| c.id == client_id)
+    }
+
+    /// Hourly rate that applies to a log when it started.
+    ///
+    /// The most specific rate wins: a rate on one of the log's tags (in tag order), then the
+    /// log's project or its nearest ancestor with a rate, then the client's rate history and
+    /// finally the client's default hourly rate.
+    pub fn rate_for(&self, log: &Log) -> Option<Decimal> {
+        for tag in &log.tags {
+            if let Some(rate) = self.latest(&RateScope::Tag(tag.clone()), &log.start) {
+                return Some(rate);
+            }
+        }
+
+        let mut proj = self.projects.iter().find(|p| p.id == log.proj_id);
+        let mut depth = 0;
+        while let Some(p) = proj {
+            if let Some(rate) = self.latest(&RateScope::Project(p.id), &log.start) {
+                return Some(rate);
+            }
+            if depth > self.projects.len() {
+                break;
+            }
+            proj = p
+                .parent_id
+                .and_then(|id| self.projects.iter().find(|parent| parent.id == id));
+            depth += 1;
+        }
+
+        let client = self.client_of(log.proj_id)?;
+        self.latest(&RateScope::Client(client.id), &log.start)
+            .or(client.hourly_rate)
+    }
+
+    /// Currency of the client a project is billed to, if it has one.
+    pub fn currency_for(&self, proj_id: u32) -> Option<&str> {
+        self.client_of(proj_id).map(|c| c.currency.as_str())
+    }
+
+    /// Exact amount earned by a log. Non-billable logs and logs without a rate earn nothing.
+    pub fn earnings(&self, log: &Log) -> Decimal {
+        if !log.billable {
+            return Decimal::ZERO;
+        }
+
+        match self.rate_for(log) {
+            Some(rate) => money::earnings(log.seconds(), rate),
+            None => Decimal::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::time::to_datetime;
+
+    use super::*;
+
+    fn project(id: u32, parent_id: Option<u32>, client_id: Option<u32>) -> Project {
+        let mut proj = Project::load(
+            id,
+            format!("p{}", id),
+            None,
+            to_datetime(0),
+            to_datetime(0),
+            Duration::zero(),
+        );
+        proj.parent_id = parent_id;
+        proj.client_id = client_id;
+        proj
+    }
+
+    fn client(id: u32, hourly_rate: Option<i64>) -> Client {
+        let mut client = Client::new(
+            "acme".to_owned(),
+            None,
+            "AUD".to_owned(),
+            hourly_rate.map(Decimal::from),
+            None,
+        );
+        client.id = id;
+        client
+    }
+
+    fn rate(id: u32, scope: RateScope, amount: i64, from: i64) -> Rate {
+        let mut rate = Rate::new(scope, Decimal::from(amount), to_datetime(from));
+        rate.id = id;
+        rate
+    }
+
+    fn log(proj_id: u32, start: i64, tags: &[&str]) -> Log {
+        let mut log = Log::new(
+            1,
+            proj_id,
+            "work".to_owned(),
+            to_datetime(start),
+            to_datetime(start + 3600),
+        );
+        log.tags = tags.iter().map(|t| t.to_string()).collect();
+        log
+    }
+
+    #[test]
+    fn rate_for_falls_back_to_client_default() {
+        // Arrange
+        let projects = vec![project(1, None, Some(1))];
+        let clients = vec![client(1, Some(80))];
+        let book = RateBook::new(&projects, &clients, &[]);
+
+        // Act
+        let res = book.rate_for(&log(1, 100, &[]));
+
+        // Assert
+        assert_eq!(Some(Decimal::from(80)), res);
+    }
+
+    #[test]
+    fn rate_for_prefers_tag_then_project_then_client() {
+        // Arrange
+        let projects = vec![project(1, None, Some(1)), project(2, Some(1), None)];
+        let clients = vec![client(1, Some(80))];
+        let rates = vec![
+            rate(1, RateScope::Client(1), 90, 0),
+            rate(2, RateScope::Project(1), 100, 0),
+            rate(3, RateScope::Tag("review".to_owned()), 150, 0),
+        ];
+        let book = RateBook::new(&projects, &clients, &rates);
+
+        // Act
+        let tagged = book.rate_for(&log(2, 100, &["other", "review"]));
+        let inherited = book.rate_for(&log(2, 100, &[]));
+
+        // Assert
+        assert_eq!(Some(Decimal::from(150)), tagged);
+        assert_eq!(Some(Decimal::from(100)), inherited);
+    }
+
+    #[test]
+    fn rate_for_uses_rate_effective_when_log_started() {
+        // Arrange
+        let projects = vec![project(1, None, Some(1))];
+        let clients = vec![client(1, None)];
+        let rates = vec![
+            rate(1, RateScope::Client(1), 90, 0),
+            rate(2, RateScope::Client(1), 110, 1000),
+        ];
+        let book = RateBook::new(&projects, &clients, &rates);
+
+        // Act
+        let before = book.rate_for(&log(1, 999, &[]));
+        let after = book.rate_for(&log(1, 1000, &[]));
+
+        // Assert
+        assert_eq!(Some(Decimal::from(90)), before);
+        assert_eq!(Some(Decimal::from(110)), after);
+    }
+
+    #[test]
+    fn earnings_of_non_billable_log_is_zero() {
+        // Arrange
+        let projects = vec![project(1, None, Some(1))];
+        let clients = vec![client(1, Some(80))];
+        let book = RateBook::new(&projects, &clients, &[]);
+
+        let mut non_billable = log(1, 0, &[]);
+        non_billable.billable = false;
+
+        // Act
+        let res = book.earnings(&non_billable);
+
+        // Assert
+        assert_eq!(Decimal::ZERO, res);
+        assert_eq!(Decimal::from(80), book.earnings(&log(1, 0, &[])));
+    }
+}
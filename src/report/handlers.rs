@@ -0,0 +1,712 @@
+use std::{collections::HashMap, ops::AddAssign};
+
+use chrono::{DateTime, Duration, Local, TimeZone};
+use clap::ValueEnum;
+use rust_decimal::Decimal;
+use serde::{Serialize, Serializer};
+
+use crate::{
+    client,
+    clock::Clock,
+    config::Config,
+    model::{Client, Log, Project},
+    money,
+    print::table::Table,
+    project::tree,
+    rate::lookup::RateBook,
+    repository::Repository,
+    rounding::{self, RoundingRule},
+    time::{duration_hours, parse_date, zone::DisplayZone},
+};
+
+/// What durations in a report are grouped by
+#[derive(Clone, Copy, ValueEnum)]
+pub enum GroupBy {
+    Project,
+    Client,
+}
+
+/// The days a report covers, as given on the command line. Days run from midnight to midnight
+/// in the display zone, and a log counts towards the day it started on, even if it ends on the
+/// next.
+#[derive(Default)]
+pub struct Days {
+    /// Optional first day, defaults to the first log
+    pub from: Option<String>,
+    /// Optional last day, defaults to today
+    pub to: Option<String>,
+    /// Zone the days are in. With the zone each log was recorded in, a log started at 23:00 in
+    /// Tokyo counts towards that day, even where it was still the morning before
+    pub zone: DisplayZone,
+}
+
+/// Time and money logged against a project or client
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize)]
+struct Totals {
+    seconds: u32,
+    billable_seconds: u32,
+    /// Billable time after rounding
+    rounded_seconds: u32,
+    /// Exact amount earned by the rounded billable time
+    #[serde(serialize_with = "serialize_amount")]
+    amount: Decimal,
+}
+
+impl AddAssign for Totals {
+    fn add_assign(&mut self, other: Self) {
+        self.seconds += other.seconds;
+        self.billable_seconds += other.billable_seconds;
+        self.rounded_seconds += other.rounded_seconds;
+        self.amount += other.amount;
+    }
+}
+
+fn serialize_amount<S: Serializer>(amount: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:.2}", amount))
+}
+
+/// Totals of the logs recorded directly against each project. Billable time is rounded per
+/// project and rate, by the rule for the client the project is billed to, and amounts are
+/// earned on the rounded time.
+///
+/// # Arguments
+///
+/// * `logs` - Logs to total
+/// * `book` - Rates to price billable logs with
+/// * `rule_for` - Rounding rule for the logs of a project, by project ID
+fn own_totals<'a>(
+    logs: &[Log],
+    book: &RateBook,
+    rule_for: impl Fn(u32) -> Option<&'a RoundingRule>,
+) -> HashMap<u32, Totals> {
+    let mut totals: HashMap<u32, Totals> = HashMap::new();
+    let mut billed: Vec<(u32, Option<Decimal>, Vec<&Log>)> = Vec::new();
+
+    for log in logs {
+        let seconds = log.seconds();
+        *totals.entry(log.proj_id).or_default() += Totals {
+            seconds,
+            billable_seconds: if log.billable { seconds } else { 0 },
+            ..Default::default()
+        };
+
+        if log.billable {
+            let rate = book.rate_for(log);
+            match billed
+                .iter_mut()
+                .find(|(proj_id, r, _)| *proj_id == log.proj_id && *r == rate)
+            {
+                Some((_, _, group)) => group.push(log),
+                None => billed.push((log.proj_id, rate, vec![log])),
+            }
+        }
+    }
+
+    for (proj_id, rate, group) in billed {
+        let rounded = rounding::rounded(rule_for(proj_id), &group);
+        *totals.entry(proj_id).or_default() += Totals {
+            rounded_seconds: rounded,
+            amount: rate
+                .map(|r| money::earnings(rounded, r))
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+    }
+
+    totals
+}
+
+/// Totals per client. Logs of projects without a client are grouped under `None`.
+fn client_totals(projects: &[Project], own: &HashMap<u32, Totals>) -> HashMap<Option<u32>, Totals> {
+    let mut totals: HashMap<Option<u32>, Totals> = HashMap::new();
+    for (proj_id, proj_totals) in own {
+        let client_id = projects
+            .iter()
+            .find(|p| p.id == *proj_id)
+            .and_then(|p| tree::client_id(p, projects));
+        *totals.entry(client_id).or_default() += *proj_totals;
+    }
+
+    totals
+}
+
+/// Amount earned by a project and those of its sub-projects billed in the same currency.
+/// Amounts in other currencies can't be added together, so they only count towards the
+/// sub-project's own total.
+fn rolled_amount(
+    proj_id: u32,
+    projects: &[Project],
+    own: &HashMap<u32, Totals>,
+    book: &RateBook,
+) -> Decimal {
+    let currency = book.currency_for(proj_id);
+    let mut amount = own.get(&proj_id).map(|t| t.amount).unwrap_or_default();
+
+    for id in tree::descendants(proj_id, projects) {
+        if book.currency_for(id) == currency {
+            amount += own.get(&id).map(|t| t.amount).unwrap_or_default();
+        }
+    }
+
+    amount
+}
+
+/// Sum of the amounts earned in each currency, e.g. `AUD 100.00, USD 20.00`.
+fn format_amounts(projects: &[Project], own: &HashMap<u32, Totals>, book: &RateBook) -> String {
+    let mut per_currency: Vec<(Option<&str>, Decimal)> = Vec::new();
+    for proj in projects {
+        let amount = own.get(&proj.id).map(|t| t.amount).unwrap_or_default();
+        if amount.is_zero() {
+            continue;
+        }
+
+        let currency = book.currency_for(proj.id);
+        match per_currency.iter_mut().find(|(c, _)| *c == currency) {
+            Some((_, total)) => *total += amount,
+            None => per_currency.push((currency, amount)),
+        }
+    }
+
+    per_currency
+        .into_iter()
+        .map(|(currency, amount)| money::format_amount(amount, currency))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn print_by_project(projects: &[Project], own: &HashMap<u32, Totals>, book: &RateBook) {
+    let totals = tree::rollup(projects, own);
+
+    let mut table = Table::new(vec![
+        "Project".to_string(),
+        "Own".to_string(),
+        "Total".to_string(),
+        "Billable".to_string(),
+        "Rounded".to_string(),
+        "Amount".to_string(),
+    ]);
+    for (depth, proj) in tree::flatten(projects) {
+        let total = totals.get(&proj.id).copied().unwrap_or_default();
+        if total.seconds == 0 {
+            continue;
+        }
+
+        table.add_row(vec![
+            format!("{}{}", "  ".repeat(depth), proj.name),
+            duration_hours(own.get(&proj.id).map(|t| t.seconds).unwrap_or(0)),
+            duration_hours(total.seconds),
+            duration_hours(total.billable_seconds),
+            duration_hours(total.rounded_seconds),
+            money::format_amount(
+                rolled_amount(proj.id, projects, own, book),
+                book.currency_for(proj.id),
+            ),
+        ]);
+    }
+
+    let all = own.values().fold(Totals::default(), |mut acc, t| {
+        acc += *t;
+        acc
+    });
+    table.add_row(vec![
+        "All projects".to_string(),
+        "".to_string(),
+        duration_hours(all.seconds),
+        duration_hours(all.billable_seconds),
+        duration_hours(all.rounded_seconds),
+        format_amounts(projects, own, book),
+    ]);
+    table.print(&mut std::io::stdout());
+}
+
+fn print_by_client(
+    projects: &[Project],
+    clients: &[Client],
+    own: &HashMap<u32, Totals>,
+    book: &RateBook,
+) {
+    let totals = client_totals(projects, own);
+
+    let mut table = Table::new(vec![
+        "Client".to_string(),
+        "Total".to_string(),
+        "Billable".to_string(),
+        "Rounded".to_string(),
+        "Amount".to_string(),
+    ]);
+    for client in clients {
+        if let Some(t) = totals.get(&Some(client.id)) {
+            table.add_row(vec![
+                client.name.clone(),
+                duration_hours(t.seconds),
+                duration_hours(t.billable_seconds),
+                duration_hours(t.rounded_seconds),
+                money::format_amount(t.amount, Some(&client.currency)),
+            ]);
+        }
+    }
+    if let Some(t) = totals.get(&None) {
+        table.add_row(vec![
+            "No client".to_string(),
+            duration_hours(t.seconds),
+            duration_hours(t.billable_seconds),
+            duration_hours(t.rounded_seconds),
+            money::format_amount(t.amount, None),
+        ]);
+    }
+
+    let all = own.values().fold(Totals::default(), |mut acc, t| {
+        acc += *t;
+        acc
+    });
+    table.add_row(vec![
+        "All clients".to_string(),
+        duration_hours(all.seconds),
+        duration_hours(all.billable_seconds),
+        duration_hours(all.rounded_seconds),
+        format_amounts(projects, own, book),
+    ]);
+    table.print(&mut std::io::stdout());
+}
+
+#[derive(Serialize)]
+struct JsonProject<'a> {
+    id: u32,
+    path: String,
+    parent_id: Option<u32>,
+    currency: Option<&'a str>,
+    /// Time logged directly against the project, excluding sub-projects
+    #[serde(flatten)]
+    totals: Totals,
+}
+
+#[derive(Serialize)]
+struct JsonClient<'a> {
+    /// `None` for projects without a client
+    id: Option<u32>,
+    name: Option<&'a str>,
+    currency: Option<&'a str>,
+    #[serde(flatten)]
+    totals: Totals,
+}
+
+/// Prints the totals of each project as JSON, with raw and rounded time side by side.
+fn json_by_project(projects: &[Project], own: &HashMap<u32, Totals>, book: &RateBook) {
+    let rows: Vec<JsonProject> = tree::flatten(projects)
+        .into_iter()
+        .filter_map(|(_, proj)| {
+            own.get(&proj.id).map(|totals| JsonProject {
+                id: proj.id,
+                path: tree::path(proj, projects),
+                parent_id: proj.parent_id,
+                currency: book.currency_for(proj.id),
+                totals: *totals,
+            })
+        })
+        .collect();
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&rows).expect("Failed to serialise report")
+    );
+}
+
+/// Prints the totals of each client as JSON, with raw and rounded time side by side.
+fn json_by_client(projects: &[Project], clients: &[Client], own: &HashMap<u32, Totals>) {
+    let totals = client_totals(projects, own);
+
+    let mut rows: Vec<JsonClient> = clients
+        .iter()
+        .filter_map(|client| {
+            totals.get(&Some(client.id)).map(|t| JsonClient {
+                id: Some(client.id),
+                name: Some(&client.name),
+                currency: Some(&client.currency),
+                totals: *t,
+            })
+        })
+        .collect();
+    if let Some(t) = totals.get(&None) {
+        rows.push(JsonClient {
+            id: None,
+            name: None,
+            currency: None,
+            totals: *t,
+        });
+    }
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&rows).expect("Failed to serialise report")
+    );
+}
+
+/// Resolves the optional `from` and `to` dates of a report into a half-open range. `to` is
+/// inclusive of the whole day, and defaults to the end of the day after `now`.
+pub fn report_range(
+    from: Option<String>,
+    to: Option<String>,
+    now: DateTime<Local>,
+) -> Result<(DateTime<Local>, DateTime<Local>), String> {
+    let parse = |date: &str| {
+        parse_date(date).ok_or(format!(
+            "Invalid date \"{}\". Dates should look like 2026-09-30",
+            date
+        ))
+    };
+
+    let from = match from {
+        Some(date) => parse(&date)?,
+        None => Local.timestamp_opt(0, 0).unwrap(),
+    };
+    let to = match to {
+        Some(date) => parse(&date)? + Duration::days(1),
+        None => now + Duration::days(1),
+    };
+
+    Ok((from, to))
+}
+
+/// Whether a log started on one of the days from `from` up to, but not including, `to`, as the
+/// days fall in the given zone.
+fn started_within(
+    log: &Log,
+    from: &DateTime<Local>,
+    to: &DateTime<Local>,
+    zone: DisplayZone,
+) -> bool {
+    let day = zone.day(&log.start, log.zone);
+    day >= from.date_naive() && day < to.date_naive()
+}
+
+/// Retrieve the logs that started on the days of the report.
+///
+/// # Arguments
+///
+/// * `from` - Start of the first day in the local zone
+/// * `to` - Start of the day after the last in the local zone
+/// * `zone` - Zone the days are in
+fn logs_on_days(
+    repo: &impl Repository,
+    from: &DateTime<Local>,
+    to: &DateTime<Local>,
+    zone: DisplayZone,
+) -> rusqlite::Result<Vec<Log>> {
+    match zone {
+        DisplayZone::Current => repo.logs_between(from, to),
+        DisplayZone::Recorded => {
+            // zones are at most 26 hours apart, so the logs start within two days either side
+            let mut logs =
+                repo.logs_between(&(*from - Duration::days(2)), &(*to + Duration::days(2)))?;
+            logs.retain(|log| started_within(log, from, to, zone));
+            Ok(logs)
+        }
+    }
+}
+
+/// Print the time logged and billable amounts per project or client between two dates.
+/// Durations of sub-projects are rolled up into their parents' totals.
+///
+/// # Arguments:
+///
+/// * `days` - Days the report covers, and the zone they are in
+/// * `client` - Optional reference to the only client to include
+/// * `by` - Whether to group by project or client
+/// * `json` - Print machine readable JSON instead of a table
+pub fn summary(
+    repo: &impl Repository,
+    clock: &impl Clock,
+    days: Days,
+    client: Option<String>,
+    by: GroupBy,
+    json: bool,
+) {
+    let (from, to) = match report_range(days.from, days.to, clock.now()) {
+        Ok(range) => range,
+        Err(e) => return eprintln!("{}", e),
+    };
+
+    let client_id = match client {
+        Some(reference) => match client::resolve::resolve(repo, &reference) {
+            Ok(c) => Some(c.id),
+            Err(e) => return eprintln!("{}", e),
+        },
+        None => None,
+    };
+
+    let projects = match repo.all_projects() {
+        Ok(projects) => projects,
+        Err(e) => return eprintln!("Error retrieving your projects: {}", e),
+    };
+    let clients = match repo.all_clients() {
+        Ok(clients) => clients,
+        Err(e) => return eprintln!("Error retrieving your clients: {}", e),
+    };
+    let mut logs = match logs_on_days(repo, &from, &to, days.zone) {
+        Ok(logs) => logs,
+        Err(e) => return eprintln!("Error retrieving logs: {}", e),
+    };
+
+    if client_id.is_some() {
+        logs.retain(|log| {
+            projects
+                .iter()
+                .find(|p| p.id == log.proj_id)
+                .and_then(|p| tree::client_id(p, &projects))
+                == client_id
+        });
+    }
+
+    let rates = match repo.all_rates() {
+        Ok(rates) => rates,
+        Err(e) => return eprintln!("Error retrieving rates: {}", e),
+    };
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => return eprintln!("Error reading config.toml: {}", e),
+    };
+    let book = RateBook::new(&projects, &clients, &rates);
+    let rule_for = |proj_id: u32| {
+        let client = projects
+            .iter()
+            .find(|p| p.id == proj_id)
+            .and_then(|p| tree::client_id(p, &projects))
+            .and_then(|id| clients.iter().find(|c| c.id == id));
+        config.rounding_for(client.map(|c| c.name.as_str()))
+    };
+
+    let own = own_totals(&logs, &book, rule_for);
+    match (by, json) {
+        (GroupBy::Project, false) => print_by_project(&projects, &own, &book),
+        (GroupBy::Client, false) => print_by_client(&projects, &clients, &own, &book),
+        (GroupBy::Project, true) => json_by_project(&projects, &own, &book),
+        (GroupBy::Client, true) => json_by_client(&projects, &clients, &own),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        clock::{in_zone, CENTRAL_EUROPE},
+        time::{parse_datetime, to_datetime},
+    };
+
+    use super::*;
+
+    fn project(id: u32, parent_id: Option<u32>, client_id: Option<u32>) -> Project {
+        let mut proj = Project::load(
+            id,
+            format!("p{}", id),
+            None,
+            to_datetime(0),
+            to_datetime(0),
+            Duration::zero(),
+        );
+        proj.parent_id = parent_id;
+        proj.client_id = client_id;
+        proj
+    }
+
+    fn client(id: u32, currency: &str, rate: i64) -> Client {
+        let mut client = Client::new(
+            format!("c{}", id),
+            None,
+            currency.to_owned(),
+            Some(Decimal::from(rate)),
+            None,
+        );
+        client.id = id;
+        client
+    }
+
+    #[test]
+    fn own_totals_sums_logs_per_project() {
+        // Arrange
+        let projects = vec![project(1, None, Some(1)), project(2, None, None)];
+        let clients = vec![client(1, "AUD", 60)];
+        let book = RateBook::new(&projects, &clients, &[]);
+
+        let mut non_billable = Log::new(3, 1, "c".to_string(), to_datetime(100), to_datetime(130));
+        non_billable.billable = false;
+        let logs = vec![
+            Log::new(1, 1, "a".to_string(), to_datetime(0), to_datetime(60)),
+            Log::new(2, 2, "b".to_string(), to_datetime(0), to_datetime(30)),
+            non_billable,
+        ];
+
+        // Act
+        let res = own_totals(&logs, &book, |_| None);
+
+        // Assert
+        assert_eq!(
+            Some(&Totals {
+                seconds: 90,
+                billable_seconds: 60,
+                rounded_seconds: 60,
+                amount: Decimal::ONE,
+            }),
+            res.get(&1)
+        );
+        assert_eq!(30, res[&2].seconds);
+        assert_eq!(Decimal::ZERO, res[&2].amount);
+    }
+
+    #[test]
+    fn own_totals_earns_on_rounded_time() {
+        // Arrange
+        let projects = vec![project(1, None, Some(1))];
+        let clients = vec![client(1, "AUD", 60)];
+        let book = RateBook::new(&projects, &clients, &[]);
+        let rule = RoundingRule {
+            increment: 15,
+            direction: rounding::Direction::Up,
+            scope: rounding::Scope::Entry,
+            minimum: 0,
+        };
+        let logs = vec![Log::new(
+            1,
+            1,
+            "a".to_string(),
+            to_datetime(0),
+            to_datetime(60),
+        )];
+
+        // Act
+        let res = own_totals(&logs, &book, |_| Some(&rule));
+
+        // Assert
+        assert_eq!(
+            (60, 900),
+            (res[&1].billable_seconds, res[&1].rounded_seconds)
+        );
+        assert_eq!(Decimal::from(15), res[&1].amount);
+    }
+
+    #[test]
+    fn client_totals_uses_inherited_client() {
+        // Arrange
+        let projects = vec![
+            project(1, None, Some(9)),
+            project(2, Some(1), None),
+            project(3, None, None),
+        ];
+        let seconds = |s| Totals {
+            seconds: s,
+            ..Default::default()
+        };
+        let own = HashMap::from([(1, seconds(10)), (2, seconds(20)), (3, seconds(5))]);
+
+        // Act
+        let res = client_totals(&projects, &own);
+
+        // Assert
+        assert_eq!(30, res[&Some(9)].seconds);
+        assert_eq!(5, res[&None].seconds);
+    }
+
+    #[test]
+    fn rolled_amount_skips_sub_projects_in_other_currencies() {
+        // Arrange
+        let projects = vec![
+            project(1, None, Some(1)),
+            project(2, Some(1), None),
+            project(3, Some(1), Some(2)),
+        ];
+        let clients = vec![client(1, "AUD", 1), client(2, "USD", 1)];
+        let book = RateBook::new(&projects, &clients, &[]);
+        let amount = |a| Totals {
+            amount: Decimal::from(a),
+            ..Default::default()
+        };
+        let own = HashMap::from([(1, amount(1)), (2, amount(10)), (3, amount(100))]);
+
+        // Act
+        let res = rolled_amount(1, &projects, &own, &book);
+
+        // Assert
+        assert_eq!(Decimal::from(11), res);
+        assert_eq!(
+            "AUD 11.00, USD 100.00",
+            format_amounts(&projects, &own, &book)
+        );
+    }
+
+    #[test]
+    fn report_range_includes_whole_last_day() {
+        // Act
+        let (from, to) = report_range(
+            Some("2026-09-01".to_string()),
+            Some("2026-09-30".to_string()),
+            Local::now(),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(parse_date("2026-09-01").unwrap(), from);
+        assert_eq!(parse_date("2026-10-01").unwrap(), to);
+    }
+
+    #[test]
+    fn report_range_invalid_date_returns_error() {
+        // Act
+        let res = report_range(Some("yesterday".to_string()), None, Local::now());
+
+        // Assert
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn report_range_without_last_day_ends_a_day_after_now() {
+        // Arrange
+        let now = parse_datetime("2026-10-25 23:30").unwrap();
+
+        // Act
+        let (_, to) = report_range(None, None, now).unwrap();
+
+        // Assert
+        assert_eq!(now + Duration::days(1), to);
+    }
+
+    #[test]
+    fn started_within_should_count_log_on_the_day_it_started_in_the_display_zone() {
+        if in_zone(
+            CENTRAL_EUROPE,
+            concat!(
+                module_path!(),
+                "::started_within_should_count_log_on_the_day_it_started_in_the_display_zone"
+            ),
+        ) {
+            return;
+        }
+
+        // Arrange
+        // 22:00 on the 19th in Berlin, 05:00 on the 20th in Tokyo
+        let mut log = Log::new(
+            1,
+            1,
+            "call".to_owned(),
+            parse_datetime("2026-10-19 22:00").unwrap(),
+            parse_datetime("2026-10-19 23:00").unwrap(),
+        );
+        log.zone = Some("Asia/Tokyo".parse().unwrap());
+        let day = |date: &str| {
+            let from = parse_datetime(&format!("{} 00:00", date)).unwrap();
+            (from, from + Duration::days(1))
+        };
+        let (nineteenth, twentieth) = (day("2026-10-19"), day("2026-10-20"));
+
+        // Act
+        let current = (
+            started_within(&log, &nineteenth.0, &nineteenth.1, DisplayZone::Current),
+            started_within(&log, &twentieth.0, &twentieth.1, DisplayZone::Current),
+        );
+        let recorded = (
+            started_within(&log, &nineteenth.0, &nineteenth.1, DisplayZone::Recorded),
+            started_within(&log, &twentieth.0, &twentieth.1, DisplayZone::Recorded),
+        );
+
+        // Assert
+        assert_eq!((true, false), current);
+        assert_eq!((false, true), recorded);
+    }
+}
@@ -1,8 +1,57 @@
-use chrono::{DateTime, Local, TimeZone};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    error::Error,
+    ops::Deref,
+    path::Path,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Duration, Local};
 use mockall::automock;
-use rusqlite::{params, Connection, Result};
+use rusqlite::{
+    backup::Progress, ffi, params, types::Type, Connection, DatabaseName, OptionalExtension,
+    Result, Row,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::{
+    clock::{Clock, SystemClock},
+    config::Config,
+    db::backups::{self, Kind},
+    integrity::chain::{self, Sealed},
+    log::search::Pattern,
+    model::{
+        Budget, BudgetPeriod, Client, Goal, GoalPeriod, GoalScope, Invoice, InvoiceLine, Log,
+        LogRevision, Operation, OperationState, Project, Rate, RateScope,
+    },
+    time::{from_millis, to_datetime},
+};
 
-use crate::model::{Log, Project};
+#[cfg(test)]
+mod conformance;
+pub mod text;
+
+/// Where projects and logs are stored
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Storage {
+    pub backend: Backend,
+    /// Directory of the text files, `$XDG_DATA_HOME/arrow/text` unless set otherwise
+    pub dir: Option<PathBuf>,
+}
+
+/// The kind of repository projects and logs are stored in
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    /// A SQLite database at `$XDG_CONFIG_HOME/arrow/arrow.db`
+    #[default]
+    Sqlite,
+    /// Human readable text files, with the logs of each month in a file of their own
+    Text,
+}
 
 #[automock]
 pub trait Repository {
@@ -11,14 +60,112 @@ pub trait Repository {
     fn save_log(&self, project_id: &u32, log: &Log) -> Result<u32>;
     fn get_project(&self, id: &u32) -> Result<(Project, Vec<Log>)>;
     fn get_project_by_name(&self, name: &str) -> Result<Project>;
-    fn get_logs(&self, proj_id: &u32, msg: &str) -> Result<Vec<Log>>;
+    fn search_logs(&self, proj_id: Option<u32>, pattern: &Pattern) -> Result<Vec<Log>>;
+    fn logs_between(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<Log>>;
+    fn save_alias(&self, project_id: &u32, alias: &str) -> Result<()>;
+    fn all_aliases(&self) -> Result<Vec<(String, u32)>>;
+    fn save_client(&self, client: &Client) -> Result<u32>;
+    fn all_clients(&self) -> Result<Vec<Client>>;
+    fn get_client(&self, id: &u32) -> Result<Client>;
+    fn update_client(&self, client: &Client) -> Result<usize>;
+    fn delete_client(&self, id: &u32) -> Result<usize>;
+    fn save_rate(&self, rate: &Rate) -> Result<u32>;
+    fn all_rates(&self) -> Result<Vec<Rate>>;
+    fn delete_rate(&self, id: &u32) -> Result<usize>;
     fn update_project(&self, project: &Project) -> Result<usize>;
-    fn delete_project(&self, id: &u32);
-    fn delete_log(&self, proj_id: &u32, log_id: &u32);
+    fn delete_project(&self, id: &u32) -> Result<usize>;
+    fn delete_log(&self, proj_id: &u32, log_id: &u32) -> Result<usize>;
+    fn get_log(&self, id: &u32) -> Result<Log>;
+    fn update_log(&self, log: &Log) -> Result<usize>;
+    fn save_invoice(&self, invoice: &Invoice, log_ids: &[u32]) -> Result<u32>;
+    fn all_invoices(&self) -> Result<Vec<Invoice>>;
+    fn save_goal(&self, goal: &Goal) -> Result<u32>;
+    fn all_goals(&self) -> Result<Vec<Goal>>;
+    fn delete_goal(&self, id: &u32) -> Result<usize>;
+    fn is_imported(&self, source: &str, external_id: &str) -> Result<bool>;
+    fn import_log(&self, source: &str, external_id: &str, log: &Log) -> Result<Option<u32>>;
+    fn all_imports(&self) -> Result<Vec<(String, String, u32)>>;
+    fn all_logs(&self) -> Result<Vec<Log>>;
+    fn clear(&self) -> Result<()>;
+    fn begin_operation(&self, command: &str) -> Result<u32>;
+    fn end_operation(&self, id: &u32) -> Result<()>;
+    fn all_operations(&self) -> Result<Vec<Operation>>;
+    fn undo(&self) -> Result<Option<Operation>>;
+    fn redo(&self) -> Result<Option<Operation>>;
+    fn log_history(&self, id: &u32) -> Result<Vec<LogRevision>>;
+    fn all_log_revisions(&self) -> Result<Vec<LogRevision>>;
+    fn save_log_revision(&self, revision: &LogRevision) -> Result<u32>;
+    fn log_chain(&self) -> Result<Vec<Sealed>>;
+    fn seal_logs(&self) -> Result<usize>;
+    fn begin_transaction(&self) -> Result<()>;
+    fn commit_transaction(&self) -> Result<()>;
+    fn rollback_transaction(&self) -> Result<()>;
+}
+
+/// Runs the steps in a transaction, so their changes are committed together if every step
+/// succeeds and rolled back if any fails. Transactions can be nested
+///
+/// # Arguments
+///
+/// * `repo` - Repository the steps change
+/// * `steps` - Changes to make, using `repo`
+pub fn atomically<R: Repository, T, E: From<rusqlite::Error>>(
+    repo: &R,
+    steps: impl FnOnce() -> std::result::Result<T, E>,
+) -> std::result::Result<T, E> {
+    repo.begin_transaction()?;
+    let done = steps().and_then(|value| {
+        repo.commit_transaction()?;
+        Ok(value)
+    });
+    if done.is_err() {
+        let _ = repo.rollback_transaction();
+    }
+    done
 }
 
 pub struct Sqlite {
     conn: Connection,
+    /// Whether logs are sealed in a hash chain as they are saved, changed and deleted
+    chained: bool,
+    /// Tells the time changes are made at
+    clock: Box<dyn Clock>,
+}
+
+/// A savepoint on the connection, released when committed and rolled back when dropped before
+/// then. Unlike transactions, scopes nest inside each other and inside `begin_transaction`
+struct Scope<'a> {
+    conn: &'a Connection,
+    open: bool,
+}
+
+impl<'a> Scope<'a> {
+    fn begin(conn: &'a Connection) -> Result<Self> {
+        conn.execute_batch("SAVEPOINT arrow")?;
+        Ok(Scope { conn, open: true })
+    }
+
+    fn commit(mut self) -> Result<()> {
+        self.conn.execute_batch("RELEASE arrow")?;
+        self.open = false;
+        Ok(())
+    }
+}
+
+impl Deref for Scope<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+    }
+}
+
+impl Drop for Scope<'_> {
+    fn drop(&mut self) {
+        if self.open {
+            let _ = self.conn.execute_batch("ROLLBACK TO arrow; RELEASE arrow");
+        }
+    }
 }
 
 /*
@@ -37,133 +184,496 @@ Contract:
 */
 
 impl Sqlite {
-    pub fn new() -> Result<Self, rusqlite::Error> {
-        let xdg_dirs = xdg::BaseDirectories::with_prefix("arrow").unwrap();
-        let db_path = xdg_dirs.get_config_home().join("arrow.db");
-        let conn = Connection::open(db_path)?;
-        Ok(Sqlite { conn })
+    /// Opens the arrow database, creating it if needed, and brings its schema up to date. The
+    /// database is backed up before it is migrated, and whenever a scheduled backup is due.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let xdg_dirs = xdg::BaseDirectories::with_prefix("arrow")?;
+        let db_path = xdg_dirs.place_config_file("arrow.db")?;
+        let mut conn = Connection::open(db_path)?;
+
+        let runner = embedded::migrations::runner();
+        let migrated: bool = conn.query_row(
+            "SELECT EXISTS (
+                SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'refinery_schema_history'
+            )",
+            [],
+            |row| row.get(0),
+        )?;
+        // a new database has nothing worth backing up yet
+        let pending = migrated
+            && runner
+                .get_last_applied_migration(&mut conn)?
+                .is_some_and(|last| {
+                    runner
+                        .get_migrations()
+                        .iter()
+                        .any(|m| m.version() > last.version())
+                });
+        let config = Config::load()?;
+        let clock = SystemClock;
+        if pending {
+            backups::rotate(&conn, &clock, Kind::Migration, config.backups.keep)?;
+        }
+        runner.run(&mut conn)?;
+        install_journal(&conn)?;
+
+        backups::on_schedule(&conn, &clock, config.backups);
+        Ok(Sqlite {
+            conn,
+            chained: config.integrity.chain,
+            clock: Box::new(clock),
+        })
+    }
+
+    /// Backs the database up to the backups directory, rotating out the oldest backups of the
+    /// same kind. Returns where the backup was written
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Why the backup is taken
+    /// * `keep` - How many backups of the kind to keep
+    pub fn back_up(&self, kind: Kind, keep: usize) -> Result<PathBuf, Box<dyn Error>> {
+        backups::rotate(&self.conn, &self.clock, kind, keep)
+    }
+
+    /// Overwrites the database with a copy of it, then brings its schema up to date
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Backup or other copy of the database to restore
+    pub fn restore_from(&mut self, path: &Path) -> Result<(), Box<dyn Error>> {
+        self.conn
+            .restore(DatabaseName::Main, path, None::<fn(Progress)>)?;
+        embedded::migrations::runner().run(&mut self.conn)?;
+        install_journal(&self.conn)?;
+        Ok(())
+    }
+
+    /// Opens an empty database that only lives as long as the connection, with an up to date
+    /// schema. Logs are not sealed
+    pub fn in_memory() -> Result<Self, Box<dyn Error>> {
+        let mut conn = Connection::open_in_memory()?;
+        embedded::migrations::runner().run(&mut conn)?;
+        install_journal(&conn)?;
+        Ok(Sqlite {
+            conn,
+            chained: false,
+            clock: Box::new(SystemClock),
+        })
+    }
+
+    /// Uses the clock to tell the time changes are made at, instead of the system's
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Clock to tell the time by
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// The newest or oldest operation in a state
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - State of the operation to find
+    /// * `newest` - Whether to find the newest operation in the state, or the oldest
+    fn operation(&self, state: OperationState, newest: bool) -> Result<Option<Operation>> {
+        let mut operations: Vec<Operation> = self
+            .all_operations()?
+            .into_iter()
+            .filter(|o| o.state == state)
+            .collect();
+
+        Ok(match newest {
+            true => operations.pop(),
+            false if operations.is_empty() => None,
+            false => Some(operations.remove(0)),
+        })
+    }
+
+    /// Puts the rows an operation changed back the way they were before it, or redoes its changes.
+    /// Either every change is applied or none are
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The operation to revert or replay
+    /// * `undo` - Whether to revert the operation, or replay it
+    fn replay(&self, operation: &Operation, undo: bool) -> Result<()> {
+        let tx = Scope::begin(&self.conn)?;
+
+        let order = if undo { "DESC" } else { "ASC" };
+        let mut stmt = tx.prepare(&format!(
+            "SELECT table_name, old_row, new_row
+            FROM operation_changes
+            WHERE operation_id = ?1
+            ORDER BY id {}",
+            order
+        ))?;
+        let changes = stmt
+            .query_map([operation.id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        // rows are briefly inconsistent while an operation is replayed change by change
+        tx.execute_batch("PRAGMA defer_foreign_keys = ON")?;
+        for (table, old_row, new_row) in changes {
+            let (from, to) = if undo {
+                (new_row, old_row)
+            } else {
+                (old_row, new_row)
+            };
+
+            match (from.as_deref().map(row_image), to.as_deref().map(row_image)) {
+                (Some(from), Some(to)) => {
+                    let to = to?;
+                    let columns: Vec<String> = to
+                        .keys()
+                        .enumerate()
+                        .map(|(i, c)| format!("\"{}\" = ?{}", c, i + 2))
+                        .collect();
+                    tx.execute(
+                        &format!(
+                            "UPDATE \"{}\" SET {} WHERE rowid = ?1",
+                            table,
+                            columns.join(", ")
+                        ),
+                        rusqlite::params_from_iter(
+                            [sql_value(&from?["rowid"])]
+                                .into_iter()
+                                .chain(to.values().map(sql_value)),
+                        ),
+                    )?;
+                }
+                (Some(from), None) => {
+                    tx.execute(
+                        &format!("DELETE FROM \"{}\" WHERE rowid = ?1", table),
+                        [sql_value(&from?["rowid"])],
+                    )?;
+                }
+                (None, Some(to)) => {
+                    let to = to?;
+                    let columns: Vec<String> = to.keys().map(|c| format!("\"{}\"", c)).collect();
+                    let placeholders: Vec<String> =
+                        (1..=to.len()).map(|i| format!("?{}", i)).collect();
+                    tx.execute(
+                        &format!(
+                            "INSERT INTO \"{}\" ({}) VALUES ({})",
+                            table,
+                            columns.join(", "),
+                            placeholders.join(", ")
+                        ),
+                        rusqlite::params_from_iter(to.values().map(sql_value)),
+                    )?;
+                }
+                (None, None) => {}
+            }
+        }
+
+        let state = if undo { "undone" } else { "done" };
+        tx.execute(
+            "UPDATE operations SET state = ?1 WHERE id = ?2",
+            params![state, operation.id],
+        )?;
+
+        drop(stmt);
+        tx.commit()
+    }
+
+    /// ID of the tag with the given name, creating the tag if it does not exist yet
+    fn tag_id(&self, name: &str) -> Result<u32> {
+        self.conn
+            .execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [name])?;
+        self.conn
+            .query_row("SELECT id FROM tags WHERE name = ?1", [name], |row| {
+                row.get(0)
+            })
+    }
+
+    /// Every log from the given ID on with its stored hash, in order, and the hash of the log
+    /// before them
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - ID of the first log to retrieve
+    fn chain_from(&self, from: &u32) -> Result<(Option<String>, Vec<Sealed>)> {
+        let previous = self
+            .conn
+            .query_row(
+                "SELECT hash FROM logs WHERE id < ?1 ORDER BY id DESC LIMIT 1",
+                [from],
+                |row| row.get(0),
+            )
+            .optional()?
+            .flatten();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, project_id, message, start, end, billable, invoice_id, zone, hash
+            FROM logs
+            WHERE id >= ?1
+            ORDER BY id",
+        )?;
+        let rows = stmt.query_map([from], |row| Ok((log_from_row(row)?, row.get(8)?)))?;
+        let (mut logs, hashes): (Vec<Log>, Vec<Option<String>>) =
+            rows.collect::<Result<Vec<Sealed>>>()?.into_iter().unzip();
+        self.attach_tags(&mut logs)?;
+
+        Ok((previous, logs.into_iter().zip(hashes).collect()))
+    }
+
+    /// IDs of the logs from the given ID on that still match their hash. Taken before arrow
+    /// changes logs, so only these are sealed again afterwards
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - ID of the first log to check
+    fn intact_logs(&self, from: &u32) -> Result<HashSet<u32>> {
+        if !self.chained {
+            return Ok(HashSet::new());
+        }
+
+        let (mut previous, chain) = self.chain_from(from)?;
+        let mut intact = HashSet::new();
+        for (log, hash) in chain {
+            if hash.is_some() && hash == Some(chain::link(previous.as_deref(), &log)) {
+                intact.insert(log.id);
+            }
+            previous = hash;
+        }
+
+        Ok(intact)
+    }
+
+    /// Hashes the given logs again, chaining each to the log before it. Other logs keep their
+    /// hash, so a log changed without arrow still fails verification
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - ID of the first log that may need a new hash
+    /// * `logs` - IDs of the logs to hash
+    fn seal(&self, from: &u32, logs: &HashSet<u32>) -> Result<()> {
+        if !self.chained {
+            return Ok(());
+        }
+
+        let (mut previous, chain) = self.chain_from(from)?;
+        for (log, mut hash) in chain {
+            if logs.contains(&log.id) {
+                let link = chain::link(previous.as_deref(), &log);
+                if hash.as_ref() != Some(&link) {
+                    self.conn.execute(
+                        "UPDATE logs SET hash = ?1 WHERE id = ?2",
+                        params![link, log.id],
+                    )?;
+                }
+                hash = Some(link);
+            }
+            previous = hash;
+        }
+
+        Ok(())
+    }
+
+    /// Fills in the tags of each log, and whether it was edited
+    fn attach_tags(&self, logs: &mut [Log]) -> Result<()> {
+        if logs.is_empty() {
+            return Ok(());
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT lt.log_id, t.name
+            FROM log_tags lt
+            INNER JOIN tags t ON t.id = lt.tag_id
+            ORDER BY lt.rowid",
+        )?;
+        let mut rows = stmt.query([])?;
+
+        let mut tags: HashMap<u32, Vec<String>> = HashMap::new();
+        while let Some(row) = rows.next()? {
+            tags.entry(row.get(0)?).or_default().push(row.get(1)?);
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT log_id FROM log_revisions")?;
+        let edited = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<HashSet<u32>>>()?;
+
+        for log in logs.iter_mut() {
+            log.tags = tags.remove(&log.id).unwrap_or_default();
+            log.edited = edited.contains(&log.id);
+        }
+
+        Ok(())
     }
 }
 
 impl Repository for Sqlite {
-    /// Saves the given project to the database
+    /// Saves the given project to the database. A non-zero ID is kept, otherwise one is generated
     ///
     /// # Arguments
     ///
     /// * `project` - The project to be saved
     fn save_project(&self, project: &Project) -> Result<u32> {
         self.conn.execute(
-            "INSERT INTO projects (name, description, created, updated, duration) VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT INTO projects (id, name, description, created, updated, duration, parent_id, client_id, budget_seconds, budget_period) VALUES (NULLIF(?1, 0), ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
+                project.id,
                 project.name,
                 project.description,
                 project.created.timestamp(),
-                project.created.timestamp(),
-                project.duration
+                project.updated.timestamp(),
+                project.duration.num_milliseconds(),
+                project.parent_id,
+                project.client_id,
+                project.budget.map(|b| b.seconds),
+                project.budget.and_then(|b| b.period).map(period_to_sql)
             ],
         )?;
 
         Ok(self.conn.last_insert_rowid() as u32)
     }
 
-    /// Saves the log to the database
+    /// Saves the log to the database. A non-zero ID is kept, otherwise one is generated. The log
+    /// is sealed when the hash chain is turned on
     ///
     /// # Arguments
     ///
     /// * `project_id` - ID of the project to save the log under
     /// * `log` - The log to be saved
     fn save_log(&self, project_id: &u32, log: &Log) -> Result<u32> {
+        // logs saved with a new ID come last, nothing after them needs sealing again
+        let mut intact = match log.id {
+            0 => HashSet::new(),
+            id => self.intact_logs(&id)?,
+        };
+        let tx = Scope::begin(&self.conn)?;
+
         self.conn.execute(
-            "INSERT INTO logs (message, start, end, project_id) VALUES (?1, ?2, ?3, ?4)",
+            "INSERT INTO logs (id, message, start, end, project_id, billable, zone)
+            VALUES (NULLIF(?1, 0), ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
+                log.id,
                 log.message,
-                log.start.timestamp(),
-                log.end.timestamp(),
-                project_id
+                log.start.timestamp_millis(),
+                log.end.timestamp_millis(),
+                project_id,
+                log.billable,
+                log.zone.map(|zone| zone.to_string())
             ],
         )?;
+        let log_id = self.conn.last_insert_rowid() as u32;
+
+        for tag in &log.tags {
+            self.conn
+                .execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [tag])?;
+            self.conn.execute(
+                "INSERT OR IGNORE INTO log_tags (log_id, tag_id)
+                SELECT ?1, id FROM tags WHERE name = ?2",
+                params![log_id, tag],
+            )?;
+        }
 
-        Ok(self.conn.last_insert_rowid() as u32)
+        intact.insert(log_id);
+        self.seal(&log_id, &intact)?;
+        tx.commit()?;
+        Ok(log_id)
     }
 
-    /// Delete a project by it's ID. Deletes all logs that are associated with it
+    /// Delete a project by it's ID. Deletes all logs that are associated with it. Returns the
+    /// number of projects deleted
     ///
     /// # Arguments
     ///
     /// * `id` - ID of the project to delete
-    fn delete_project(&self, id: &u32) {
-        match self
+    fn delete_project(&self, id: &u32) -> Result<usize> {
+        let first_log = self
             .conn
-            .execute("DELETE FROM logs WHERE project_id = ?1", [id])
-        {
-            Err(err) => panic!("Delete failed: {}", err),
-            Ok(_) => {}
-        }
+            .query_row(
+                "SELECT MIN(id) FROM logs WHERE project_id = ?1",
+                [id],
+                |row| row.get::<_, Option<u32>>(0),
+            )?
+            .unwrap_or(u32::MAX);
+        let intact = self.intact_logs(&first_log)?;
+        let tx = Scope::begin(&self.conn)?;
+
+        tx.execute("DELETE FROM project_aliases WHERE project_id = ?1", [id])?;
+        tx.execute("DELETE FROM rates WHERE project_id = ?1", [id])?;
+        tx.execute("DELETE FROM goals WHERE project_id = ?1", [id])?;
+        tx.execute(
+            "DELETE FROM log_tags WHERE log_id IN (SELECT id FROM logs WHERE project_id = ?1)",
+            [id],
+        )?;
+        tx.execute(
+            "DELETE FROM imports WHERE log_id IN (SELECT id FROM logs WHERE project_id = ?1)",
+            [id],
+        )?;
+        tx.execute(
+            "DELETE FROM log_revisions WHERE log_id IN (SELECT id FROM logs WHERE project_id = ?1)",
+            [id],
+        )?;
+        tx.execute("DELETE FROM logs WHERE project_id = ?1", [id])?;
+        self.seal(&first_log, &intact)?;
+        let deleted = tx.execute("DELETE FROM projects WHERE id = ?1", [id])?;
 
-        match self
-            .conn
-            .execute("DELETE FROM projects WHERE id = ?1", [id])
-        {
-            Err(err) => panic!("Delete failed: {}", err),
-            Ok(rows) => {
-                if rows < 1 {
-                    eprintln!(
-                        "No project with id {} exists. Please specify an existing project.",
-                        id
-                    );
-                } else {
-                    println!("Deleted project {}", id);
-                }
-            }
-        };
+        tx.commit()?;
+        Ok(deleted)
     }
 
-    /// Delete a log by it's project ID and log ID.
+    /// Delete a log by it's project ID and log ID. Invoiced logs are never deleted. Returns the
+    /// number of logs deleted
     ///
     /// # Arguments
     ///
     /// * `proj_id` - ID of the project to delete the log from
     /// * `log_id` - ID of the log to delete
-    fn delete_log(&self, proj_id: &u32, log_id: &u32) {
-        match self.conn.execute(
-            "DELETE FROM logs WHERE id = ?1 AND project_id = ?2",
+    fn delete_log(&self, proj_id: &u32, log_id: &u32) -> Result<usize> {
+        let intact = self.intact_logs(log_id)?;
+        let tx = Scope::begin(&self.conn)?;
+
+        for table in ["log_tags", "imports", "log_revisions"] {
+            tx.execute(
+                &format!(
+                    "DELETE FROM {}
+                    WHERE log_id IN (
+                        SELECT id FROM logs WHERE id = ?1 AND project_id = ?2 AND invoice_id IS NULL
+                    )",
+                    table
+                ),
+                [log_id, proj_id],
+            )?;
+        }
+        let deleted = tx.execute(
+            "DELETE FROM logs WHERE id = ?1 AND project_id = ?2 AND invoice_id IS NULL",
             [log_id, proj_id],
-        ) {
-            Ok(rows) => {
-                if rows < 1 {
-                    eprintln!(
-                        "No log with id {} exists for project {}. Please specify an existing log.",
-                        log_id, proj_id
-                    );
-                } else {
-                    println!("Deleted log {}", log_id);
-                }
-            }
-            Err(err) => panic!("Delete failed: {}", err),
-        };
+        )?;
+        if deleted > 0 {
+            self.seal(log_id, &intact)?;
+        }
+
+        tx.commit()?;
+        Ok(deleted)
     }
 
     /// Retrieve all projects in the database
     fn all_projects(&self) -> Result<Vec<Project>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, created, updated, duration
+            "SELECT id, name, description, created, updated, duration, parent_id, client_id,
+                budget_seconds, budget_period
             FROM projects",
         )?;
         let mut rows = stmt.query([])?;
 
         let mut projects: Vec<Project> = Vec::new();
         while let Some(row) = rows.next()? {
-            let created = to_datetime(row.get(3)?);
-            let updated = to_datetime(row.get(4)?);
-
-            projects.push(Project::load(
-                row.get(0)?,
-                row.get(1)?,
-                row.get(2)?,
-                created,
-                updated,
-                row.get(5)?
-            ));
+            projects.push(project_from_row(row)?);
         }
 
         Ok(projects)
@@ -176,93 +686,106 @@ impl Repository for Sqlite {
     /// - `id` - ID of the project to retrieve
     fn get_project(&self, id: &u32) -> Result<(Project, Vec<Log>)> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, created, updated, duration
+            "SELECT id, name, description, created, updated, duration, parent_id, client_id,
+                budget_seconds, budget_period
             FROM projects
             WHERE id = ?1",
         )?;
-        let proj = stmt.query_row([id], |row| {
-            let created = to_datetime(row.get(3)?);
-            let updated = to_datetime(row.get(4)?);
-
-            Ok(Project::load(
-                row.get(0)?,
-                row.get(1)?,
-                row.get(2)?,
-                created,
-                updated,
-                row.get(5)?
-            ))
-        })?;
+        let proj = stmt.query_row([id], project_from_row)?;
 
         stmt = self.conn.prepare(
-            "SELECT l.id, l.message, l.start, l.end 
+            "SELECT l.id, l.project_id, l.message, l.start, l.end, l.billable, l.invoice_id,
+                l.zone
                 FROM projects p
                 INNER JOIN logs l ON p.id = l.project_id
                 WHERE p.id = ?1",
         )?;
 
-        let mut rows = stmt.query([id])?;
-        let mut logs: Vec<Log> = Vec::new();
-
-        while let Some(row) = rows.next()? {
-            let start = to_datetime(row.get(2)?);
-            let end = to_datetime(row.get(3)?);
-
-            logs.push(Log::new(row.get(0)?, *id, row.get(1)?, start, end))
-        }
+        let rows = stmt.query_map([id], log_from_row)?;
+        let mut logs = rows.collect::<Result<Vec<Log>>>()?;
+        self.attach_tags(&mut logs)?;
 
         Ok((proj, logs))
     }
 
     fn get_project_by_name(&self, name: &str) -> Result<Project> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, created, updated, duration
+            "SELECT id, name, description, created, updated, duration, parent_id, client_id,
+                budget_seconds, budget_period
             FROM projects
             WHERE name = ?1",
         )?;
-        let proj = stmt.query_row([name], |row| {
-            let created = to_datetime(row.get(3)?);
-            let updated = to_datetime(row.get(4)?);
-
-            Ok(Project::load(
-                row.get(0)?,
-                row.get(1)?,
-                row.get(2)?,
-                created,
-                updated,
-                row.get(5)?
-            ))
-        })?;
+        let proj = stmt.query_row([name], project_from_row)?;
 
         Ok(proj)
     }
 
-    fn get_logs(&self, proj_id: &u32, msg: &str) -> Result<Vec<Log>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT
-                l.id
-                l.message
-                l.start
-                l.end
-            FROM 
-                logs l 
-            INNER JOIN 
-                projects p ON l.project_id = p.id
-            WHERE 
-                p.id = ?1 
-            AND
-                l.message LIKE %?2%",
-        )?;
-
-        let mut rows = stmt.query(params![proj_id, msg])?;
-        let mut logs: Vec<Log> = Vec::new();
+    /// Retrieve the logs whose message matches, oldest first. The search index narrows the logs
+    /// down, and the pattern decides which of them match, so both repositories match alike.
+    /// Regular expressions are narrowed down by the text every match contains, and every log is
+    /// matched against those without any such text of at least 3 characters
+    ///
+    /// # Arguments
+    ///
+    /// * `proj_id` - ID of the only project to search, `None` to search every project
+    /// * `pattern` - What to match the messages against
+    fn search_logs(&self, proj_id: Option<u32>, pattern: &Pattern) -> Result<Vec<Log>> {
+        let like = |text: &str| {
+            (
+                "LIKE ?2 ESCAPE '\\'",
+                format!(
+                    "%{}%",
+                    text.replace('\\', "\\\\")
+                        .replace('%', "\\%")
+                        .replace('_', "\\_")
+                ),
+            )
+        };
+        let (operator, indexed) = match pattern {
+            Pattern::Text(text) => like(text),
+            Pattern::Glob(glob, _) => ("GLOB ?2", glob.clone()),
+            Pattern::Regex(_) => match pattern.required_text() {
+                Some(text) => like(&text),
+                None => ("GLOB ?2", "*".to_owned()),
+            },
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, project_id, message, start, end, billable, invoice_id, zone
+            FROM logs
+            WHERE (?1 IS NULL OR project_id = ?1)
+                AND id IN (SELECT rowid FROM logs_search WHERE message {})
+            ORDER BY start",
+            operator
+        ))?;
+
+        let rows = stmt.query_map(params![proj_id, indexed], log_from_row)?;
+        let mut logs = rows.collect::<Result<Vec<Log>>>()?;
+        logs.retain(|log| pattern.matches(&log.message));
+        self.attach_tags(&mut logs)?;
 
-        while let Some(row) = rows.next()? {
-            let start = to_datetime(row.get(2)?);
-            let end = to_datetime(row.get(3)?);
+        Ok(logs)
+    }
 
-            logs.push(Log::new(row.get(0)?, *proj_id, row.get(1)?, start, end));
-        }
+    /// Retrieve the logs of every project that started within the given range
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Inclusive start of the range
+    /// * `to` - Exclusive end of the range
+    fn logs_between(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<Log>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, project_id, message, start, end, billable, invoice_id, zone
+            FROM logs
+            WHERE start >= ?1 AND start < ?2
+            ORDER BY start",
+        )?;
+
+        let rows = stmt.query_map(
+            [from.timestamp_millis(), to.timestamp_millis()],
+            log_from_row,
+        )?;
+        let mut logs = rows.collect::<Result<Vec<Log>>>()?;
+        self.attach_tags(&mut logs)?;
 
         Ok(logs)
     }
@@ -270,320 +793,1078 @@ impl Repository for Sqlite {
     fn update_project(&self, project: &Project) -> Result<usize> {
         match self.conn.execute(
             "UPDATE projects
-            SET name = ?1, description = ?2, updated = ?3, duration = ?4
-            WHERE id = ?5",
+            SET name = ?1, description = ?2, updated = ?3, duration = ?4, parent_id = ?5,
+                client_id = ?6, budget_seconds = ?7, budget_period = ?8
+            WHERE id = ?9",
             params![
                 project.name,
                 project.description,
-                Local::now().timestamp(),
-                project.duration,
+                self.clock.now().timestamp(),
+                project.duration.num_milliseconds(),
+                project.parent_id,
+                project.client_id,
+                project.budget.map(|b| b.seconds),
+                project.budget.and_then(|b| b.period).map(period_to_sql),
                 project.id
             ],
         ) {
-            Ok(updated) => return Ok(updated),
+            Ok(updated) => Ok(updated),
             Err(err) => panic!("Update failed: {}", err),
         }
     }
-}
 
-fn to_datetime(timestamp: i64) -> DateTime<Local> {
-    Local
-        .timestamp_opt(timestamp, 0)
-        .single()
-        .expect("Failed to read timestamp")
-}
+    /// Registers an alternative name the project can be referred to by
+    ///
+    /// # Arguments
+    ///
+    /// * `project_id` - ID of the project the alias points to
+    /// * `alias` - The alias. Must be unique across all projects
+    fn save_alias(&self, project_id: &u32, alias: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO project_aliases (alias, project_id) VALUES (?1, ?2)",
+            params![alias, project_id],
+        )?;
 
-mod embedded {
-    use refinery::embed_migrations;
-    embed_migrations!();
-}
+        Ok(())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Retrieve every alias along with the ID of the project it points to
+    fn all_aliases(&self) -> Result<Vec<(String, u32)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT alias, project_id FROM project_aliases")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
 
-    // project saves successfully  // DONE
-    // logs save successfully // log without a project is rejected  // DONE
-    // edge cases - empty string; null string; integer
-    // check if project saves w/o description // DONE
-    // delete projects + logs // DONE
-    // update projects + logs
+        rows.collect()
+    }
 
-    fn test_repo() -> impl Repository {
-        let mut conn = Connection::open_in_memory().unwrap();
-        embedded::migrations::runner().run(&mut conn).unwrap();
-        Sqlite { conn }
+    /// Saves the given client to the database. A non-zero ID is kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client to be saved. Names must be unique
+    fn save_client(&self, client: &Client) -> Result<u32> {
+        self.conn.execute(
+            "INSERT INTO clients (id, name, contact, currency, hourly_rate, address)
+            VALUES (NULLIF(?1, 0), ?2, ?3, ?4, ?5, ?6)",
+            params![
+                client.id,
+                client.name,
+                client.contact,
+                client.currency,
+                client.hourly_rate.map(|r| r.to_string()),
+                client.address
+            ],
+        )?;
+
+        Ok(self.conn.last_insert_rowid() as u32)
     }
 
-    fn default_test_project() -> Project {
-        let created = Local::now();
-        let updated = Local::now();
+    /// Retrieve all clients in the database, ordered by name
+    fn all_clients(&self) -> Result<Vec<Client>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, contact, currency, hourly_rate, address
+            FROM clients
+            ORDER BY name",
+        )?;
+        let rows = stmt.query_map([], client_from_row)?;
 
-        Project::new(
-            "test".to_owned(),
-            Option::Some("hi".to_owned()),
-            created,
-            updated,
+        rows.collect()
+    }
+
+    /// Retrieve a client by ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the client to retrieve
+    fn get_client(&self, id: &u32) -> Result<Client> {
+        self.conn.query_row(
+            "SELECT id, name, contact, currency, hourly_rate, address
+            FROM clients
+            WHERE id = ?1",
+            [id],
+            client_from_row,
         )
     }
 
-    #[test]
-    fn save_project_should_save_in_db() {
-        // Arrange
-        let repo = test_repo();
-        let project = default_test_project();
+    /// Overwrites the stored client with the same ID. Returns the number of rows updated
+    fn update_client(&self, client: &Client) -> Result<usize> {
+        self.conn.execute(
+            "UPDATE clients
+            SET name = ?1, contact = ?2, currency = ?3, hourly_rate = ?4, address = ?5
+            WHERE id = ?6",
+            params![
+                client.name,
+                client.contact,
+                client.currency,
+                client.hourly_rate.map(|r| r.to_string()),
+                client.address,
+                client.id
+            ],
+        )
+    }
 
-        // Act
-        let id = repo.save_project(&project);
+    /// Delete a client and its rates by ID. Fails while projects are still billed to the client,
+    /// or invoices were made out to it
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the client to delete
+    fn delete_client(&self, id: &u32) -> Result<usize> {
+        let referenced: bool = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM projects WHERE client_id = ?1)
+                OR EXISTS (SELECT 1 FROM invoices WHERE client_id = ?1)",
+            [id],
+            |row| row.get(0),
+        )?;
+        if referenced {
+            return Err(constraint("FOREIGN KEY constraint failed"));
+        }
 
-        // Assert
-        let (actual_project, _) = repo.get_project(&id.unwrap()).unwrap();
+        self.conn
+            .execute("DELETE FROM rates WHERE client_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM clients WHERE id = ?1", [id])
+    }
 
-        assert_eq!("test", actual_project.name);
-        assert_eq!("hi", actual_project.description.unwrap());
-        assert_eq!(
-            project.created.timestamp(),
-            actual_project.created.timestamp()
-        );
-        assert_eq!(
-            project.updated.timestamp(),
-            actual_project.updated.timestamp()
-        );
-        assert_eq!(0, actual_project.duration);
+    /// Saves the given rate to the database. Tags are created if they don't exist yet. A non-zero
+    /// ID is kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The rate to be saved
+    fn save_rate(&self, rate: &Rate) -> Result<u32> {
+        let (client_id, project_id, tag_id) = match &rate.scope {
+            RateScope::Client(id) => (Some(*id), None, None),
+            RateScope::Project(id) => (None, Some(*id), None),
+            RateScope::Tag(name) => (None, None, Some(self.tag_id(name)?)),
+        };
+
+        self.conn.execute(
+            "INSERT INTO rates (id, client_id, project_id, tag_id, amount, effective_from)
+            VALUES (NULLIF(?1, 0), ?2, ?3, ?4, ?5, ?6)",
+            params![
+                rate.id,
+                client_id,
+                project_id,
+                tag_id,
+                rate.amount.to_string(),
+                rate.effective_from.timestamp()
+            ],
+        )?;
+
+        Ok(self.conn.last_insert_rowid() as u32)
     }
 
-    #[test]
-    fn project_should_save_in_db_no_description() {
-        // Arrange
-        let name = "test2";
-        let created = Local::now();
-        let updated = Local::now();
+    /// Retrieve every rate, oldest first
+    fn all_rates(&self) -> Result<Vec<Rate>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.id, r.client_id, r.project_id, t.name, r.amount, r.effective_from
+            FROM rates r
+            LEFT JOIN tags t ON t.id = r.tag_id
+            ORDER BY r.effective_from, r.id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let scope = match (row.get(1)?, row.get(2)?, row.get(3)?) {
+                (Some(client_id), _, _) => RateScope::Client(client_id),
+                (_, Some(project_id), _) => RateScope::Project(project_id),
+                (_, _, Some(tag)) => RateScope::Tag(tag),
+                _ => {
+                    return Err(rusqlite::Error::InvalidColumnType(
+                        1,
+                        "scope".to_owned(),
+                        Type::Null,
+                    ))
+                }
+            };
+
+            Ok(Rate {
+                id: row.get(0)?,
+                scope,
+                amount: decimal_from_sql(row, 4)?,
+                effective_from: to_datetime(row.get(5)?),
+            })
+        })?;
+
+        rows.collect()
+    }
+
+    /// Delete a rate by ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the rate to delete
+    fn delete_rate(&self, id: &u32) -> Result<usize> {
+        self.conn.execute("DELETE FROM rates WHERE id = ?1", [id])
+    }
 
-        let project = Project::new(name.to_owned(), None, created, updated);
+    /// Saves a goal, creating its tag if needed. A non-zero ID is kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - The goal to be saved
+    fn save_goal(&self, goal: &Goal) -> Result<u32> {
+        let (project_id, tag_id) = match &goal.scope {
+            GoalScope::Overall => (None, None),
+            GoalScope::Project(id) => (Some(*id), None),
+            GoalScope::Tag(name) => (None, Some(self.tag_id(name)?)),
+        };
+        let period = match goal.period {
+            GoalPeriod::Day => "day",
+            GoalPeriod::Weekday => "weekday",
+            GoalPeriod::Week => "week",
+        };
 
-        let repo = test_repo();
+        self.conn.execute(
+            "INSERT INTO goals (id, project_id, tag_id, seconds, period)
+            VALUES (NULLIF(?1, 0), ?2, ?3, ?4, ?5)",
+            params![goal.id, project_id, tag_id, goal.seconds, period],
+        )?;
 
-        // Act
-        let id = repo.save_project(&project);
+        Ok(self.conn.last_insert_rowid() as u32)
+    }
 
-        // Assert
-        let (actual_project, _) = repo.get_project(&id.unwrap()).unwrap();
+    /// Retrieve every goal in the order they were set
+    fn all_goals(&self) -> Result<Vec<Goal>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT g.id, g.project_id, t.name, g.seconds, g.period
+            FROM goals g
+            LEFT JOIN tags t ON t.id = g.tag_id
+            ORDER BY g.id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let scope = match (row.get(1)?, row.get(2)?) {
+                (Some(project_id), _) => GoalScope::Project(project_id),
+                (_, Some(tag)) => GoalScope::Tag(tag),
+                _ => GoalScope::Overall,
+            };
+            let period = match row.get::<_, String>(4)?.as_str() {
+                "day" => GoalPeriod::Day,
+                "weekday" => GoalPeriod::Weekday,
+                "week" => GoalPeriod::Week,
+                _ => {
+                    return Err(rusqlite::Error::InvalidColumnType(
+                        4,
+                        "period".to_owned(),
+                        Type::Text,
+                    ))
+                }
+            };
+
+            Ok(Goal {
+                id: row.get(0)?,
+                scope,
+                seconds: row.get(3)?,
+                period,
+            })
+        })?;
 
-        assert_eq!(name, actual_project.name);
-        assert_eq!(None, actual_project.description);
-        assert_eq!(created.timestamp(), actual_project.created.timestamp());
-        assert_eq!(updated.timestamp(), actual_project.updated.timestamp());
+        rows.collect()
     }
 
-    #[test]
-    fn all_projects_should_return_all_projects() {
-        // Arrange
-        let repo = test_repo();
-
-        for n in 1..=2 {
-            let name = format!("test{}", n);
-            let created = Local::now();
-            let updated = Local::now();
-
-            let project = Project::new(
-                name.to_owned(),
-                Option::Some("hi".to_owned()),
-                created,
-                updated,
-            );
-            let _ = repo.save_project(&project);
+    /// Delete a goal by ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the goal to delete
+    fn delete_goal(&self, id: &u32) -> Result<usize> {
+        self.conn.execute("DELETE FROM goals WHERE id = ?1", [id])
+    }
+
+    /// Retrieve a log and its tags by ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the log to retrieve
+    fn get_log(&self, id: &u32) -> Result<Log> {
+        let log = self.conn.query_row(
+            "SELECT id, project_id, message, start, end, billable, invoice_id, zone
+            FROM logs
+            WHERE id = ?1",
+            [id],
+            log_from_row,
+        )?;
+
+        let mut logs = vec![log];
+        self.attach_tags(&mut logs)?;
+        Ok(logs.remove(0))
+    }
+
+    /// Overwrites the stored log with the same ID, including its tags. Invoiced logs are locked
+    /// and left untouched. The message and times are kept as a revision whenever they change.
+    /// The log and those after it are sealed again, unless they were changed without arrow.
+    /// Returns the number of logs updated
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - The log to save
+    fn update_log(&self, log: &Log) -> Result<usize> {
+        let tx = Scope::begin(&self.conn)?;
+        let intact = self.intact_logs(&log.id)?;
+
+        tx.execute(
+            "INSERT INTO log_revisions (log_id, changed, changed_by, message, start, end)
+            SELECT id, ?2, ?3, message, start, end
+            FROM logs
+            WHERE id = ?1
+                AND invoice_id IS NULL
+                AND (message IS NOT ?4 OR start IS NOT ?5 OR end IS NOT ?6)",
+            params![
+                log.id,
+                self.clock.now().timestamp(),
+                current_user(),
+                log.message,
+                log.start.timestamp_millis(),
+                log.end.timestamp_millis()
+            ],
+        )?;
+
+        let updated = tx.execute(
+            "UPDATE logs
+            SET message = ?1, start = ?2, end = ?3, project_id = ?4, billable = ?5, zone = ?6
+            WHERE id = ?7 AND invoice_id IS NULL",
+            params![
+                log.message,
+                log.start.timestamp_millis(),
+                log.end.timestamp_millis(),
+                log.proj_id,
+                log.billable,
+                log.zone.map(|zone| zone.to_string()),
+                log.id
+            ],
+        )?;
+
+        if updated > 0 {
+            tx.execute("DELETE FROM log_tags WHERE log_id = ?1", [log.id])?;
+            for tag in &log.tags {
+                tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [tag])?;
+                tx.execute(
+                    "INSERT OR IGNORE INTO log_tags (log_id, tag_id)
+                    SELECT ?1, id FROM tags WHERE name = ?2",
+                    params![log.id, tag],
+                )?;
+            }
+            self.seal(&log.id, &intact)?;
         }
 
-        // Act
-        let projects = repo.all_projects().unwrap();
+        tx.commit()?;
+        Ok(updated)
+    }
 
-        // Assert
-        assert_eq!(2, projects.len());
+    /// Saves an invoice with its lines and marks the given logs as billed on it. Either
+    /// everything is saved or nothing is. A non-zero ID is kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `invoice` - The invoice to be saved
+    /// * `log_ids` - IDs of the logs billed on the invoice. Fails if any are already invoiced
+    fn save_invoice(&self, invoice: &Invoice, log_ids: &[u32]) -> Result<u32> {
+        let tx = Scope::begin(&self.conn)?;
+
+        tx.execute(
+            "INSERT INTO invoices
+                (id, number, client_id, period_start, period_end, created, currency, total)
+            VALUES (NULLIF(?1, 0), ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                invoice.id,
+                invoice.number,
+                invoice.client_id,
+                invoice.period_start.timestamp(),
+                invoice.period_end.timestamp(),
+                invoice.created.timestamp(),
+                invoice.currency,
+                invoice.total.to_string()
+            ],
+        )?;
+        let invoice_id = tx.last_insert_rowid() as u32;
+
+        for line in &invoice.lines {
+            tx.execute(
+                "INSERT INTO invoice_lines
+                    (invoice_id, description, raw_seconds, seconds, rate, amount)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    invoice_id,
+                    line.description,
+                    line.raw_seconds,
+                    line.seconds,
+                    line.rate.to_string(),
+                    line.amount.to_string()
+                ],
+            )?;
+        }
+
+        for log_id in log_ids {
+            let updated = tx.execute(
+                "UPDATE logs SET invoice_id = ?1 WHERE id = ?2 AND invoice_id IS NULL",
+                [invoice_id, *log_id],
+            )?;
+            if updated == 0 {
+                // already invoiced or missing, dropping tx rolls everything back
+                return Err(rusqlite::Error::QueryReturnedNoRows);
+            }
+        }
+
+        tx.commit()?;
+        Ok(invoice_id)
     }
 
-    #[test]
-    fn save_log_should_save_in_db() {
-        // Arrange
-        let repo = test_repo();
+    /// Whether a log was imported from the source before
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Tracker the log came from, e.g. `timewarrior`
+    /// * `external_id` - Identifies the log within the source
+    fn is_imported(&self, source: &str, external_id: &str) -> Result<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM imports WHERE source = ?1 AND external_id = ?2)",
+            [source, external_id],
+            |row| row.get(0),
+        )
+    }
 
-        let created = Local::now();
-        let updated = Local::now();
-        let project = Project::new("project".to_owned(), None, created, updated);
+    /// Saves a log copied from another time tracker, unless it was imported before. Returns
+    /// `None` for logs that were already imported, so importing the same data twice is safe.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Tracker the log came from, e.g. `timewarrior`
+    /// * `external_id` - Identifies the log within the source
+    /// * `log` - The log to be saved
+    fn import_log(&self, source: &str, external_id: &str, log: &Log) -> Result<Option<u32>> {
+        let tx = Scope::begin(&self.conn)?;
 
-        let project_id = repo.save_project(&project).unwrap();
+        if self.is_imported(source, external_id)? {
+            return Ok(None);
+        }
 
-        let message = "code cleanup";
-        let log = Log::new(0, project_id, message.to_owned(), created, updated);
+        let log_id = self.save_log(&log.proj_id, log)?;
+        tx.execute(
+            "INSERT INTO imports (source, external_id, log_id) VALUES (?1, ?2, ?3)",
+            params![source, external_id, log_id],
+        )?;
 
-        // Act
-        repo.save_log(&project_id, &log).unwrap();
+        tx.commit()?;
+        Ok(Some(log_id))
+    }
 
-        // Assert
-        let (actual_project, actual_logs) = repo.get_project(&project_id).unwrap();
+    /// Retrieve every invoice and its lines, oldest first
+    fn all_invoices(&self) -> Result<Vec<Invoice>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, number, client_id, period_start, period_end, created, currency, total
+            FROM invoices
+            ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Invoice {
+                id: row.get(0)?,
+                number: row.get(1)?,
+                client_id: row.get(2)?,
+                period_start: to_datetime(row.get(3)?),
+                period_end: to_datetime(row.get(4)?),
+                created: to_datetime(row.get(5)?),
+                currency: row.get(6)?,
+                lines: Vec::new(),
+                total: decimal_from_sql(row, 7)?,
+            })
+        })?;
+        let mut invoices = rows.collect::<Result<Vec<Invoice>>>()?;
 
-        assert_eq!("project", actual_project.name);
-        assert_eq!(created.timestamp(), actual_project.created.timestamp());
-        assert_eq!(updated.timestamp(), actual_project.updated.timestamp());
+        let mut stmt = self.conn.prepare(
+            "SELECT invoice_id, description, seconds, rate, amount, raw_seconds
+            FROM invoice_lines
+            ORDER BY id",
+        )?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let invoice_id: u32 = row.get(0)?;
+            let line = InvoiceLine {
+                description: row.get(1)?,
+                raw_seconds: row.get(5)?,
+                seconds: row.get(2)?,
+                rate: decimal_from_sql(row, 3)?,
+                amount: decimal_from_sql(row, 4)?,
+            };
+
+            if let Some(invoice) = invoices.iter_mut().find(|i| i.id == invoice_id) {
+                invoice.lines.push(line);
+            }
+        }
 
-        assert_eq!(1, actual_logs.len());
-        assert_eq!(message, actual_logs[0].message);
-        assert_eq!(created.timestamp(), actual_logs[0].start.timestamp());
-        assert_eq!(updated.timestamp(), actual_logs[0].end.timestamp());
+        Ok(invoices)
     }
 
-    #[test]
-    fn save_log_no_project_should_fail() {
-        // Arrange
-        let repo = test_repo();
-        let start = Local::now();
-        let end = Local::now();
+    /// Retrieve the source, external ID and log ID of every imported log
+    fn all_imports(&self) -> Result<Vec<(String, String, u32)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT source, external_id, log_id FROM imports ORDER BY log_id")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+        rows.collect()
+    }
 
-        let message = "code cleanup";
-        let log = Log::new(0, 0, message.to_owned(), start, end);
+    /// Retrieve every log and its tags, in the order they were saved
+    fn all_logs(&self) -> Result<Vec<Log>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, project_id, message, start, end, billable, invoice_id, zone
+            FROM logs
+            ORDER BY id",
+        )?;
 
-        // Act
-        let res = repo.save_log(&0, &log);
+        let rows = stmt.query_map([], log_from_row)?;
+        let mut logs = rows.collect::<Result<Vec<Log>>>()?;
+        self.attach_tags(&mut logs)?;
 
-        // Assert
-        assert!(res.is_err());
+        Ok(logs)
     }
 
-    #[test]
-    fn delete_project_should_delete_project_from_db() {
-        // Arrange
+    /// Deletes everything in the database, leaving its tables empty
+    fn clear(&self) -> Result<()> {
+        let tx = Scope::begin(&self.conn)?;
+        tx.execute_batch(
+            "DELETE FROM imports;
+            DELETE FROM log_revisions;
+            DELETE FROM log_tags;
+            DELETE FROM invoice_lines;
+            DELETE FROM logs;
+            DELETE FROM invoices;
+            DELETE FROM goals;
+            DELETE FROM rates;
+            DELETE FROM tags;
+            DELETE FROM project_aliases;
+            DELETE FROM projects;
+            DELETE FROM clients;
+            DELETE FROM sqlite_sequence;",
+        )?;
 
-        let repo = test_repo();
-        let project = default_test_project();
+        tx.commit()
+    }
 
-        let project_id = repo.save_project(&project).unwrap();
+    /// Starts recording every change made to the database, until the operation is ended.
+    /// Returns the ID of the operation
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - Command line making the changes
+    fn begin_operation(&self, command: &str) -> Result<u32> {
+        self.conn.execute(
+            "INSERT INTO operations (command, created, state) VALUES (?1, ?2, 'recording')",
+            params![command, self.clock.now().timestamp()],
+        )?;
 
-        let message = "code cleanup";
-        let log = Log::new(
-            0,
-            project_id,
-            message.to_owned(),
-            project.created,
-            project.updated,
-        );
+        Ok(self.conn.last_insert_rowid() as u32)
+    }
 
-        repo.save_log(&project_id, &log).unwrap();
+    /// Stops recording changes. Operations that changed nothing are forgotten, otherwise the
+    /// operation can be undone and operations undone before it can no longer be redone
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the operation to end
+    fn end_operation(&self, id: &u32) -> Result<()> {
+        let tx = Scope::begin(&self.conn)?;
+
+        let changed: bool = tx.query_row(
+            "SELECT EXISTS (SELECT 1 FROM operation_changes WHERE operation_id = ?1)",
+            [id],
+            |row| row.get(0),
+        )?;
+        if changed {
+            tx.execute(
+                "DELETE FROM operation_changes
+                WHERE operation_id IN (SELECT id FROM operations WHERE state = 'undone')",
+                [],
+            )?;
+            tx.execute("DELETE FROM operations WHERE state = 'undone'", [])?;
+            tx.execute("UPDATE operations SET state = 'done' WHERE id = ?1", [id])?;
+        } else {
+            tx.execute("DELETE FROM operations WHERE id = ?1", [id])?;
+        }
 
-        // Act
-        repo.delete_project(&project_id);
+        tx.commit()
+    }
 
-        // confirm log + project is removed
-        // Assert
-        let res = repo.get_project(&project_id);
-        assert!(res.is_err());
+    /// Retrieve every operation in the journal, oldest first
+    fn all_operations(&self) -> Result<Vec<Operation>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT o.id, o.command, o.created, o.state, COUNT(c.id)
+            FROM operations o
+            LEFT JOIN operation_changes c ON c.operation_id = o.id
+            GROUP BY o.id
+            ORDER BY o.id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Operation {
+                id: row.get(0)?,
+                command: row.get(1)?,
+                created: to_datetime(row.get(2)?),
+                state: match row.get::<_, String>(3)?.as_str() {
+                    "done" => OperationState::Done,
+                    "undone" => OperationState::Undone,
+                    _ => OperationState::Recording,
+                },
+                changes: row.get(4)?,
+            })
+        })?;
+
+        rows.collect()
     }
 
-    #[test]
-    fn delete_log_should_delete_log_from_db() {
-        // Arrange
-        let repo = test_repo();
-        let project = default_test_project();
-
-        let project_id = repo.save_project(&project).unwrap();
-
-        let message = "code cleanup";
-        let log = Log::new(
-            0,
-            project_id,
-            message.to_owned(),
-            project.created,
-            project.updated,
-        );
+    /// Reverts the most recent operation that has not been undone yet. Returns `None` when there
+    /// is nothing left to undo
+    fn undo(&self) -> Result<Option<Operation>> {
+        let Some(operation) = self.operation(OperationState::Done, true)? else {
+            return Ok(None);
+        };
+
+        self.replay(&operation, true)?;
+        Ok(Some(operation))
+    }
 
-        let log_id = repo.save_log(&project_id, &log).unwrap();
+    /// Replays the operation undone last. Returns `None` when there is nothing to redo
+    fn redo(&self) -> Result<Option<Operation>> {
+        let Some(operation) = self.operation(OperationState::Undone, false)? else {
+            return Ok(None);
+        };
 
-        // Act
-        repo.delete_log(&project_id, &log_id);
+        self.replay(&operation, false)?;
+        Ok(Some(operation))
+    }
 
-        // Assert
-        let (_, logs) = repo.get_project(&project_id).unwrap();
-        assert_eq!(0, logs.len());
+    /// Retrieve the revisions of a log, oldest first. Each holds what the log said before a change
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the log
+    fn log_history(&self, id: &u32) -> Result<Vec<LogRevision>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, log_id, changed, changed_by, message, start, end
+            FROM log_revisions
+            WHERE log_id = ?1
+            ORDER BY id",
+        )?;
+        let rows = stmt.query_map([id], revision_from_row)?;
+
+        rows.collect()
+    }
+
+    /// Retrieve the revisions of every log, oldest first
+    fn all_log_revisions(&self) -> Result<Vec<LogRevision>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, log_id, changed, changed_by, message, start, end
+            FROM log_revisions
+            ORDER BY id",
+        )?;
+        let rows = stmt.query_map([], revision_from_row)?;
+
+        rows.collect()
+    }
+
+    /// Saves a revision recorded before, such as one restored from a backup. A non-zero ID is
+    /// kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `revision` - The revision to be saved
+    fn save_log_revision(&self, revision: &LogRevision) -> Result<u32> {
+        self.conn.execute(
+            "INSERT INTO log_revisions (id, log_id, changed, changed_by, message, start, end)
+            VALUES (NULLIF(?1, 0), ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                revision.id,
+                revision.log_id,
+                revision.changed.timestamp(),
+                revision.changed_by,
+                revision.message,
+                revision.start.timestamp_millis(),
+                revision.end.timestamp_millis()
+            ],
+        )?;
+
+        Ok(self.conn.last_insert_rowid() as u32)
+    }
+
+    /// Retrieve every log and its tags with the hash sealing it, in the order they were saved
+    fn log_chain(&self) -> Result<Vec<Sealed>> {
+        Ok(self.chain_from(&0)?.1)
+    }
+
+    /// Starts a transaction. Until it is committed or rolled back, changes are only seen through
+    /// this connection. Transactions started inside another are committed with it
+    fn begin_transaction(&self) -> Result<()> {
+        self.conn.execute_batch("SAVEPOINT arrow")
+    }
+
+    /// Keeps the changes made since the most recent transaction began
+    fn commit_transaction(&self) -> Result<()> {
+        self.conn.execute_batch("RELEASE arrow")
+    }
+
+    /// Undoes the changes made since the most recent transaction began, and ends it
+    fn rollback_transaction(&self) -> Result<()> {
+        self.conn.execute_batch("ROLLBACK TO arrow; RELEASE arrow")
+    }
+
+    /// Seals the logs saved before the hash chain was turned on, and those saved while it was
+    /// off. Logs that no longer match their hash are left alone. Returns the number of logs
+    /// sealed
+    fn seal_logs(&self) -> Result<usize> {
+        if !self.chained {
+            return Ok(0);
+        }
+
+        let tx = Scope::begin(&self.conn)?;
+        let mut logs = self.intact_logs(&0)?;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM logs WHERE hash IS NULL")?;
+        let unsealed = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<HashSet<u32>>>()?;
+        logs.extend(&unsealed);
+
+        self.seal(&0, &logs)?;
+        drop(stmt);
+        tx.commit()?;
+        Ok(unsealed.len())
+    }
+}
+
+/// Maps a row selected as `id, name, description, created, updated, duration, parent_id,
+/// client_id, budget_seconds, budget_period`
+fn project_from_row(row: &Row) -> Result<Project> {
+    let mut proj = Project::load(
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        to_datetime(row.get(3)?),
+        to_datetime(row.get(4)?),
+        Duration::milliseconds(row.get(5)?),
+    );
+    proj.parent_id = row.get(6)?;
+    proj.client_id = row.get(7)?;
+    proj.budget = match row.get::<_, Option<u32>>(8)? {
+        Some(seconds) => Some(Budget {
+            seconds,
+            period: match row.get::<_, Option<String>>(9)?.as_deref() {
+                Some("week") => Some(BudgetPeriod::Week),
+                Some("month") => Some(BudgetPeriod::Month),
+                _ => None,
+            },
+        }),
+        None => None,
+    };
+
+    Ok(proj)
+}
+
+fn period_to_sql(period: BudgetPeriod) -> &'static str {
+    match period {
+        BudgetPeriod::Week => "week",
+        BudgetPeriod::Month => "month",
+    }
+}
+
+/// Maps a row selected as `id, name, contact, currency, hourly_rate, address`
+fn client_from_row(row: &Row) -> Result<Client> {
+    let hourly_rate = match row.get::<_, Option<String>>(4)? {
+        Some(_) => Some(decimal_from_sql(row, 4)?),
+        None => None,
+    };
+
+    Ok(Client {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        contact: row.get(2)?,
+        currency: row.get(3)?,
+        hourly_rate,
+        address: row.get(5)?,
+    })
+}
+
+/// Maps a row selected as `id, project_id, message, start, end, billable, invoice_id, zone`. Tags
+/// are not loaded
+fn log_from_row(row: &Row) -> Result<Log> {
+    let mut log = Log::new(
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        from_millis(row.get(3)?),
+        from_millis(row.get(4)?),
+    );
+    log.billable = row.get(5)?;
+    log.invoice_id = row.get(6)?;
+    log.zone =
+        match row.get::<_, Option<String>>(7)? {
+            Some(zone) => Some(zone.parse().map_err(|_| {
+                rusqlite::Error::InvalidColumnType(7, "zone".to_owned(), Type::Text)
+            })?),
+            None => None,
+        };
+
+    Ok(log)
+}
+
+/// Maps a row selected as `id, log_id, changed, changed_by, message, start, end`
+fn revision_from_row(row: &Row) -> Result<LogRevision> {
+    Ok(LogRevision {
+        id: row.get(0)?,
+        log_id: row.get(1)?,
+        changed: to_datetime(row.get(2)?),
+        changed_by: row.get(3)?,
+        message: row.get(4)?,
+        start: from_millis(row.get(5)?),
+        end: from_millis(row.get(6)?),
+    })
+}
+
+/// Name of the user running arrow, recorded with changes to logs
+fn current_user() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_owned())
+}
+
+/// An error worded like SQLite's, for constraints checked outside of the schema, so both
+/// repositories fail the same way
+fn constraint(message: impl Into<String>) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        ffi::Error::new(ffi::SQLITE_CONSTRAINT),
+        Some(message.into()),
+    )
+}
+
+/// Reads a decimal stored as text
+fn decimal_from_sql(row: &Row, idx: usize) -> Result<Decimal> {
+    let text: String = row.get(idx)?;
+    text.parse::<Decimal>()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, Box::new(e)))
+}
+
+/// Creates triggers recording every insert, update and delete into the operation being recorded,
+/// with images of the row before and after. Triggers that no longer list every column of their
+/// table, because a migration changed it, are recreated
+fn install_journal(conn: &Connection) -> Result<()> {
+    // the search index is kept up to date by triggers of its own
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master
+        WHERE type = 'table'
+            AND name NOT LIKE 'sqlite_%'
+            AND name NOT IN ('refinery_schema_history', 'operations', 'operation_changes')
+            AND name NOT LIKE 'logs_search%'",
+    )?;
+    let tables = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<String>>>()?;
+
+    for table in tables {
+        let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1)")?;
+        let columns = stmt
+            .query_map([&table], |row| row.get(0))?
+            .collect::<Result<Vec<String>>>()?;
+        let image = |row: &str| {
+            let values: Vec<String> = columns
+                .iter()
+                .map(|c| format!("'{}', {}.\"{}\"", c, row, c))
+                .collect();
+            format!("json_object('rowid', {}.rowid, {})", row, values.join(", "))
+        };
+
+        for (event, old_row, new_row) in [
+            ("insert", "NULL".to_owned(), image("NEW")),
+            ("update", image("OLD"), image("NEW")),
+            ("delete", image("OLD"), "NULL".to_owned()),
+        ] {
+            let name = format!("journal_{}_{}", table, event);
+            let sql = format!(
+                "CREATE TRIGGER {} AFTER {} ON \"{}\"
+                BEGIN
+                    INSERT INTO operation_changes (operation_id, table_name, old_row, new_row)
+                    SELECT id, '{}', {}, {}
+                    FROM operations
+                    WHERE state = 'recording'
+                    ORDER BY id DESC
+                    LIMIT 1;
+                END",
+                name,
+                event.to_uppercase(),
+                table,
+                table,
+                old_row,
+                new_row
+            );
+
+            let current: Option<String> = conn
+                .query_row(
+                    "SELECT sql FROM sqlite_master WHERE type = 'trigger' AND name = ?1",
+                    [&name],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if current.as_deref() != Some(sql.as_str()) {
+                conn.execute(&format!("DROP TRIGGER IF EXISTS {}", name), [])?;
+                conn.execute(&sql, [])?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a row image recorded in the journal
+fn row_image(json: &str) -> Result<serde_json::Map<String, serde_json::Value>> {
+    serde_json::from_str(json).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+fn sql_value(value: &serde_json::Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value;
+
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+mod embedded {
+    use refinery::embed_migrations;
+    embed_migrations!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        conformance::{default_test_project, repository_tests},
+        *,
+    };
+
+    repository_tests!(|clock| Sqlite::in_memory().unwrap().with_clock(clock));
+
+    fn chained_test_repo() -> Sqlite {
+        let mut repo = Sqlite::in_memory().unwrap();
+        repo.chained = true;
+        repo
+    }
+
+    fn save_test_logs(repo: &Sqlite, count: i64) -> Vec<Log> {
+        let project_id = repo.save_project(&default_test_project()).unwrap();
+        (0..count)
+            .map(|i| {
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    format!("log {}", i),
+                    to_datetime(i * 100),
+                    to_datetime(i * 100 + 50),
+                );
+                log.id = repo.save_log(&project_id, &log).unwrap();
+                log
+            })
+            .collect()
     }
 
     #[test]
-    fn update_project_should_update_project_in_db() {
+    fn migrating_to_milliseconds_should_keep_times_and_durations() {
         // Arrange
-        let repo = test_repo();
-        let project = default_test_project();
-
-        let project_id = repo.save_project(&project).unwrap();
-
-        let updated_name = "updated";
-        let updated_desc = "updated desc";
-        let updated_project = Project::load(
-            project_id,
-            updated_name.to_owned(),
-            Option::Some(updated_desc.to_owned()),
-            Local::now(),
-            Local::now(),
-            0
-        );
+        let mut conn = Connection::open_in_memory().unwrap();
+        embedded::migrations::runner()
+            .set_target(refinery::Target::Version(15))
+            .run(&mut conn)
+            .unwrap();
+        conn.execute_batch(
+            "INSERT INTO projects (id, name, created, updated, duration) VALUES (1, 'web', 0, 0, 90);
+            INSERT INTO logs (id, message, start, end, project_id) VALUES (1, 'work', 1000, 1090, 1);
+            INSERT INTO operations (id, command, created, state) VALUES (1, 'log add', 0, 'done');
+            INSERT INTO operation_changes (operation_id, table_name, old_row, new_row)
+            VALUES (1, 'logs', NULL, '{\"id\":1,\"start\":1000,\"end\":1090}');",
+        )
+        .unwrap();
 
         // Act
-        let updated_rows = repo.update_project(&updated_project).unwrap();
+        embedded::migrations::runner().run(&mut conn).unwrap();
 
         // Assert
-        let (actual_project, _) = repo.get_project(&project_id).unwrap();
-
-        assert_eq!(1, updated_rows);
-        assert_eq!(updated_name, actual_project.name);
-        assert_eq!(updated_desc, actual_project.description.unwrap());
+        let image: String = conn
+            .query_row("SELECT new_row FROM operation_changes", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        let repo = Sqlite {
+            conn,
+            chained: false,
+            clock: Box::new(SystemClock),
+        };
+        let (project, logs) = repo.get_project(&1).unwrap();
+        assert_eq!(Duration::seconds(90), project.duration);
+        assert_eq!(1_000_000, logs[0].start.timestamp_millis());
+        assert_eq!(Duration::seconds(90), logs[0].duration());
+        assert_eq!(r#"{"id":1,"start":1000000,"end":1090000}"#, image);
     }
 
     #[test]
-    fn update_project_incorrect_id_should_fail() {
+    fn changes_made_by_arrow_should_keep_chain_intact() {
         // Arrange
-        let repo = test_repo();
-        let project = default_test_project();
-
-        let project_id = repo.save_project(&project).unwrap();
-
-        let updated_name = "updated";
-        let updated_desc = "updated desc";
-        let updated_project = Project::load(
-            project_id + 1,
-            updated_name.to_owned(),
-            Option::Some(updated_desc.to_owned()),
-            Local::now(),
-            Local::now(),
-            0
-        );
+        let repo = chained_test_repo();
+        let mut logs = save_test_logs(&repo, 4);
 
         // Act
-        let res = repo.update_project(&updated_project).unwrap();
+        logs[1].message = "changed".to_owned();
+        repo.update_log(&logs[1]).unwrap();
+        repo.delete_log(&logs[2].proj_id, &logs[2].id).unwrap();
 
         // Assert
-        assert_eq!(0, res);
+        let report = chain::verify(&repo.log_chain().unwrap());
+        assert_eq!((0, 3), (report.unsealed, report.sealed));
+        assert!(report.breaks.is_empty());
     }
 
     #[test]
-    fn get_project_by_name_should_return_project() {
+    fn changes_made_without_arrow_should_break_chain_at_changed_log() {
         // Arrange
-        let repo = test_repo();
-        let project = default_test_project();
-
-        let project_id = repo.save_project(&project).unwrap();
+        let repo = chained_test_repo();
+        let mut logs = save_test_logs(&repo, 4);
+        repo.conn
+            .execute(
+                "UPDATE logs SET end = end + 3600 WHERE id = ?1",
+                [logs[2].id],
+            )
+            .unwrap();
 
         // Act
-        let actual_project = repo.get_project_by_name(&project.name).unwrap();
+        logs[0].message = "changed".to_owned();
+        repo.update_log(&logs[0]).unwrap();
 
         // Assert
-        assert_eq!(project_id, actual_project.id);
+        let report = chain::verify(&repo.log_chain().unwrap());
+        assert_eq!(
+            vec![chain::Break {
+                log_id: logs[2].id,
+                problem: chain::Problem::Changed
+            }],
+            report.breaks
+        );
     }
 
     #[test]
-    fn get_project_by_name_no_project_found_should_return_error() {
+    fn seal_logs_should_seal_logs_saved_before_chain() {
         // Arrange
-        let repo = test_repo();
+        let mut repo = chained_test_repo();
+        repo.chained = false;
+        save_test_logs(&repo, 2);
+        repo.chained = true;
 
         // Act
-        let res = repo.get_project_by_name("not a project");
+        let res = repo.seal_logs().unwrap();
 
         // Assert
-        assert!(res.is_err());
+        let report = chain::verify(&repo.log_chain().unwrap());
+        assert_eq!(2, res);
+        assert_eq!((0, 2), (report.unsealed, report.sealed));
+        assert!(report.breaks.is_empty());
     }
 }
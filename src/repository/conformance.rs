@@ -0,0 +1,2061 @@
+use chrono::Local;
+use rust_decimal::Decimal;
+
+use crate::{
+    model::{Client, Invoice, InvoiceLine, Project},
+    time::to_datetime,
+};
+
+/// Tests every repository has to pass, whatever it stores projects and logs in. Invoke it in the
+/// tests of a repository with a closure creating an empty repository that tells the time by the
+/// given clock, e.g. `repository_tests!(|clock| Sqlite::in_memory().unwrap().with_clock(clock))`,
+/// and the tests are run against a new repository each
+macro_rules! repository_tests {
+    ($repo:expr) => {
+        mod conformance {
+            use chrono::{Duration, Local};
+            use rust_decimal::Decimal;
+
+            use super::*;
+            use $crate::{
+                clock::FixedClock,
+                log::search::{Matching, Pattern},
+                model::{
+                    Budget, BudgetPeriod, Goal, GoalPeriod, GoalScope, Log, LogRevision,
+                    OperationState, Project, Rate, RateScope,
+                },
+                repository::{
+                    atomically,
+                    conformance::{
+                        default_test_client, default_test_invoice, default_test_project,
+                    },
+                    Repository,
+                },
+                time::{parse_datetime, to_datetime},
+            };
+
+            fn clocked_repo(clock: FixedClock) -> impl Repository {
+                let new_repo = $repo;
+                new_repo(clock)
+            }
+
+            fn test_repo() -> impl Repository {
+                clocked_repo(FixedClock::at(to_datetime(1_000_000_000)))
+            }
+
+            #[test]
+            fn save_project_should_save_in_db() {
+                // Arrange
+                let repo = test_repo();
+                let project = default_test_project();
+
+                // Act
+                let id = repo.save_project(&project);
+
+                // Assert
+                let (actual_project, _) = repo.get_project(&id.unwrap()).unwrap();
+
+                assert_eq!("test", actual_project.name);
+                assert_eq!("hi", actual_project.description.unwrap());
+                assert_eq!(
+                    project.created.timestamp(),
+                    actual_project.created.timestamp()
+                );
+                assert_eq!(
+                    project.updated.timestamp(),
+                    actual_project.updated.timestamp()
+                );
+                assert_eq!(Duration::zero(), actual_project.duration);
+            }
+
+            #[test]
+            fn project_should_save_in_db_no_description() {
+                // Arrange
+                let name = "test2";
+                let created = Local::now();
+                let updated = Local::now();
+
+                let project = Project::new(name.to_owned(), None, created, updated);
+
+                let repo = test_repo();
+
+                // Act
+                let id = repo.save_project(&project);
+
+                // Assert
+                let (actual_project, _) = repo.get_project(&id.unwrap()).unwrap();
+
+                assert_eq!(name, actual_project.name);
+                assert_eq!(None, actual_project.description);
+                assert_eq!(created.timestamp(), actual_project.created.timestamp());
+                assert_eq!(updated.timestamp(), actual_project.updated.timestamp());
+            }
+
+            #[test]
+            fn all_projects_should_return_all_projects() {
+                // Arrange
+                let repo = test_repo();
+
+                for n in 1..=2 {
+                    let name = format!("test{}", n);
+                    let created = Local::now();
+                    let updated = Local::now();
+
+                    let project = Project::new(
+                        name.to_owned(),
+                        Option::Some("hi".to_owned()),
+                        created,
+                        updated,
+                    );
+                    let _ = repo.save_project(&project);
+                }
+
+                // Act
+                let projects = repo.all_projects().unwrap();
+
+                // Assert
+                assert_eq!(2, projects.len());
+            }
+
+            #[test]
+            fn save_log_should_save_in_db() {
+                // Arrange
+                let repo = test_repo();
+
+                let created = Local::now();
+                let updated = Local::now();
+                let project = Project::new("project".to_owned(), None, created, updated);
+
+                let project_id = repo.save_project(&project).unwrap();
+
+                let message = "code cleanup";
+                let log = Log::new(0, project_id, message.to_owned(), created, updated);
+
+                // Act
+                repo.save_log(&project_id, &log).unwrap();
+
+                // Assert
+                let (actual_project, actual_logs) = repo.get_project(&project_id).unwrap();
+
+                assert_eq!("project", actual_project.name);
+                assert_eq!(created.timestamp(), actual_project.created.timestamp());
+                assert_eq!(updated.timestamp(), actual_project.updated.timestamp());
+
+                assert_eq!(1, actual_logs.len());
+                assert_eq!(message, actual_logs[0].message);
+                assert_eq!(created.timestamp(), actual_logs[0].start.timestamp());
+                assert_eq!(updated.timestamp(), actual_logs[0].end.timestamp());
+            }
+
+            #[test]
+            fn save_log_no_project_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                let start = Local::now();
+                let end = Local::now();
+
+                let message = "code cleanup";
+                let log = Log::new(0, 0, message.to_owned(), start, end);
+
+                // Act
+                let res = repo.save_log(&0, &log);
+
+                // Assert
+                assert!(res.is_err());
+            }
+
+            #[test]
+            fn delete_project_should_delete_project_from_db() {
+                // Arrange
+
+                let repo = test_repo();
+                let project = default_test_project();
+
+                let project_id = repo.save_project(&project).unwrap();
+
+                let message = "code cleanup";
+                let log = Log::new(
+                    0,
+                    project_id,
+                    message.to_owned(),
+                    project.created,
+                    project.updated,
+                );
+
+                repo.save_log(&project_id, &log).unwrap();
+
+                // Act
+                let deleted = repo.delete_project(&project_id).unwrap();
+
+                // confirm log + project is removed
+                // Assert
+                let res = repo.get_project(&project_id);
+                assert!(res.is_err());
+                assert_eq!(1, deleted);
+            }
+
+            #[test]
+            fn delete_log_should_delete_log_from_db() {
+                // Arrange
+                let repo = test_repo();
+                let project = default_test_project();
+
+                let project_id = repo.save_project(&project).unwrap();
+
+                let message = "code cleanup";
+                let log = Log::new(
+                    0,
+                    project_id,
+                    message.to_owned(),
+                    project.created,
+                    project.updated,
+                );
+
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+
+                // Act
+                let deleted = repo.delete_log(&project_id, &log_id).unwrap();
+
+                // Assert
+                let (_, logs) = repo.get_project(&project_id).unwrap();
+                assert_eq!(0, logs.len());
+                assert_eq!(1, deleted);
+            }
+
+            #[test]
+            fn update_project_should_update_project_in_db() {
+                // Arrange
+                let repo = test_repo();
+                let project = default_test_project();
+
+                let project_id = repo.save_project(&project).unwrap();
+
+                let updated_name = "updated";
+                let updated_desc = "updated desc";
+                let updated_project = Project::load(
+                    project_id,
+                    updated_name.to_owned(),
+                    Option::Some(updated_desc.to_owned()),
+                    Local::now(),
+                    Local::now(),
+                    Duration::zero(),
+                );
+
+                // Act
+                let updated_rows = repo.update_project(&updated_project).unwrap();
+
+                // Assert
+                let (actual_project, _) = repo.get_project(&project_id).unwrap();
+
+                assert_eq!(1, updated_rows);
+                assert_eq!(updated_name, actual_project.name);
+                assert_eq!(updated_desc, actual_project.description.unwrap());
+            }
+
+            #[test]
+            fn update_project_incorrect_id_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                let project = default_test_project();
+
+                let project_id = repo.save_project(&project).unwrap();
+
+                let updated_name = "updated";
+                let updated_desc = "updated desc";
+                let updated_project = Project::load(
+                    project_id + 1,
+                    updated_name.to_owned(),
+                    Option::Some(updated_desc.to_owned()),
+                    Local::now(),
+                    Local::now(),
+                    Duration::zero(),
+                );
+
+                // Act
+                let res = repo.update_project(&updated_project).unwrap();
+
+                // Assert
+                assert_eq!(0, res);
+            }
+
+            #[test]
+            fn get_project_by_name_should_return_project() {
+                // Arrange
+                let repo = test_repo();
+                let project = default_test_project();
+
+                let project_id = repo.save_project(&project).unwrap();
+
+                // Act
+                let actual_project = repo.get_project_by_name(&project.name).unwrap();
+
+                // Assert
+                assert_eq!(project_id, actual_project.id);
+            }
+
+            #[test]
+            fn get_project_by_name_no_project_found_should_return_error() {
+                // Arrange
+                let repo = test_repo();
+
+                // Act
+                let res = repo.get_project_by_name("not a project");
+
+                // Assert
+                assert!(res.is_err());
+            }
+
+            #[test]
+            fn save_alias_should_be_returned_by_all_aliases() {
+                // Arrange
+                let repo = test_repo();
+                let project = default_test_project();
+
+                let project_id = repo.save_project(&project).unwrap();
+
+                // Act
+                repo.save_alias(&project_id, "t").unwrap();
+
+                // Assert
+                let aliases = repo.all_aliases().unwrap();
+                assert_eq!(vec![("t".to_owned(), project_id)], aliases);
+            }
+
+            #[test]
+            fn save_alias_duplicate_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                let project = default_test_project();
+
+                let project_id = repo.save_project(&project).unwrap();
+                repo.save_alias(&project_id, "t").unwrap();
+
+                // Act
+                let res = repo.save_alias(&project_id, "t");
+
+                // Assert
+                assert!(res.is_err());
+            }
+
+            #[test]
+            fn save_alias_no_project_should_fail() {
+                // Arrange
+                let repo = test_repo();
+
+                // Act
+                let res = repo.save_alias(&1, "t");
+
+                // Assert
+                assert!(res.is_err());
+            }
+
+            #[test]
+            fn save_project_with_parent_should_save_parent_id() {
+                // Arrange
+                let repo = test_repo();
+                let parent_id = repo.save_project(&default_test_project()).unwrap();
+
+                let mut child = Project::new("child".to_owned(), None, Local::now(), Local::now());
+                child.parent_id = Some(parent_id);
+
+                // Act
+                let child_id = repo.save_project(&child).unwrap();
+
+                // Assert
+                let (actual_child, _) = repo.get_project(&child_id).unwrap();
+                let (actual_parent, _) = repo.get_project(&parent_id).unwrap();
+
+                assert_eq!(Some(parent_id), actual_child.parent_id);
+                assert_eq!(None, actual_parent.parent_id);
+            }
+
+            #[test]
+            fn save_project_with_missing_parent_should_fail() {
+                // Arrange
+                let repo = test_repo();
+
+                let mut child = Project::new("child".to_owned(), None, Local::now(), Local::now());
+                child.parent_id = Some(42);
+
+                // Act
+                let res = repo.save_project(&child);
+
+                // Assert
+                assert!(res.is_err());
+            }
+
+            #[test]
+            fn update_project_with_budget_should_save_budget() {
+                // Arrange
+                let repo = test_repo();
+                let mut project = default_test_project();
+                project.budget = Some(Budget {
+                    seconds: 40 * 3600,
+                    period: None,
+                });
+                project.id = repo.save_project(&project).unwrap();
+
+                let recurring = Budget {
+                    seconds: 20 * 3600,
+                    period: Some(BudgetPeriod::Month),
+                };
+                project.budget = Some(recurring);
+
+                // Act
+                repo.update_project(&project).unwrap();
+
+                // Assert
+                let (actual, _) = repo.get_project(&project.id).unwrap();
+                assert_eq!(Some(recurring), actual.budget);
+            }
+
+            #[test]
+            fn logs_between_should_only_return_logs_started_in_range() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+
+                for (msg, start) in [
+                    ("before", 100),
+                    ("first", 200),
+                    ("second", 250),
+                    ("after", 300),
+                ] {
+                    let log = Log::new(
+                        0,
+                        project_id,
+                        msg.to_owned(),
+                        to_datetime(start),
+                        to_datetime(start + 10),
+                    );
+                    repo.save_log(&project_id, &log).unwrap();
+                }
+
+                // Act
+                let logs = repo
+                    .logs_between(&to_datetime(200), &to_datetime(300))
+                    .unwrap();
+
+                // Assert
+                let messages: Vec<&str> = logs.iter().map(|l| l.message.as_str()).collect();
+                assert_eq!(vec!["first", "second"], messages);
+                assert_eq!(project_id, logs[0].proj_id);
+            }
+
+            #[test]
+            fn save_client_should_save_in_db() {
+                // Arrange
+                let repo = test_repo();
+                let client = default_test_client();
+
+                // Act
+                let id = repo.save_client(&client).unwrap();
+
+                // Assert
+                let actual = repo.get_client(&id).unwrap();
+
+                assert_eq!("acme", actual.name);
+                assert_eq!(Some("Wile E. Coyote".to_owned()), actual.contact);
+                assert_eq!("AUD", actual.currency);
+                assert_eq!(Some(Decimal::new(8550, 2)), actual.hourly_rate);
+                assert_eq!(None, actual.address);
+            }
+
+            #[test]
+            fn save_client_duplicate_name_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                repo.save_client(&default_test_client()).unwrap();
+
+                // Act
+                let res = repo.save_client(&default_test_client());
+
+                // Assert
+                assert!(res.is_err());
+            }
+
+            #[test]
+            fn update_client_should_update_client_in_db() {
+                // Arrange
+                let repo = test_repo();
+                let id = repo.save_client(&default_test_client()).unwrap();
+
+                let mut client = repo.get_client(&id).unwrap();
+                client.hourly_rate = None;
+                client.address = Some("1 Desert Rd".to_owned());
+
+                // Act
+                let updated_rows = repo.update_client(&client).unwrap();
+
+                // Assert
+                let actual = repo.get_client(&id).unwrap();
+
+                assert_eq!(1, updated_rows);
+                assert_eq!(None, actual.hourly_rate);
+                assert_eq!(Some("1 Desert Rd".to_owned()), actual.address);
+            }
+
+            #[test]
+            fn delete_client_with_projects_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+
+                let mut project = default_test_project();
+                project.client_id = Some(client_id);
+                let project_id = repo.save_project(&project).unwrap();
+
+                // Act
+                let res = repo.delete_client(&client_id);
+
+                // Assert
+                let (actual_project, _) = repo.get_project(&project_id).unwrap();
+
+                assert!(res.is_err());
+                assert_eq!(Some(client_id), actual_project.client_id);
+            }
+
+            #[test]
+            fn delete_client_with_invoices_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+                repo.save_invoice(&default_test_invoice(client_id), &[])
+                    .unwrap();
+
+                // Act
+                let res = repo.delete_client(&client_id);
+
+                // Assert
+                assert!(res.is_err());
+                assert_eq!(1, repo.all_clients().unwrap().len());
+            }
+
+            #[test]
+            fn delete_client_should_delete_client_from_db() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+
+                // Act
+                let deleted = repo.delete_client(&client_id).unwrap();
+
+                // Assert
+                assert_eq!(1, deleted);
+                assert!(repo.all_clients().unwrap().is_empty());
+            }
+
+            #[test]
+            fn save_log_should_save_billable_and_tags() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "meeting".to_owned(),
+                    Local::now(),
+                    Local::now(),
+                );
+                log.billable = false;
+                log.tags = vec!["calls".to_owned(), "admin".to_owned(), "calls".to_owned()];
+
+                // Act
+                repo.save_log(&project_id, &log).unwrap();
+
+                // Assert
+                let (_, actual_logs) = repo.get_project(&project_id).unwrap();
+
+                assert!(!actual_logs[0].billable);
+                assert_eq!(
+                    vec!["calls".to_owned(), "admin".to_owned()],
+                    actual_logs[0].tags
+                );
+            }
+
+            #[test]
+            fn delete_log_with_tags_should_delete_log_from_db() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "meeting".to_owned(),
+                    Local::now(),
+                    Local::now(),
+                );
+                log.tags = vec!["calls".to_owned()];
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+
+                // Act
+                repo.delete_log(&project_id, &log_id).unwrap();
+
+                // Assert
+                let (_, logs) = repo.get_project(&project_id).unwrap();
+                assert_eq!(0, logs.len());
+            }
+
+            #[test]
+            fn import_log_twice_should_save_once() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "meeting".to_owned(),
+                    Local::now(),
+                    Local::now(),
+                );
+                log.tags = vec!["calls".to_owned()];
+
+                // Act
+                let first = repo
+                    .import_log("timewarrior", "20260101T090000Z", &log)
+                    .unwrap();
+                let second = repo
+                    .import_log("timewarrior", "20260101T090000Z", &log)
+                    .unwrap();
+
+                // Assert
+                let (_, logs) = repo.get_project(&project_id).unwrap();
+                assert!(first.is_some());
+                assert_eq!(None, second);
+                assert!(repo.is_imported("timewarrior", "20260101T090000Z").unwrap());
+                assert_eq!(1, logs.len());
+                assert_eq!(vec!["calls".to_owned()], logs[0].tags);
+            }
+
+            #[test]
+            fn save_rate_should_save_each_scope() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+
+                let scopes = [
+                    RateScope::Client(client_id),
+                    RateScope::Project(project_id),
+                    RateScope::Tag("review".to_owned()),
+                ];
+
+                // Act
+                for (i, scope) in scopes.iter().enumerate() {
+                    let rate = Rate::new(
+                        scope.clone(),
+                        Decimal::new(100 + i as i64, 0),
+                        to_datetime(i as i64),
+                    );
+                    repo.save_rate(&rate).unwrap();
+                }
+
+                // Assert
+                let rates = repo.all_rates().unwrap();
+                let actual: Vec<(RateScope, Decimal)> =
+                    rates.into_iter().map(|r| (r.scope, r.amount)).collect();
+
+                assert_eq!(
+                    vec![
+                        (scopes[0].clone(), Decimal::new(100, 0)),
+                        (scopes[1].clone(), Decimal::new(101, 0)),
+                        (scopes[2].clone(), Decimal::new(102, 0)),
+                    ],
+                    actual
+                );
+            }
+
+            #[test]
+            fn save_rate_missing_project_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                let rate = Rate::new(RateScope::Project(42), Decimal::ONE, Local::now());
+
+                // Act
+                let res = repo.save_rate(&rate);
+
+                // Assert
+                assert!(res.is_err());
+            }
+
+            #[test]
+            fn delete_rate_should_delete_rate_from_db() {
+                // Arrange
+                let repo = test_repo();
+                let rate = Rate::new(
+                    RateScope::Tag("review".to_owned()),
+                    Decimal::ONE,
+                    Local::now(),
+                );
+                let rate_id = repo.save_rate(&rate).unwrap();
+
+                // Act
+                let deleted = repo.delete_rate(&rate_id).unwrap();
+
+                // Assert
+                assert_eq!(1, deleted);
+                assert!(repo.all_rates().unwrap().is_empty());
+            }
+
+            #[test]
+            fn save_goal_should_save_each_scope() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+
+                let scopes = [
+                    GoalScope::Overall,
+                    GoalScope::Project(project_id),
+                    GoalScope::Tag("focus".to_owned()),
+                ];
+
+                // Act
+                for scope in &scopes {
+                    repo.save_goal(&Goal::new(scope.clone(), 3600, GoalPeriod::Weekday))
+                        .unwrap();
+                }
+
+                // Assert
+                let goals = repo.all_goals().unwrap();
+                let actual: Vec<(GoalScope, u32, GoalPeriod)> = goals
+                    .into_iter()
+                    .map(|g| (g.scope, g.seconds, g.period))
+                    .collect();
+
+                assert_eq!(
+                    scopes
+                        .iter()
+                        .map(|s| (s.clone(), 3600, GoalPeriod::Weekday))
+                        .collect::<Vec<_>>(),
+                    actual
+                );
+            }
+
+            #[test]
+            fn delete_goal_should_delete_goal_from_db() {
+                // Arrange
+                let repo = test_repo();
+                let goal_id = repo
+                    .save_goal(&Goal::new(GoalScope::Overall, 60, GoalPeriod::Day))
+                    .unwrap();
+
+                // Act
+                let deleted = repo.delete_goal(&goal_id).unwrap();
+
+                // Assert
+                assert_eq!(1, deleted);
+                assert!(repo.all_goals().unwrap().is_empty());
+            }
+
+            #[test]
+            fn update_log_should_update_log_in_db() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "typo".to_owned(),
+                    to_datetime(0),
+                    to_datetime(10),
+                );
+                log.tags = vec!["old".to_owned()];
+                log.id = repo.save_log(&project_id, &log).unwrap();
+
+                log.message = "fixed".to_owned();
+                log.end = to_datetime(20);
+                log.billable = false;
+                log.tags = vec!["new".to_owned()];
+
+                // Act
+                let updated = repo.update_log(&log).unwrap();
+
+                // Assert
+                let actual = repo.get_log(&log.id).unwrap();
+
+                assert_eq!(1, updated);
+                assert_eq!("fixed", actual.message);
+                assert_eq!(20, actual.end.timestamp());
+                assert!(!actual.billable);
+                assert_eq!(vec!["new".to_owned()], actual.tags);
+            }
+
+            #[test]
+            fn save_invoice_should_mark_logs_invoiced() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(0),
+                    to_datetime(3600),
+                );
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+
+                // Act
+                let invoice_id = repo
+                    .save_invoice(&default_test_invoice(client_id), &[log_id])
+                    .unwrap();
+
+                // Assert
+                let invoices = repo.all_invoices().unwrap();
+                let actual_log = repo.get_log(&log_id).unwrap();
+
+                assert_eq!(1, invoices.len());
+                assert_eq!("INV-0001", invoices[0].number);
+                assert_eq!(1, invoices[0].lines.len());
+                assert_eq!(Decimal::new(8550, 2), invoices[0].lines[0].amount);
+                assert_eq!(
+                    (3500, 3600),
+                    (
+                        invoices[0].lines[0].raw_seconds,
+                        invoices[0].lines[0].seconds
+                    )
+                );
+                assert_eq!(Some(invoice_id), actual_log.invoice_id);
+            }
+
+            #[test]
+            fn save_invoice_with_invoiced_log_should_save_nothing() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(0),
+                    to_datetime(3600),
+                );
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+                repo.save_invoice(&default_test_invoice(client_id), &[log_id])
+                    .unwrap();
+
+                let mut second = default_test_invoice(client_id);
+                second.number = "INV-0002".to_owned();
+
+                // Act
+                let res = repo.save_invoice(&second, &[log_id]);
+
+                // Assert
+                assert!(res.is_err());
+                assert_eq!(1, repo.all_invoices().unwrap().len());
+            }
+
+            #[test]
+            fn invoiced_log_should_be_locked() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(0),
+                    to_datetime(3600),
+                );
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+                repo.save_invoice(&default_test_invoice(client_id), &[log_id])
+                    .unwrap();
+
+                let mut edited = repo.get_log(&log_id).unwrap();
+                edited.message = "changed".to_owned();
+
+                // Act
+                let updated = repo.update_log(&edited).unwrap();
+                repo.delete_log(&project_id, &log_id).unwrap();
+
+                // Assert
+                let actual = repo.get_log(&log_id).unwrap();
+
+                assert_eq!(0, updated);
+                assert_eq!("work", actual.message);
+            }
+
+            #[test]
+            fn undo_then_redo_should_revert_and_replay_operation() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let id = repo
+                    .begin_operation("project edit test -n renamed")
+                    .unwrap();
+                let (mut project, _) = repo.get_project(&project_id).unwrap();
+                project.name = "renamed".to_owned();
+                repo.update_project(&project).unwrap();
+                repo.end_operation(&id).unwrap();
+
+                // Act
+                let undone = repo.undo().unwrap().unwrap();
+                let (after_undo, _) = repo.get_project(&project_id).unwrap();
+                let redone = repo.redo().unwrap().unwrap();
+                let (after_redo, _) = repo.get_project(&project_id).unwrap();
+
+                // Assert
+                assert_eq!((id, 1), (undone.id, undone.changes));
+                assert_eq!("test", after_undo.name);
+                assert_eq!(id, redone.id);
+                assert_eq!("renamed", after_redo.name);
+                assert!(repo.redo().unwrap().is_none());
+            }
+
+            #[test]
+            fn end_operation_should_forget_operations_without_changes() {
+                // Arrange
+                let repo = test_repo();
+                let id = repo.begin_operation("project ls").unwrap();
+
+                // Act
+                repo.end_operation(&id).unwrap();
+
+                // Assert
+                assert!(repo.all_operations().unwrap().is_empty());
+                assert!(repo.undo().unwrap().is_none());
+            }
+
+            #[test]
+            fn update_log_should_keep_previous_message_and_times() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "wrok".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                log.id = repo.save_log(&project_id, &log).unwrap();
+
+                // Act
+                log.tags = vec!["review".to_owned()];
+                repo.update_log(&log).unwrap();
+                log.message = "work".to_owned();
+                log.end = to_datetime(300);
+                repo.update_log(&log).unwrap();
+
+                // Assert
+                let history = repo.log_history(&log.id).unwrap();
+                assert_eq!(1, history.len());
+                assert_eq!("wrok", history[0].message);
+                assert_eq!(200, history[0].end.timestamp());
+                assert!(repo.get_log(&log.id).unwrap().edited);
+            }
+
+            #[test]
+            fn atomically_should_roll_back_every_step_when_one_fails() {
+                // Arrange
+                let repo = test_repo();
+                let log = Log::new(0, 0, "work".to_owned(), to_datetime(100), to_datetime(200));
+
+                // Act
+                let res = atomically(&repo, || {
+                    let project_id = repo.save_project(&default_test_project())?;
+                    repo.save_log(&project_id, &log)?;
+                    repo.save_log(&(project_id + 1), &log)
+                });
+
+                // Assert
+                assert!(res.is_err());
+                assert!(repo.all_projects().unwrap().is_empty());
+                assert!(repo.all_logs().unwrap().is_empty());
+            }
+
+            #[test]
+            fn nested_transactions_should_commit_with_outer_transaction() {
+                // Arrange
+                let repo = test_repo();
+                repo.begin_transaction().unwrap();
+                repo.save_project(&default_test_project()).unwrap();
+
+                // Act
+                atomically(&repo, || repo.save_client(&default_test_client())).unwrap();
+                repo.rollback_transaction().unwrap();
+
+                // Assert
+                assert!(repo.all_projects().unwrap().is_empty());
+                assert!(repo.all_clients().unwrap().is_empty());
+            }
+
+            #[test]
+            fn save_project_with_empty_name_and_description_should_save_them() {
+                // Arrange
+                let repo = test_repo();
+                let project = Project::new(
+                    String::new(),
+                    Some(String::new()),
+                    Local::now(),
+                    Local::now(),
+                );
+
+                // Act
+                let id = repo.save_project(&project).unwrap();
+
+                // Assert
+                let (actual_project, _) = repo.get_project(&id).unwrap();
+                assert_eq!("", actual_project.name);
+                assert_eq!(Some(String::new()), actual_project.description);
+            }
+
+            #[test]
+            fn save_project_with_unicode_should_read_back_unchanged() {
+                // Arrange
+                let repo = test_repo();
+                let name = "Café ☕ 日本語";
+                let description = "naïve\tplan\nwith 🚀 and \\N";
+                let project = Project::new(
+                    name.to_owned(),
+                    Some(description.to_owned()),
+                    Local::now(),
+                    Local::now(),
+                );
+
+                // Act
+                let id = repo.save_project(&project).unwrap();
+
+                // Assert
+                let (actual_project, _) = repo.get_project(&id).unwrap();
+                assert_eq!(name, actual_project.name);
+                assert_eq!(Some(description.to_owned()), actual_project.description);
+                assert_eq!(id, repo.get_project_by_name(name).unwrap().id);
+            }
+
+            #[test]
+            fn save_project_duplicate_name_should_save_both() {
+                // Arrange
+                let repo = test_repo();
+
+                // Act
+                let first = repo.save_project(&default_test_project()).unwrap();
+                let second = repo.save_project(&default_test_project()).unwrap();
+
+                // Assert
+                assert_ne!(first, second);
+                assert_eq!(2, repo.all_projects().unwrap().len());
+            }
+
+            #[test]
+            fn get_project_no_project_found_should_return_error() {
+                // Arrange
+                let repo = test_repo();
+
+                // Act
+                let res = repo.get_project(&1);
+
+                // Assert
+                assert!(matches!(res, Err(rusqlite::Error::QueryReturnedNoRows)));
+            }
+
+            #[test]
+            fn update_project_with_huge_duration_and_budget_should_save_them() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let (mut project, _) = repo.get_project(&project_id).unwrap();
+                project.duration = Duration::milliseconds(i64::MAX);
+                project.budget = Some(Budget {
+                    seconds: u32::MAX,
+                    period: Some(BudgetPeriod::Month),
+                });
+
+                // Act
+                let updated = repo.update_project(&project).unwrap();
+
+                // Assert
+                let (actual_project, _) = repo.get_project(&project_id).unwrap();
+                assert_eq!(1, updated);
+                assert_eq!(project.duration, actual_project.duration);
+                assert_eq!(project.budget, actual_project.budget);
+            }
+
+            #[test]
+            fn delete_project_should_delete_its_aliases_rates_and_goals() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let other_id = repo.save_project(&default_test_project()).unwrap();
+                repo.save_alias(&project_id, "t").unwrap();
+                repo.save_alias(&other_id, "o").unwrap();
+                repo.save_rate(&Rate::new(
+                    RateScope::Project(project_id),
+                    Decimal::new(100, 0),
+                    to_datetime(0),
+                ))
+                .unwrap();
+                repo.save_goal(&Goal::new(
+                    GoalScope::Project(project_id),
+                    3600,
+                    GoalPeriod::Week,
+                ))
+                .unwrap();
+
+                // Act
+                repo.delete_project(&project_id).unwrap();
+
+                // Assert
+                assert_eq!(
+                    vec![("o".to_owned(), other_id)],
+                    repo.all_aliases().unwrap()
+                );
+                assert!(repo.all_rates().unwrap().is_empty());
+                assert!(repo.all_goals().unwrap().is_empty());
+            }
+
+            #[test]
+            fn save_log_with_empty_message_should_save_in_db() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let log = Log::new(
+                    0,
+                    project_id,
+                    String::new(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+
+                // Act
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+
+                // Assert
+                assert_eq!("", repo.get_log(&log_id).unwrap().message);
+            }
+
+            #[test]
+            fn save_log_with_unicode_message_and_tags_should_read_back_unchanged() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "Réunion — 会議 ✅\nsecond line".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                log.tags = vec![
+                    "überprüfung".to_owned(),
+                    "日本".to_owned(),
+                    "a,b".to_owned(),
+                ];
+
+                // Act
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+
+                // Assert
+                let actual_log = repo.get_log(&log_id).unwrap();
+                assert_eq!(log.message, actual_log.message);
+                assert_eq!(log.tags, actual_log.tags);
+            }
+
+            #[test]
+            fn save_log_spanning_decades_should_keep_its_duration() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "long haul".to_owned(),
+                    to_datetime(0),
+                    to_datetime(4_000_000_000),
+                );
+
+                // Act
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+
+                // Assert
+                let actual_log = repo.get_log(&log_id).unwrap();
+                assert_eq!(0, actual_log.start.timestamp());
+                assert_eq!(4_000_000_000, actual_log.end.timestamp());
+            }
+
+            #[test]
+            fn get_log_no_log_found_should_return_error() {
+                // Arrange
+                let repo = test_repo();
+
+                // Act
+                let res = repo.get_log(&1);
+
+                // Assert
+                assert!(matches!(res, Err(rusqlite::Error::QueryReturnedNoRows)));
+            }
+
+            #[test]
+            fn update_log_incorrect_id_should_update_nothing() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let log = Log::new(
+                    7,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+
+                // Act
+                let updated = repo.update_log(&log).unwrap();
+
+                // Assert
+                assert_eq!(0, updated);
+                assert!(repo.all_logs().unwrap().is_empty());
+            }
+
+            #[test]
+            fn delete_log_of_other_project_should_keep_log() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let other_id = repo.save_project(&default_test_project()).unwrap();
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+
+                // Act
+                let deleted = repo.delete_log(&other_id, &log_id).unwrap();
+
+                // Assert
+                assert_eq!(0, deleted);
+                assert!(repo.get_log(&log_id).is_ok());
+            }
+
+            #[test]
+            fn search_logs_in_project_should_match_part_of_message_ignoring_case() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let other_id = repo.save_project(&default_test_project()).unwrap();
+                for (proj_id, msg, start) in [
+                    (project_id, "Fix login bug", 100),
+                    (project_id, "write docs", 200),
+                    (project_id, "review LOGIN page", 300),
+                    (other_id, "login for other project", 400),
+                ] {
+                    let log = Log::new(
+                        0,
+                        proj_id,
+                        msg.to_owned(),
+                        to_datetime(start),
+                        to_datetime(start + 50),
+                    );
+                    repo.save_log(&proj_id, &log).unwrap();
+                }
+
+                // Act
+                let logs = repo
+                    .search_logs(
+                        Some(project_id),
+                        &Pattern::new("Login", Matching::Text).unwrap(),
+                    )
+                    .unwrap();
+
+                // Assert
+                let messages: Vec<&str> = logs.iter().map(|l| l.message.as_str()).collect();
+                assert_eq!(vec!["Fix login bug", "review LOGIN page"], messages);
+                assert!(logs.iter().all(|l| l.proj_id == project_id));
+            }
+
+            #[test]
+            fn all_logs_should_return_logs_of_every_project() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let other_id = repo.save_project(&default_test_project()).unwrap();
+                let mut ids = Vec::new();
+                for proj_id in [project_id, other_id, project_id] {
+                    let log = Log::new(
+                        0,
+                        proj_id,
+                        "work".to_owned(),
+                        to_datetime(100),
+                        to_datetime(200),
+                    );
+                    ids.push(repo.save_log(&proj_id, &log).unwrap());
+                }
+
+                // Act
+                let logs = repo.all_logs().unwrap();
+
+                // Assert
+                assert_eq!(ids, logs.iter().map(|l| l.id).collect::<Vec<u32>>());
+                assert_eq!(
+                    vec![project_id, other_id, project_id],
+                    logs.iter().map(|l| l.proj_id).collect::<Vec<u32>>()
+                );
+            }
+
+            #[test]
+            fn get_client_no_client_found_should_return_error() {
+                // Arrange
+                let repo = test_repo();
+
+                // Act
+                let res = repo.get_client(&1);
+
+                // Assert
+                assert!(matches!(res, Err(rusqlite::Error::QueryReturnedNoRows)));
+            }
+
+            #[test]
+            fn update_client_duplicate_name_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                repo.save_client(&default_test_client()).unwrap();
+                let mut other = default_test_client();
+                other.name = "globex".to_owned();
+                other.id = repo.save_client(&other).unwrap();
+
+                // Act
+                other.name = "acme".to_owned();
+                let res = repo.update_client(&other);
+
+                // Assert
+                assert!(res.is_err());
+                assert_eq!("globex", repo.get_client(&other.id).unwrap().name);
+            }
+
+            #[test]
+            fn update_client_incorrect_id_should_update_nothing() {
+                // Arrange
+                let repo = test_repo();
+                let mut client = default_test_client();
+                client.id = 1;
+
+                // Act
+                let updated = repo.update_client(&client).unwrap();
+
+                // Assert
+                assert_eq!(0, updated);
+                assert!(repo.all_clients().unwrap().is_empty());
+            }
+
+            #[test]
+            fn all_rates_should_be_ordered_by_effective_from() {
+                // Arrange
+                let repo = test_repo();
+                for effective_from in [300, 100, 200] {
+                    repo.save_rate(&Rate::new(
+                        RateScope::Tag("calls".to_owned()),
+                        Decimal::new(effective_from, 0),
+                        to_datetime(effective_from),
+                    ))
+                    .unwrap();
+                }
+
+                // Act
+                let rates = repo.all_rates().unwrap();
+
+                // Assert
+                assert_eq!(
+                    vec![100, 200, 300],
+                    rates
+                        .iter()
+                        .map(|r| r.effective_from.timestamp())
+                        .collect::<Vec<i64>>()
+                );
+            }
+
+            #[test]
+            fn save_goal_with_huge_seconds_should_save_in_db() {
+                // Arrange
+                let repo = test_repo();
+
+                // Act
+                repo.save_goal(&Goal::new(GoalScope::Overall, u32::MAX, GoalPeriod::Day))
+                    .unwrap();
+
+                // Assert
+                assert_eq!(u32::MAX, repo.all_goals().unwrap()[0].seconds);
+            }
+
+            #[test]
+            fn save_invoice_should_be_returned_by_all_invoices() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+                let invoice = default_test_invoice(client_id);
+
+                // Act
+                let invoice_id = repo.save_invoice(&invoice, &[]).unwrap();
+
+                // Assert
+                let invoices = repo.all_invoices().unwrap();
+                assert_eq!(1, invoices.len());
+                assert_eq!(invoice_id, invoices[0].id);
+                assert_eq!("INV-0001", invoices[0].number);
+                assert_eq!(invoice.total, invoices[0].total);
+                assert_eq!(1, invoices[0].lines.len());
+                assert_eq!(3500, invoices[0].lines[0].raw_seconds);
+                assert_eq!(3600, invoices[0].lines[0].seconds);
+            }
+
+            #[test]
+            fn save_invoice_duplicate_number_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+                repo.save_invoice(&default_test_invoice(client_id), &[])
+                    .unwrap();
+
+                // Act
+                let res = repo.save_invoice(&default_test_invoice(client_id), &[]);
+
+                // Assert
+                assert!(res.is_err());
+                assert_eq!(1, repo.all_invoices().unwrap().len());
+            }
+
+            #[test]
+            fn all_imports_should_return_source_and_external_id_of_each_log() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+
+                // Act
+                let log_id = repo.import_log("toggl", "42", &log).unwrap().unwrap();
+
+                // Assert
+                assert_eq!(
+                    vec![("toggl".to_owned(), "42".to_owned(), log_id)],
+                    repo.all_imports().unwrap()
+                );
+                assert!(!repo.is_imported("toggl", "43").unwrap());
+                assert!(!repo.is_imported("clockify", "42").unwrap());
+            }
+
+            #[test]
+            fn clear_should_delete_everything() {
+                // Arrange
+                let repo = test_repo();
+                let client_id = repo.save_client(&default_test_client()).unwrap();
+                let mut project = default_test_project();
+                project.client_id = Some(client_id);
+                let project_id = repo.save_project(&project).unwrap();
+                repo.save_alias(&project_id, "t").unwrap();
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                log.tags = vec!["calls".to_owned()];
+                let log_id = repo.import_log("toggl", "42", &log).unwrap().unwrap();
+                repo.save_invoice(&default_test_invoice(client_id), &[log_id])
+                    .unwrap();
+                repo.save_goal(&Goal::new(GoalScope::Overall, 3600, GoalPeriod::Day))
+                    .unwrap();
+
+                // Act
+                repo.clear().unwrap();
+
+                // Assert
+                assert!(repo.all_projects().unwrap().is_empty());
+                assert!(repo.all_aliases().unwrap().is_empty());
+                assert!(repo.all_clients().unwrap().is_empty());
+                assert!(repo.all_logs().unwrap().is_empty());
+                assert!(repo.all_imports().unwrap().is_empty());
+                assert!(repo.all_invoices().unwrap().is_empty());
+                assert!(repo.all_goals().unwrap().is_empty());
+                assert_eq!(1, repo.save_project(&default_test_project()).unwrap());
+            }
+
+            #[test]
+            fn undo_should_restore_deleted_log_with_its_tags() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                log.tags = vec!["calls".to_owned(), "focus".to_owned()];
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+                let id = repo.begin_operation("log remove 1").unwrap();
+                repo.delete_log(&project_id, &log_id).unwrap();
+                repo.end_operation(&id).unwrap();
+
+                // Act
+                let undone = repo.undo().unwrap();
+
+                // Assert
+                let restored = repo.get_log(&log_id).unwrap();
+                assert_eq!(Some(id), undone.map(|o| o.id));
+                assert_eq!("work", restored.message);
+                assert_eq!(log.tags, restored.tags);
+                assert_eq!(
+                    OperationState::Undone,
+                    repo.all_operations().unwrap()[0].state
+                );
+            }
+
+            #[test]
+            fn undo_with_nothing_recorded_should_do_nothing() {
+                // Arrange
+                let repo = test_repo();
+                repo.save_project(&default_test_project()).unwrap();
+
+                // Act
+                let undone = repo.undo().unwrap();
+
+                // Assert
+                assert!(undone.is_none());
+                assert_eq!(1, repo.all_projects().unwrap().len());
+            }
+
+            #[test]
+            fn save_log_revision_should_be_returned_by_all_log_revisions() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+                let revision = LogRevision {
+                    id: 0,
+                    log_id,
+                    changed: to_datetime(300),
+                    changed_by: "José".to_owned(),
+                    message: "wrok".to_owned(),
+                    start: to_datetime(100),
+                    end: to_datetime(150),
+                };
+
+                // Act
+                let revision_id = repo.save_log_revision(&revision).unwrap();
+
+                // Assert
+                let revisions = repo.all_log_revisions().unwrap();
+                assert_eq!(1, revisions.len());
+                assert_eq!(revision_id, revisions[0].id);
+                assert_eq!("José", revisions[0].changed_by);
+                assert_eq!("wrok", revisions[0].message);
+                assert_eq!(150, revisions[0].end.timestamp());
+                assert_eq!(1, repo.log_history(&log_id).unwrap().len());
+                assert!(repo.get_log(&log_id).unwrap().edited);
+            }
+
+            #[test]
+            fn seal_logs_without_chain_should_seal_nothing() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "work".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                repo.save_log(&project_id, &log).unwrap();
+
+                // Act
+                let sealed = repo.seal_logs().unwrap();
+
+                // Assert
+                let chain = repo.log_chain().unwrap();
+                assert_eq!(0, sealed);
+                assert_eq!(1, chain.len());
+                assert_eq!(None, chain[0].1);
+            }
+
+            #[test]
+            fn rollback_transaction_should_undo_changes_since_begin() {
+                // Arrange
+                let repo = test_repo();
+                repo.save_project(&default_test_project()).unwrap();
+
+                // Act
+                repo.begin_transaction().unwrap();
+                repo.save_client(&default_test_client()).unwrap();
+                repo.rollback_transaction().unwrap();
+
+                // Assert
+                assert_eq!(1, repo.all_projects().unwrap().len());
+                assert!(repo.all_clients().unwrap().is_empty());
+            }
+
+            #[test]
+            fn commit_transaction_without_transaction_should_fail() {
+                // Arrange
+                let repo = test_repo();
+
+                // Act
+                let res = repo.commit_transaction();
+
+                // Assert
+                assert!(res.is_err());
+            }
+
+            #[test]
+            fn update_project_should_be_dated_by_clock() {
+                // Arrange
+                let clock = FixedClock::at(to_datetime(1_000_000_000));
+                let repo = clocked_repo(clock.clone());
+                let project =
+                    Project::new("test".to_owned(), None, to_datetime(100), to_datetime(100));
+                let project_id = repo.save_project(&project).unwrap();
+                let (project, _) = repo.get_project(&project_id).unwrap();
+
+                // Act
+                clock.advance(Duration::days(1));
+                repo.update_project(&project).unwrap();
+
+                // Assert
+                let (actual_project, _) = repo.get_project(&project_id).unwrap();
+                assert_eq!(100, actual_project.created.timestamp());
+                assert_eq!(1_000_086_400, actual_project.updated.timestamp());
+            }
+
+            #[test]
+            fn begin_operation_should_be_dated_by_clock() {
+                // Arrange
+                let clock = FixedClock::at(to_datetime(1_000_000_000));
+                let repo = clocked_repo(clock.clone());
+
+                // Act
+                let id = repo.begin_operation("project new test").unwrap();
+                repo.save_project(&default_test_project()).unwrap();
+                clock.advance(Duration::minutes(5));
+                repo.end_operation(&id).unwrap();
+
+                // Assert
+                let operations = repo.all_operations().unwrap();
+                assert_eq!(1_000_000_000, operations[0].created.timestamp());
+            }
+
+            #[test]
+            fn update_log_should_date_revision_by_clock() {
+                // Arrange
+                let clock = FixedClock::at(to_datetime(1_000_000_000));
+                let repo = clocked_repo(clock.clone());
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "wrok".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                log.id = repo.save_log(&project_id, &log).unwrap();
+
+                // Act
+                clock.set(to_datetime(2_000_000_000));
+                log.message = "work".to_owned();
+                repo.update_log(&log).unwrap();
+
+                // Assert
+                let history = repo.log_history(&log.id).unwrap();
+                assert_eq!(2_000_000_000, history[0].changed.timestamp());
+            }
+
+            #[test]
+            fn logs_between_should_include_log_started_before_midnight_in_the_day_it_started() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "late night".to_owned(),
+                    parse_datetime("2026-10-19 23:30").unwrap(),
+                    parse_datetime("2026-10-20 01:00").unwrap(),
+                );
+                repo.save_log(&project_id, &log).unwrap();
+
+                // Act
+                let first_day = repo
+                    .logs_between(
+                        &parse_datetime("2026-10-19 00:00").unwrap(),
+                        &parse_datetime("2026-10-20 00:00").unwrap(),
+                    )
+                    .unwrap();
+                let second_day = repo
+                    .logs_between(
+                        &parse_datetime("2026-10-20 00:00").unwrap(),
+                        &parse_datetime("2026-10-21 00:00").unwrap(),
+                    )
+                    .unwrap();
+
+                // Assert
+                assert_eq!(1, first_day.len());
+                assert_eq!(
+                    90 * 60,
+                    (first_day[0].end - first_day[0].start).num_seconds()
+                );
+                assert!(second_day.is_empty());
+            }
+
+            #[test]
+            fn save_log_should_keep_the_zone_it_was_recorded_in() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut named = Log::new(
+                    0,
+                    project_id,
+                    "a".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                named.zone = Some("Asia/Tokyo".parse().unwrap());
+                let mut offset = named.clone();
+                offset.zone = Some("-03:30".parse().unwrap());
+                let unknown = Log::new(
+                    0,
+                    project_id,
+                    "c".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+
+                // Act
+                let named_id = repo.save_log(&project_id, &named).unwrap();
+                let offset_id = repo.save_log(&project_id, &offset).unwrap();
+                let unknown_id = repo.save_log(&project_id, &unknown).unwrap();
+
+                // Assert
+                assert_eq!(named.zone, repo.get_log(&named_id).unwrap().zone);
+                assert_eq!(offset.zone, repo.get_log(&offset_id).unwrap().zone);
+                assert_eq!(None, repo.get_log(&unknown_id).unwrap().zone);
+            }
+
+            #[test]
+            fn update_log_should_change_the_zone() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "a".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                log.id = repo.save_log(&project_id, &log).unwrap();
+                log.zone = Some("Europe/Berlin".parse().unwrap());
+
+                // Act
+                let updated = repo.update_log(&log).unwrap();
+
+                // Assert
+                assert_eq!(1, updated);
+                assert_eq!(log.zone, repo.all_logs().unwrap()[0].zone);
+                assert!(repo.log_history(&log.id).unwrap().is_empty());
+            }
+
+            #[test]
+            fn save_log_ending_before_it_starts_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "backwards".to_owned(),
+                    to_datetime(200),
+                    to_datetime(100),
+                );
+
+                // Act
+                let res = repo.save_log(&project_id, &log);
+
+                // Assert
+                assert!(res.is_err());
+                assert!(repo.all_logs().unwrap().is_empty());
+            }
+
+            #[test]
+            fn update_log_ending_before_it_starts_should_fail() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "a".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                log.id = repo.save_log(&project_id, &log).unwrap();
+                log.end = to_datetime(50);
+
+                // Act
+                let res = repo.update_log(&log);
+
+                // Assert
+                assert!(res.is_err());
+                assert_eq!(200, repo.get_log(&log.id).unwrap().end.timestamp());
+                assert!(repo.log_history(&log.id).unwrap().is_empty());
+            }
+
+            #[test]
+            fn save_log_should_keep_milliseconds() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let start = to_datetime(100) + Duration::milliseconds(250);
+                let log = Log::new(
+                    0,
+                    project_id,
+                    "a".to_owned(),
+                    start,
+                    start + Duration::microseconds(1_500_700),
+                );
+
+                // Act
+                let log_id = repo.save_log(&project_id, &log).unwrap();
+
+                // Assert
+                let actual_log = repo.get_log(&log_id).unwrap();
+                assert_eq!(100_250, actual_log.start.timestamp_millis());
+                assert_eq!(101_750, actual_log.end.timestamp_millis());
+                assert_eq!(Duration::milliseconds(1500), actual_log.duration());
+            }
+
+            #[test]
+            fn update_log_changing_milliseconds_should_keep_previous_times() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut log = Log::new(
+                    0,
+                    project_id,
+                    "a".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                log.id = repo.save_log(&project_id, &log).unwrap();
+                log.end += Duration::milliseconds(5);
+
+                // Act
+                repo.update_log(&log).unwrap();
+
+                // Assert
+                let history = repo.log_history(&log.id).unwrap();
+                assert_eq!(1, history.len());
+                assert_eq!(200_000, history[0].end.timestamp_millis());
+                assert_eq!(
+                    200_005,
+                    repo.get_log(&log.id).unwrap().end.timestamp_millis()
+                );
+            }
+
+            /// Saves a log with each message in its own project, a minute apart, and returns
+            /// a function searching every project for the messages that match
+            fn searchable_repo(messages: &[&str]) -> impl Fn(&str, Matching) -> Vec<String> {
+                let repo = test_repo();
+                for (i, message) in messages.iter().enumerate() {
+                    let project_id = repo.save_project(&default_test_project()).unwrap();
+                    let start = 1000 + i as i64 * 60;
+                    let log = Log::new(
+                        0,
+                        project_id,
+                        message.to_string(),
+                        to_datetime(start),
+                        to_datetime(start + 30),
+                    );
+                    repo.save_log(&project_id, &log).unwrap();
+                }
+
+                move |text, matching| {
+                    repo.search_logs(None, &Pattern::new(text, matching).unwrap())
+                        .unwrap()
+                        .into_iter()
+                        .map(|l| l.message)
+                        .collect()
+                }
+            }
+
+            #[test]
+            fn search_logs_across_projects_should_return_matches_oldest_first() {
+                // Arrange
+                let search = searchable_repo(&["Deploy api", "write docs", "deploy web"]);
+
+                // Act
+                let found = search("DEPLOY", Matching::Text);
+
+                // Assert
+                assert_eq!(vec!["Deploy api", "deploy web"], found);
+            }
+
+            #[test]
+            fn search_logs_should_treat_wildcards_in_text_literally() {
+                // Arrange
+                let search = searchable_repo(&["100% done", "1000 done", "a_b", "axb", "c:\\d"]);
+
+                // Act
+                // Assert
+                assert_eq!(vec!["100% done"], search("100%", Matching::Text));
+                assert_eq!(vec!["a_b"], search("_", Matching::Text));
+                assert_eq!(vec!["c:\\d"], search("\\", Matching::Text));
+            }
+
+            #[test]
+            fn search_logs_shorter_than_the_index_should_still_match() {
+                // Arrange
+                let search = searchable_repo(&["QA pass", "deploy"]);
+
+                // Act
+                let found = search("qa", Matching::Text);
+
+                // Assert
+                assert_eq!(vec!["QA pass"], found);
+            }
+
+            #[test]
+            fn search_logs_with_glob_should_match_whole_message() {
+                // Arrange
+                let search = searchable_repo(&["deploy v2", "redeploy v2", "Deploy v3", "deploy"]);
+
+                // Act
+                let found = search("deploy v?", Matching::Glob);
+
+                // Assert
+                assert_eq!(vec!["deploy v2"], found);
+            }
+
+            #[test]
+            fn search_logs_with_regex_should_match_part_of_message() {
+                // Arrange
+                let search = searchable_repo(&["fix #12", "review #3", "fix typo", "Fix #7"]);
+
+                // Act
+                let found = search("(?i)^fix #[0-9]+", Matching::Regex);
+
+                // Assert
+                assert_eq!(vec!["fix #12", "Fix #7"], found);
+            }
+
+            #[test]
+            fn search_logs_with_regex_containing_text_should_match_like_without() {
+                // Arrange
+                let search = searchable_repo(&[
+                    "deploy v12 to prod",
+                    "Deploy v3 to prod",
+                    "deploy docs to prod",
+                    "redeploy v4 to staging",
+                ]);
+
+                // Act
+                let found = search("deploy v[0-9]+ to (prod|staging)", Matching::Regex);
+
+                // Assert
+                assert_eq!(vec!["deploy v12 to prod", "redeploy v4 to staging"], found);
+            }
+
+            #[test]
+            fn search_logs_should_follow_edited_and_removed_logs() {
+                // Arrange
+                let repo = test_repo();
+                let project_id = repo.save_project(&default_test_project()).unwrap();
+                let mut edited = Log::new(
+                    0,
+                    project_id,
+                    "standup".to_owned(),
+                    to_datetime(100),
+                    to_datetime(200),
+                );
+                edited.id = repo.save_log(&project_id, &edited).unwrap();
+                let removed = Log::new(
+                    0,
+                    project_id,
+                    "standup".to_owned(),
+                    to_datetime(300),
+                    to_datetime(400),
+                );
+                let removed_id = repo.save_log(&project_id, &removed).unwrap();
+                edited.message = "planning".to_owned();
+
+                // Act
+                repo.update_log(&edited).unwrap();
+                repo.delete_log(&project_id, &removed_id).unwrap();
+
+                // Assert
+                let search = |text: &str| {
+                    repo.search_logs(None, &Pattern::new(text, Matching::Text).unwrap())
+                        .unwrap()
+                };
+                assert!(search("standup").is_empty());
+                assert_eq!(edited.id, search("plan")[0].id);
+            }
+        }
+    };
+}
+
+pub(crate) use repository_tests;
+
+pub fn default_test_project() -> Project {
+    let created = Local::now();
+    let updated = Local::now();
+
+    Project::new(
+        "test".to_owned(),
+        Option::Some("hi".to_owned()),
+        created,
+        updated,
+    )
+}
+
+pub fn default_test_client() -> Client {
+    Client::new(
+        "acme".to_owned(),
+        Some("Wile E. Coyote".to_owned()),
+        "AUD".to_owned(),
+        Some(Decimal::new(8550, 2)),
+        None,
+    )
+}
+
+pub fn default_test_invoice(client_id: u32) -> Invoice {
+    Invoice {
+        id: 0,
+        number: "INV-0001".to_owned(),
+        client_id,
+        period_start: to_datetime(0),
+        period_end: to_datetime(100),
+        created: Local::now(),
+        currency: "AUD".to_owned(),
+        lines: vec![InvoiceLine {
+            description: "test".to_owned(),
+            raw_seconds: 3500,
+            seconds: 3600,
+            rate: Decimal::new(8550, 2),
+            amount: Decimal::new(8550, 2),
+        }],
+        total: Decimal::new(8550, 2),
+    }
+}
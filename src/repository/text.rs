@@ -0,0 +1,1515 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    env,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use chrono::{DateTime, Local, SubsecRound};
+use rusqlite::{ffi, Result};
+
+use crate::{
+    clock::{Clock, SystemClock},
+    config::Config,
+    integrity::chain::{self, Sealed},
+    log::search::Pattern,
+    model::{
+        Client, Goal, GoalScope, Invoice, Log, LogRevision, Operation, OperationState, Project,
+        Rate, RateScope,
+    },
+};
+
+use self::records::{render, Alias, Change, Import, Item, Record, Table};
+
+use super::{constraint, current_user, Repository};
+
+mod records;
+
+/// Stores everything in human readable text files, one record per line with its fields
+/// separated by tabs. Logs are kept in a file per month, named after the month they started in,
+/// e.g. `logs/2026-10.txt`. The files are read when the store is opened and rewritten whenever
+/// they change, unless a transaction is open.
+pub struct Text {
+    dir: PathBuf,
+    data: RefCell<Data>,
+    /// The data as each open transaction began, innermost last
+    transactions: RefCell<Vec<Data>>,
+    /// The operation being recorded, and the data as it began
+    recording: RefCell<Option<(u32, Data)>>,
+    /// Contents of each file when it was last read or written, by path within `dir`
+    written: RefCell<BTreeMap<PathBuf, String>>,
+    /// Whether logs are sealed in a hash chain as they are saved, changed and deleted
+    chained: bool,
+    /// Whether `dir` is removed when the store is dropped
+    temporary: bool,
+    /// Tells the time changes are made at
+    clock: Box<dyn Clock>,
+}
+
+/// Every record, by table. Tables are named after their SQLite counterparts
+#[derive(Clone, Default)]
+struct Data {
+    projects: Table<Project>,
+    aliases: Table<Alias>,
+    clients: Table<Client>,
+    rates: Table<Rate>,
+    goals: Table<Goal>,
+    logs: Table<Sealed>,
+    invoices: Table<Invoice>,
+    items: Table<Item>,
+    imports: Table<Import>,
+    revisions: Table<LogRevision>,
+    operations: Table<Operation>,
+    changes: Table<Change>,
+    /// Highest ID generated for each table, so IDs of deleted records are never used again
+    sequences: BTreeMap<String, u32>,
+}
+
+/// A record an operation changed, as `(table, old line, new line)`
+type Image = (&'static str, Option<String>, Option<String>);
+
+impl Data {
+    /// Reads every file in the directory. Missing files are treated as empty
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory the files are in
+    /// * `written` - Filled with the contents of each file read
+    fn load(dir: &Path, written: &mut BTreeMap<PathBuf, String>) -> Result<Data, String> {
+        let mut data = Data::default();
+
+        read(dir, "projects.txt", &mut data.projects, written)?;
+        read(dir, "project_aliases.txt", &mut data.aliases, written)?;
+        read(dir, "clients.txt", &mut data.clients, written)?;
+        read(dir, "rates.txt", &mut data.rates, written)?;
+        read(dir, "goals.txt", &mut data.goals, written)?;
+        read(dir, "invoices.txt", &mut data.invoices, written)?;
+        read(dir, "invoice_lines.txt", &mut data.items, written)?;
+        read(dir, "imports.txt", &mut data.imports, written)?;
+        read(dir, "log_revisions.txt", &mut data.revisions, written)?;
+        read(dir, "operations.txt", &mut data.operations, written)?;
+        read(dir, "operation_changes.txt", &mut data.changes, written)?;
+
+        let months = fs::read_dir(dir.join("logs")).map_err(|e| format!("logs: {}", e))?;
+        let mut months = months
+            .map(|entry| entry.map(|e| e.file_name()))
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(|e| format!("logs: {}", e))?;
+        months.sort();
+        for month in months {
+            let file = Path::new("logs").join(month);
+            if file.extension().is_some_and(|e| e == "txt") {
+                read(dir, &file, &mut data.logs, written)?;
+            }
+        }
+
+        if let Ok(text) = fs::read_to_string(dir.join("sequences.txt")) {
+            for line in text.lines().filter(|l| !l.starts_with('#')) {
+                if let Some((table, last)) = line.split_once('\t') {
+                    let last = last
+                        .parse()
+                        .map_err(|e| format!("sequences.txt: {}: {}", table, e))?;
+                    data.sequences.insert(table.to_owned(), last);
+                }
+            }
+            written.insert(PathBuf::from("sequences.txt"), text);
+        }
+
+        Ok(data)
+    }
+
+    /// Contents of every file, by path within the directory
+    fn files(&self) -> BTreeMap<PathBuf, String> {
+        let mut files: BTreeMap<PathBuf, String> = [
+            ("projects.txt", render(&self.projects)),
+            ("project_aliases.txt", render(&self.aliases)),
+            ("clients.txt", render(&self.clients)),
+            ("rates.txt", render(&self.rates)),
+            ("goals.txt", render(&self.goals)),
+            ("invoices.txt", render(&self.invoices)),
+            ("invoice_lines.txt", render(&self.items)),
+            ("imports.txt", render(&self.imports)),
+            ("log_revisions.txt", render(&self.revisions)),
+            ("operations.txt", render(&self.operations)),
+            ("operation_changes.txt", render(&self.changes)),
+        ]
+        .into_iter()
+        .map(|(file, text)| (PathBuf::from(file), text))
+        .collect();
+
+        let mut months: BTreeMap<String, Vec<(&u32, &Sealed)>> = BTreeMap::new();
+        for (id, sealed) in &self.logs {
+            let month = sealed.0.start.format("%Y-%m.txt").to_string();
+            months.entry(month).or_default().push((id, sealed));
+        }
+        for (month, logs) in months {
+            files.insert(Path::new("logs").join(month), render(logs));
+        }
+
+        let mut sequences = "# table\tlast id\n".to_owned();
+        for (table, last) in &self.sequences {
+            sequences.push_str(&format!("{}\t{}\n", table, last));
+        }
+        files.insert(PathBuf::from("sequences.txt"), sequences);
+
+        files
+    }
+
+    /// Every record an operation can change, as lines by ID, by table
+    fn images(&self) -> [(&'static str, BTreeMap<u32, String>); 10] {
+        [
+            ("projects", images(&self.projects)),
+            ("project_aliases", images(&self.aliases)),
+            ("clients", images(&self.clients)),
+            ("rates", images(&self.rates)),
+            ("goals", images(&self.goals)),
+            ("logs", images(&self.logs)),
+            ("invoices", images(&self.invoices)),
+            ("invoice_lines", images(&self.items)),
+            ("imports", images(&self.imports)),
+            ("log_revisions", images(&self.revisions)),
+        ]
+    }
+
+    /// The records that differ between this data and the given data
+    fn changes_to(&self, after: &Data) -> Vec<Image> {
+        let mut changes = Vec::new();
+        for ((table, before), (_, after)) in self.images().into_iter().zip(after.images()) {
+            let ids: BTreeSet<&u32> = before.keys().chain(after.keys()).collect();
+            for id in ids {
+                let (old, new) = (before.get(id), after.get(id));
+                if old != new {
+                    changes.push((table, old.cloned(), new.cloned()));
+                }
+            }
+        }
+        changes
+    }
+
+    /// Changes a record from one line to another, inserting or deleting it when either is missing
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Name of the table the record is in
+    /// * `from` - The record as it is now
+    /// * `to` - The record as it should be
+    fn put(&mut self, table: &str, from: Option<&str>, to: Option<&str>) -> Result<(), String> {
+        match table {
+            "projects" => put(&mut self.projects, from, to),
+            "project_aliases" => put(&mut self.aliases, from, to),
+            "clients" => put(&mut self.clients, from, to),
+            "rates" => put(&mut self.rates, from, to),
+            "goals" => put(&mut self.goals, from, to),
+            "logs" => put(&mut self.logs, from, to),
+            "invoices" => put(&mut self.invoices, from, to),
+            "invoice_lines" => put(&mut self.items, from, to),
+            "imports" => put(&mut self.imports, from, to),
+            "log_revisions" => put(&mut self.revisions, from, to),
+            _ => Err(format!("{} is not a table", table)),
+        }
+    }
+
+    /// The ID to save a new record with. A non-zero ID is kept unless it is taken, otherwise the
+    /// next ID is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - Records the new one is saved with
+    /// * `name` - Name of the table
+    /// * `id` - ID the record was given
+    fn new_id<R>(&self, table: &Table<R>, name: &str, id: u32) -> Result<u32> {
+        match id {
+            0 => {
+                let last = table.keys().next_back().copied().unwrap_or_default();
+                Ok(last.max(self.sequences.get(name).copied().unwrap_or_default()) + 1)
+            }
+            id if table.contains_key(&id) => {
+                Err(constraint(format!("UNIQUE constraint failed: {}.id", name)))
+            }
+            id => Ok(id),
+        }
+    }
+
+    /// Records that an ID was used, so it is not generated again
+    fn claim(&mut self, name: &str, id: u32) {
+        let last = self.sequences.entry(name.to_owned()).or_default();
+        *last = id.max(*last);
+    }
+
+    /// Copies of the logs with whether each was edited filled in
+    fn read_logs<'a>(&self, logs: impl Iterator<Item = &'a Sealed>) -> Vec<Log> {
+        let edited: HashSet<u32> = self.revisions.values().map(|r| r.log_id).collect();
+        logs.map(|(log, _)| {
+            let mut log = log.clone();
+            log.edited = edited.contains(&log.id);
+            log
+        })
+        .collect()
+    }
+}
+
+impl Text {
+    /// Opens the text files in the configured directory, `$XDG_DATA_HOME/arrow/text` unless set
+    /// otherwise, creating it if needed.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let config = Config::load()?;
+        let dir = match config.storage.dir {
+            Some(dir) => dir,
+            None => xdg::BaseDirectories::with_prefix("arrow")?.create_data_directory("text")?,
+        };
+
+        Self::open(&dir, config.integrity.chain)
+    }
+
+    /// Reads the text files in a directory, creating it if needed
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory the files are in
+    /// * `chained` - Whether logs are sealed in a hash chain
+    pub fn open(dir: &Path, chained: bool) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir.join("logs"))?;
+        let mut written = BTreeMap::new();
+        let data = Data::load(dir, &mut written)?;
+
+        Ok(Text {
+            dir: dir.to_owned(),
+            data: RefCell::new(data),
+            transactions: RefCell::new(Vec::new()),
+            recording: RefCell::new(None),
+            written: RefCell::new(written),
+            chained,
+            temporary: false,
+            clock: Box::new(SystemClock),
+        })
+    }
+
+    /// Opens an empty store in a new temporary directory, which is removed when the store is
+    /// dropped. Logs are not sealed
+    pub fn temporary() -> Result<Self, Box<dyn Error>> {
+        static OPENED: AtomicUsize = AtomicUsize::new(0);
+
+        let dir = env::temp_dir().join(format!(
+            "arrow-{}-{}",
+            process::id(),
+            OPENED.fetch_add(1, Ordering::SeqCst)
+        ));
+        let mut text = Self::open(&dir, false)?;
+        text.temporary = true;
+        Ok(text)
+    }
+
+    /// Uses the clock to tell the time changes are made at, instead of the system's
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Clock to tell the time by
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Makes a change to the data, then writes the files it changed unless a transaction is
+    /// open. Changes check everything that could fail before they change anything, so a failed
+    /// change leaves the data as it was
+    fn change<T>(&self, steps: impl FnOnce(&mut Data) -> Result<T>) -> Result<T> {
+        let value = steps(&mut self.data.borrow_mut())?;
+        self.flush()?;
+        Ok(value)
+    }
+
+    /// Writes every file that changed since it was last read or written, and removes files of
+    /// months without logs
+    fn flush(&self) -> Result<()> {
+        if !self.transactions.borrow().is_empty() {
+            return Ok(());
+        }
+
+        let files = self.data.borrow().files();
+        let mut written = self.written.borrow_mut();
+        for (file, text) in &files {
+            if written.get(file) != Some(text) {
+                let path = self.dir.join(file);
+                let partial = path.with_extension("txt.partial");
+                fs::write(&partial, text)
+                    .and_then(|_| fs::rename(&partial, &path))
+                    .map_err(|e| io_error(file, e))?;
+            }
+        }
+        for file in written.keys().filter(|f| !files.contains_key(*f)) {
+            match fs::remove_file(self.dir.join(file)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(io_error(file, e)),
+                _ => {}
+            }
+        }
+
+        *written = files;
+        Ok(())
+    }
+
+    /// Saves a log within a change. Fails before changing anything when the project is missing,
+    /// the ID is taken or the log ends before it starts
+    fn insert_log(&self, data: &mut Data, project_id: &u32, log: &Log) -> Result<u32> {
+        refers_to(&data.projects, Some(*project_id))?;
+        in_order(log)?;
+        let log_id = data.new_id(&data.logs, "logs", log.id)?;
+        // logs saved with a new ID come last, nothing after them needs sealing again
+        let mut intact = match log.id {
+            0 => HashSet::new(),
+            id => self.intact_logs(data, id),
+        };
+
+        let mut saved = Log::new(
+            log_id,
+            *project_id,
+            log.message.clone(),
+            log.start.trunc_subsecs(3),
+            log.end.trunc_subsecs(3),
+        );
+        saved.billable = log.billable;
+        saved.zone = log.zone;
+        saved.tags = unique(&log.tags);
+        data.logs.insert(log_id, (saved, None));
+        data.claim("logs", log_id);
+
+        intact.insert(log_id);
+        self.seal(data, log_id, &intact);
+        Ok(log_id)
+    }
+
+    /// IDs of the logs from the given ID on that still match their hash. Taken before arrow
+    /// changes logs, so only these are sealed again afterwards
+    fn intact_logs(&self, data: &Data, from: u32) -> HashSet<u32> {
+        if !self.chained {
+            return HashSet::new();
+        }
+
+        let mut previous = data
+            .logs
+            .range(..from)
+            .next_back()
+            .and_then(|(_, s)| s.1.clone());
+        let mut intact = HashSet::new();
+        for (log, hash) in data.logs.range(from..).map(|(_, sealed)| sealed) {
+            if hash.is_some() && *hash == Some(chain::link(previous.as_deref(), log)) {
+                intact.insert(log.id);
+            }
+            previous = hash.clone();
+        }
+
+        intact
+    }
+
+    /// Hashes the given logs again, chaining each to the log before it. Other logs keep their
+    /// hash, so a log changed without arrow still fails verification
+    fn seal(&self, data: &mut Data, from: u32, logs: &HashSet<u32>) {
+        if !self.chained {
+            return;
+        }
+
+        let mut previous = data
+            .logs
+            .range(..from)
+            .next_back()
+            .and_then(|(_, s)| s.1.clone());
+        for (id, (log, hash)) in data.logs.range_mut(from..) {
+            if logs.contains(id) {
+                *hash = Some(chain::link(previous.as_deref(), log));
+            }
+            previous = hash.clone();
+        }
+    }
+
+    /// The newest or oldest operation in a state
+    fn operation(&self, state: OperationState, newest: bool) -> Result<Option<Operation>> {
+        let mut operations = self
+            .all_operations()?
+            .into_iter()
+            .filter(|o| o.state == state);
+
+        Ok(match newest {
+            true => operations.next_back(),
+            false => operations.next(),
+        })
+    }
+
+    /// Puts the records an operation changed back the way they were before it, or redoes its
+    /// changes. Either every change is applied or none are
+    fn replay(&self, operation: &Operation, undo: bool) -> Result<()> {
+        self.change(|data| {
+            let mut replayed = data.clone();
+            for change in data.changes.values() {
+                if change.operation_id != operation.id {
+                    continue;
+                }
+
+                let (from, to) = match undo {
+                    true => (&change.new, &change.old),
+                    false => (&change.old, &change.new),
+                };
+                replayed
+                    .put(&change.table, from.as_deref(), to.as_deref())
+                    .map_err(corrupt)?;
+            }
+
+            if let Some(replayed) = replayed.operations.get_mut(&operation.id) {
+                replayed.state = match undo {
+                    true => OperationState::Undone,
+                    false => OperationState::Done,
+                };
+            }
+            *data = replayed;
+            Ok(())
+        })
+    }
+}
+
+impl Drop for Text {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+impl Repository for Text {
+    /// Saves the given project. A non-zero ID is kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - The project to be saved
+    fn save_project(&self, project: &Project) -> Result<u32> {
+        self.change(|data| {
+            let id = data.new_id(&data.projects, "projects", project.id)?;
+            if project.parent_id != Some(id) {
+                refers_to(&data.projects, project.parent_id)?;
+            }
+            refers_to(&data.clients, project.client_id)?;
+
+            let mut saved = project.clone();
+            saved.id = id;
+            data.projects.insert(id, saved);
+            data.claim("projects", id);
+            Ok(id)
+        })
+    }
+
+    /// Saves the log. A non-zero ID is kept, otherwise one is generated. The log is sealed when
+    /// the hash chain is turned on
+    ///
+    /// # Arguments
+    ///
+    /// * `project_id` - ID of the project to save the log under
+    /// * `log` - The log to be saved
+    fn save_log(&self, project_id: &u32, log: &Log) -> Result<u32> {
+        self.change(|data| self.insert_log(data, project_id, log))
+    }
+
+    /// Retrieve all projects
+    fn all_projects(&self) -> Result<Vec<Project>> {
+        Ok(self.data.borrow().projects.values().cloned().collect())
+    }
+
+    /// Retrieve a project, and it's logs with by project ID
+    ///
+    /// # Arguments
+    ///
+    /// - `id` - ID of the project to retrieve
+    fn get_project(&self, id: &u32) -> Result<(Project, Vec<Log>)> {
+        let data = self.data.borrow();
+        let project = data
+            .projects
+            .get(id)
+            .cloned()
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        let logs = data.read_logs(data.logs.values().filter(|(l, _)| l.proj_id == *id));
+
+        Ok((project, logs))
+    }
+
+    fn get_project_by_name(&self, name: &str) -> Result<Project> {
+        self.data
+            .borrow()
+            .projects
+            .values()
+            .find(|p| p.name == name)
+            .cloned()
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    /// Retrieve the logs whose message matches, oldest first
+    ///
+    /// # Arguments
+    ///
+    /// * `proj_id` - ID of the only project to search, `None` to search every project
+    /// * `pattern` - What to match the messages against
+    fn search_logs(&self, proj_id: Option<u32>, pattern: &Pattern) -> Result<Vec<Log>> {
+        let data = self.data.borrow();
+        let mut logs = data.read_logs(data.logs.values().filter(|(l, _)| {
+            proj_id.is_none_or(|id| l.proj_id == id) && pattern.matches(&l.message)
+        }));
+        logs.sort_by_key(|l| l.start);
+
+        Ok(logs)
+    }
+
+    /// Retrieve the logs of every project that started within the given range
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Inclusive start of the range
+    /// * `to` - Exclusive end of the range
+    fn logs_between(&self, from: &DateTime<Local>, to: &DateTime<Local>) -> Result<Vec<Log>> {
+        let data = self.data.borrow();
+        let mut logs = data.read_logs(
+            data.logs
+                .values()
+                .filter(|(l, _)| l.start >= *from && l.start < *to),
+        );
+        logs.sort_by_key(|l| l.start);
+
+        Ok(logs)
+    }
+
+    /// Registers an alternative name the project can be referred to by
+    ///
+    /// # Arguments
+    ///
+    /// * `project_id` - ID of the project the alias points to
+    /// * `alias` - The alias. Must be unique across all projects
+    fn save_alias(&self, project_id: &u32, alias: &str) -> Result<()> {
+        self.change(|data| {
+            if data.aliases.values().any(|a| a.alias == alias) {
+                return Err(constraint(
+                    "UNIQUE constraint failed: project_aliases.alias",
+                ));
+            }
+            refers_to(&data.projects, Some(*project_id))?;
+
+            let id = data.new_id(&data.aliases, "project_aliases", 0)?;
+            data.aliases.insert(
+                id,
+                Alias {
+                    alias: alias.to_owned(),
+                    project_id: *project_id,
+                },
+            );
+            data.claim("project_aliases", id);
+            Ok(())
+        })
+    }
+
+    /// Retrieve every alias along with the ID of the project it points to
+    fn all_aliases(&self) -> Result<Vec<(String, u32)>> {
+        Ok(self
+            .data
+            .borrow()
+            .aliases
+            .values()
+            .map(|a| (a.alias.clone(), a.project_id))
+            .collect())
+    }
+
+    /// Saves the given client. A non-zero ID is kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The client to be saved. Names must be unique
+    fn save_client(&self, client: &Client) -> Result<u32> {
+        self.change(|data| {
+            if data.clients.values().any(|c| c.name == client.name) {
+                return Err(constraint("UNIQUE constraint failed: clients.name"));
+            }
+
+            let id = data.new_id(&data.clients, "clients", client.id)?;
+            let mut saved = client.clone();
+            saved.id = id;
+            data.clients.insert(id, saved);
+            data.claim("clients", id);
+            Ok(id)
+        })
+    }
+
+    /// Retrieve all clients, ordered by name
+    fn all_clients(&self) -> Result<Vec<Client>> {
+        let mut clients: Vec<Client> = self.data.borrow().clients.values().cloned().collect();
+        clients.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(clients)
+    }
+
+    /// Retrieve a client by ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the client to retrieve
+    fn get_client(&self, id: &u32) -> Result<Client> {
+        self.data
+            .borrow()
+            .clients
+            .get(id)
+            .cloned()
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    /// Overwrites the stored client with the same ID. Returns the number of clients updated
+    fn update_client(&self, client: &Client) -> Result<usize> {
+        self.change(|data| {
+            if data
+                .clients
+                .values()
+                .any(|c| c.name == client.name && c.id != client.id)
+            {
+                return Err(constraint("UNIQUE constraint failed: clients.name"));
+            }
+
+            Ok(match data.clients.get_mut(&client.id) {
+                Some(stored) => {
+                    *stored = client.clone();
+                    1
+                }
+                None => 0,
+            })
+        })
+    }
+
+    /// Delete a client and its rates by ID. Fails while projects or invoices are still billed to
+    /// the client
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the client to delete
+    fn delete_client(&self, id: &u32) -> Result<usize> {
+        self.change(|data| {
+            if data.projects.values().any(|p| p.client_id == Some(*id))
+                || data.invoices.values().any(|i| i.client_id == *id)
+            {
+                return Err(constraint("FOREIGN KEY constraint failed"));
+            }
+
+            data.rates.retain(|_, r| r.scope != RateScope::Client(*id));
+            Ok(data.clients.remove(id).map_or(0, |_| 1))
+        })
+    }
+
+    /// Saves the given rate. A non-zero ID is kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The rate to be saved
+    fn save_rate(&self, rate: &Rate) -> Result<u32> {
+        self.change(|data| {
+            match &rate.scope {
+                RateScope::Client(id) => refers_to(&data.clients, Some(*id))?,
+                RateScope::Project(id) => refers_to(&data.projects, Some(*id))?,
+                RateScope::Tag(_) => {}
+            }
+
+            let id = data.new_id(&data.rates, "rates", rate.id)?;
+            let mut saved = rate.clone();
+            saved.id = id;
+            data.rates.insert(id, saved);
+            data.claim("rates", id);
+            Ok(id)
+        })
+    }
+
+    /// Retrieve every rate, oldest first
+    fn all_rates(&self) -> Result<Vec<Rate>> {
+        let mut rates: Vec<Rate> = self.data.borrow().rates.values().cloned().collect();
+        rates.sort_by_key(|r| r.effective_from);
+
+        Ok(rates)
+    }
+
+    /// Delete a rate by ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the rate to delete
+    fn delete_rate(&self, id: &u32) -> Result<usize> {
+        self.change(|data| Ok(data.rates.remove(id).map_or(0, |_| 1)))
+    }
+
+    /// Saves a goal. A non-zero ID is kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `goal` - The goal to be saved
+    fn save_goal(&self, goal: &Goal) -> Result<u32> {
+        self.change(|data| {
+            if let GoalScope::Project(id) = goal.scope {
+                refers_to(&data.projects, Some(id))?;
+            }
+
+            let id = data.new_id(&data.goals, "goals", goal.id)?;
+            let mut saved = goal.clone();
+            saved.id = id;
+            data.goals.insert(id, saved);
+            data.claim("goals", id);
+            Ok(id)
+        })
+    }
+
+    /// Retrieve every goal in the order they were set
+    fn all_goals(&self) -> Result<Vec<Goal>> {
+        Ok(self.data.borrow().goals.values().cloned().collect())
+    }
+
+    /// Delete a goal by ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the goal to delete
+    fn delete_goal(&self, id: &u32) -> Result<usize> {
+        self.change(|data| Ok(data.goals.remove(id).map_or(0, |_| 1)))
+    }
+
+    fn update_project(&self, project: &Project) -> Result<usize> {
+        let updated = self.change(|data| {
+            refers_to(&data.projects, project.parent_id)?;
+            refers_to(&data.clients, project.client_id)?;
+
+            Ok(match data.projects.get_mut(&project.id) {
+                Some(stored) => {
+                    *stored = Project {
+                        created: stored.created,
+                        updated: self.clock.now(),
+                        ..project.clone()
+                    };
+                    1
+                }
+                None => 0,
+            })
+        });
+
+        match updated {
+            Ok(updated) => Ok(updated),
+            Err(err) => panic!("Update failed: {}", err),
+        }
+    }
+
+    /// Delete a project by it's ID. Deletes all logs that are associated with it. Returns the
+    /// number of projects deleted
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the project to delete
+    fn delete_project(&self, id: &u32) -> Result<usize> {
+        self.change(|data| {
+            if data
+                .projects
+                .values()
+                .any(|p| p.parent_id == Some(*id) && p.id != *id)
+            {
+                return Err(constraint("FOREIGN KEY constraint failed"));
+            }
+
+            let logs: HashSet<u32> = data
+                .logs
+                .values()
+                .filter(|(l, _)| l.proj_id == *id)
+                .map(|(l, _)| l.id)
+                .collect();
+            let first_log = logs.iter().min().copied().unwrap_or(u32::MAX);
+            let intact = self.intact_logs(data, first_log);
+
+            data.aliases.retain(|_, a| a.project_id != *id);
+            data.rates.retain(|_, r| r.scope != RateScope::Project(*id));
+            data.goals.retain(|_, g| g.scope != GoalScope::Project(*id));
+            data.imports.retain(|_, i| !logs.contains(&i.log_id));
+            data.revisions.retain(|_, r| !logs.contains(&r.log_id));
+            data.logs.retain(|log_id, _| !logs.contains(log_id));
+            self.seal(data, first_log, &intact);
+
+            Ok(data.projects.remove(id).map_or(0, |_| 1))
+        })
+    }
+
+    /// Delete a log by it's project ID and log ID. Invoiced logs are never deleted. Returns the
+    /// number of logs deleted
+    ///
+    /// # Arguments
+    ///
+    /// * `proj_id` - ID of the project to delete the log from
+    /// * `log_id` - ID of the log to delete
+    fn delete_log(&self, proj_id: &u32, log_id: &u32) -> Result<usize> {
+        self.change(|data| {
+            let deletable = data
+                .logs
+                .get(log_id)
+                .is_some_and(|(l, _)| l.proj_id == *proj_id && l.invoice_id.is_none());
+            if !deletable {
+                return Ok(0);
+            }
+
+            let intact = self.intact_logs(data, *log_id);
+            data.imports.retain(|_, i| i.log_id != *log_id);
+            data.revisions.retain(|_, r| r.log_id != *log_id);
+            data.logs.remove(log_id);
+            self.seal(data, *log_id, &intact);
+            Ok(1)
+        })
+    }
+
+    /// Retrieve a log and its tags by ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the log to retrieve
+    fn get_log(&self, id: &u32) -> Result<Log> {
+        let data = self.data.borrow();
+        data.read_logs(data.logs.get(id).into_iter())
+            .pop()
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    /// Overwrites the stored log with the same ID, including its tags. Invoiced logs are locked
+    /// and left untouched. The message and times are kept as a revision whenever they change.
+    /// The log and those after it are sealed again, unless they were changed without arrow.
+    /// Returns the number of logs updated
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - The log to save
+    fn update_log(&self, log: &Log) -> Result<usize> {
+        self.change(|data| {
+            let Some((previous, _)) = data
+                .logs
+                .get(&log.id)
+                .filter(|(l, _)| l.invoice_id.is_none())
+                .cloned()
+            else {
+                return Ok(0);
+            };
+            refers_to(&data.projects, Some(log.proj_id))?;
+            in_order(log)?;
+            let intact = self.intact_logs(data, log.id);
+
+            if previous.message != log.message
+                || previous.start.timestamp_millis() != log.start.timestamp_millis()
+                || previous.end.timestamp_millis() != log.end.timestamp_millis()
+            {
+                let id = data.new_id(&data.revisions, "log_revisions", 0)?;
+                data.revisions.insert(
+                    id,
+                    LogRevision {
+                        id,
+                        log_id: log.id,
+                        changed: self.clock.now(),
+                        changed_by: current_user(),
+                        message: previous.message,
+                        start: previous.start,
+                        end: previous.end,
+                    },
+                );
+                data.claim("log_revisions", id);
+            }
+
+            if let Some((stored, _)) = data.logs.get_mut(&log.id) {
+                stored.message = log.message.clone();
+                stored.start = log.start.trunc_subsecs(3);
+                stored.end = log.end.trunc_subsecs(3);
+                stored.proj_id = log.proj_id;
+                stored.billable = log.billable;
+                stored.zone = log.zone;
+                stored.tags = unique(&log.tags);
+            }
+            self.seal(data, log.id, &intact);
+
+            Ok(1)
+        })
+    }
+
+    /// Saves an invoice with its lines and marks the given logs as billed on it. Either
+    /// everything is saved or nothing is. A non-zero ID is kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `invoice` - The invoice to be saved
+    /// * `log_ids` - IDs of the logs billed on the invoice. Fails if any are already invoiced
+    fn save_invoice(&self, invoice: &Invoice, log_ids: &[u32]) -> Result<u32> {
+        self.change(|data| {
+            if data.invoices.values().any(|i| i.number == invoice.number) {
+                return Err(constraint("UNIQUE constraint failed: invoices.number"));
+            }
+            refers_to(&data.clients, Some(invoice.client_id))?;
+            let invoice_id = data.new_id(&data.invoices, "invoices", invoice.id)?;
+
+            let mut billed = HashSet::new();
+            for log_id in log_ids {
+                let uninvoiced = data
+                    .logs
+                    .get(log_id)
+                    .is_some_and(|(l, _)| l.invoice_id.is_none());
+                if !uninvoiced || !billed.insert(*log_id) {
+                    return Err(rusqlite::Error::QueryReturnedNoRows);
+                }
+            }
+
+            data.invoices.insert(
+                invoice_id,
+                Invoice {
+                    id: invoice_id,
+                    lines: Vec::new(),
+                    ..invoice.clone()
+                },
+            );
+            data.claim("invoices", invoice_id);
+            for line in &invoice.lines {
+                let id = data.new_id(&data.items, "invoice_lines", 0)?;
+                data.items.insert(
+                    id,
+                    Item {
+                        invoice_id,
+                        line: line.clone(),
+                    },
+                );
+                data.claim("invoice_lines", id);
+            }
+            for log_id in billed {
+                if let Some((log, _)) = data.logs.get_mut(&log_id) {
+                    log.invoice_id = Some(invoice_id);
+                }
+            }
+
+            Ok(invoice_id)
+        })
+    }
+
+    /// Whether a log was imported from the source before
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Tracker the log came from, e.g. `timewarrior`
+    /// * `external_id` - Identifies the log within the source
+    fn is_imported(&self, source: &str, external_id: &str) -> Result<bool> {
+        Ok(self
+            .data
+            .borrow()
+            .imports
+            .values()
+            .any(|i| i.source == source && i.external_id == external_id))
+    }
+
+    /// Saves a log copied from another time tracker, unless it was imported before. Returns
+    /// `None` for logs that were already imported, so importing the same data twice is safe.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Tracker the log came from, e.g. `timewarrior`
+    /// * `external_id` - Identifies the log within the source
+    /// * `log` - The log to be saved
+    fn import_log(&self, source: &str, external_id: &str, log: &Log) -> Result<Option<u32>> {
+        if self.is_imported(source, external_id)? {
+            return Ok(None);
+        }
+
+        self.change(|data| {
+            let log_id = self.insert_log(data, &log.proj_id, log)?;
+            let id = data.new_id(&data.imports, "imports", 0)?;
+            data.imports.insert(
+                id,
+                Import {
+                    source: source.to_owned(),
+                    external_id: external_id.to_owned(),
+                    log_id,
+                },
+            );
+            data.claim("imports", id);
+
+            Ok(Some(log_id))
+        })
+    }
+
+    /// Retrieve every invoice and its lines, oldest first
+    fn all_invoices(&self) -> Result<Vec<Invoice>> {
+        let data = self.data.borrow();
+        let mut invoices: Vec<Invoice> = data.invoices.values().cloned().collect();
+        for item in data.items.values() {
+            if let Some(invoice) = invoices.iter_mut().find(|i| i.id == item.invoice_id) {
+                invoice.lines.push(item.line.clone());
+            }
+        }
+
+        Ok(invoices)
+    }
+
+    /// Retrieve the source, external ID and log ID of every imported log
+    fn all_imports(&self) -> Result<Vec<(String, String, u32)>> {
+        let mut imports: Vec<(String, String, u32)> = self
+            .data
+            .borrow()
+            .imports
+            .values()
+            .map(|i| (i.source.clone(), i.external_id.clone(), i.log_id))
+            .collect();
+        imports.sort_by_key(|i| i.2);
+
+        Ok(imports)
+    }
+
+    /// Retrieve every log and its tags, in the order they were saved
+    fn all_logs(&self) -> Result<Vec<Log>> {
+        let data = self.data.borrow();
+        Ok(data.read_logs(data.logs.values()))
+    }
+
+    /// Deletes everything but the journal, leaving the files empty
+    fn clear(&self) -> Result<()> {
+        self.change(|data| {
+            *data = Data {
+                operations: std::mem::take(&mut data.operations),
+                changes: std::mem::take(&mut data.changes),
+                ..Data::default()
+            };
+            Ok(())
+        })
+    }
+
+    /// Starts recording every change made, until the operation is ended. Returns the ID of the
+    /// operation
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - Command line making the changes
+    fn begin_operation(&self, command: &str) -> Result<u32> {
+        let id = self.change(|data| {
+            let id = data.new_id(&data.operations, "operations", 0)?;
+            data.operations.insert(
+                id,
+                Operation {
+                    id,
+                    command: command.to_owned(),
+                    created: self.clock.now(),
+                    changes: 0,
+                    state: OperationState::Recording,
+                },
+            );
+            data.claim("operations", id);
+            Ok(id)
+        })?;
+
+        *self.recording.borrow_mut() = Some((id, self.data.borrow().clone()));
+        Ok(id)
+    }
+
+    /// Stops recording changes. Operations that changed nothing are forgotten, otherwise the
+    /// operation can be undone and operations undone before it can no longer be redone
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the operation to end
+    fn end_operation(&self, id: &u32) -> Result<()> {
+        let before = match self.recording.borrow_mut().take() {
+            Some((recording, before)) if recording == *id => Some(before),
+            _ => None,
+        };
+
+        self.change(|data| {
+            let changes = match before {
+                Some(before) => before.changes_to(data),
+                None => Vec::new(),
+            };
+            if changes.is_empty() {
+                data.operations.remove(id);
+                return Ok(());
+            }
+
+            let undone: HashSet<u32> = data
+                .operations
+                .values()
+                .filter(|o| o.state == OperationState::Undone)
+                .map(|o| o.id)
+                .collect();
+            data.operations.retain(|id, _| !undone.contains(id));
+            data.changes
+                .retain(|_, c| !undone.contains(&c.operation_id));
+
+            for (table, old, new) in changes {
+                let change_id = data.new_id(&data.changes, "operation_changes", 0)?;
+                data.changes.insert(
+                    change_id,
+                    Change {
+                        operation_id: *id,
+                        table: table.to_owned(),
+                        old,
+                        new,
+                    },
+                );
+                data.claim("operation_changes", change_id);
+            }
+            if let Some(operation) = data.operations.get_mut(id) {
+                operation.state = OperationState::Done;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Retrieve every operation in the journal, oldest first
+    fn all_operations(&self) -> Result<Vec<Operation>> {
+        let data = self.data.borrow();
+        let mut operations: Vec<Operation> = data.operations.values().cloned().collect();
+        for operation in &mut operations {
+            operation.changes = data
+                .changes
+                .values()
+                .filter(|c| c.operation_id == operation.id)
+                .count() as u32;
+        }
+
+        Ok(operations)
+    }
+
+    /// Reverts the most recent operation that has not been undone yet. Returns `None` when there
+    /// is nothing left to undo
+    fn undo(&self) -> Result<Option<Operation>> {
+        let Some(operation) = self.operation(OperationState::Done, true)? else {
+            return Ok(None);
+        };
+
+        self.replay(&operation, true)?;
+        Ok(Some(operation))
+    }
+
+    /// Replays the operation undone last. Returns `None` when there is nothing to redo
+    fn redo(&self) -> Result<Option<Operation>> {
+        let Some(operation) = self.operation(OperationState::Undone, false)? else {
+            return Ok(None);
+        };
+
+        self.replay(&operation, false)?;
+        Ok(Some(operation))
+    }
+
+    /// Retrieve the revisions of a log, oldest first. Each holds what the log said before a change
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID of the log
+    fn log_history(&self, id: &u32) -> Result<Vec<LogRevision>> {
+        Ok(self
+            .data
+            .borrow()
+            .revisions
+            .values()
+            .filter(|r| r.log_id == *id)
+            .cloned()
+            .collect())
+    }
+
+    /// Retrieve the revisions of every log, oldest first
+    fn all_log_revisions(&self) -> Result<Vec<LogRevision>> {
+        Ok(self.data.borrow().revisions.values().cloned().collect())
+    }
+
+    /// Saves a revision recorded before, such as one restored from a backup. A non-zero ID is
+    /// kept, otherwise one is generated
+    ///
+    /// # Arguments
+    ///
+    /// * `revision` - The revision to be saved
+    fn save_log_revision(&self, revision: &LogRevision) -> Result<u32> {
+        self.change(|data| {
+            refers_to(&data.logs, Some(revision.log_id))?;
+
+            let id = data.new_id(&data.revisions, "log_revisions", revision.id)?;
+            let mut saved = revision.clone();
+            saved.id = id;
+            data.revisions.insert(id, saved);
+            data.claim("log_revisions", id);
+            Ok(id)
+        })
+    }
+
+    /// Retrieve every log and its tags with the hash sealing it, in the order they were saved
+    fn log_chain(&self) -> Result<Vec<Sealed>> {
+        let data = self.data.borrow();
+        let logs = data.read_logs(data.logs.values());
+        let hashes = data.logs.values().map(|(_, hash)| hash.clone());
+
+        Ok(logs.into_iter().zip(hashes).collect())
+    }
+
+    /// Seals the logs saved before the hash chain was turned on, and those saved while it was
+    /// off. Logs that no longer match their hash are left alone. Returns the number of logs
+    /// sealed
+    fn seal_logs(&self) -> Result<usize> {
+        if !self.chained {
+            return Ok(0);
+        }
+
+        self.change(|data| {
+            let mut logs = self.intact_logs(data, 0);
+            let unsealed: Vec<u32> = data
+                .logs
+                .values()
+                .filter(|(_, hash)| hash.is_none())
+                .map(|(log, _)| log.id)
+                .collect();
+            logs.extend(&unsealed);
+
+            self.seal(data, 0, &logs);
+            Ok(unsealed.len())
+        })
+    }
+
+    /// Starts a transaction. Until it is committed, changes are kept in memory and not written to
+    /// the files. Transactions started inside another are committed with it
+    fn begin_transaction(&self) -> Result<()> {
+        let data = self.data.borrow().clone();
+        self.transactions.borrow_mut().push(data);
+        Ok(())
+    }
+
+    /// Keeps the changes made since the most recent transaction began
+    fn commit_transaction(&self) -> Result<()> {
+        self.transactions
+            .borrow_mut()
+            .pop()
+            .ok_or_else(no_transaction)?;
+        self.flush()
+    }
+
+    /// Undoes the changes made since the most recent transaction began, and ends it
+    fn rollback_transaction(&self) -> Result<()> {
+        let data = self
+            .transactions
+            .borrow_mut()
+            .pop()
+            .ok_or_else(no_transaction)?;
+        *self.data.borrow_mut() = data;
+        self.flush()
+    }
+}
+
+/// Adds the records in a file to the table, if the file exists
+///
+/// # Arguments
+///
+/// * `dir` - Directory the file is in
+/// * `file` - Path of the file within `dir`
+/// * `table` - Table to add the records to
+/// * `written` - Filled with the contents of the file
+fn read<R: Record>(
+    dir: &Path,
+    file: impl AsRef<Path>,
+    table: &mut Table<R>,
+    written: &mut BTreeMap<PathBuf, String>,
+) -> Result<(), String> {
+    let file = file.as_ref();
+    let text = match fs::read_to_string(dir.join(file)) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("{}: {}", file.display(), e)),
+    };
+
+    records::load(&text, table).map_err(|e| format!("{}: {}", file.display(), e))?;
+    written.insert(file.to_owned(), text);
+    Ok(())
+}
+
+fn images<R: Record>(table: &Table<R>) -> BTreeMap<u32, String> {
+    table
+        .iter()
+        .map(|(id, record)| (*id, records::line(*id, record)))
+        .collect()
+}
+
+fn put<R: Record>(
+    table: &mut Table<R>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<(), String> {
+    match (from, to) {
+        (_, Some(to)) => {
+            let (id, record) = records::parse(to)?;
+            table.insert(id, record);
+        }
+        (Some(from), None) => {
+            let (id, _) = records::parse::<R>(from)?;
+            table.remove(&id);
+        }
+        (None, None) => {}
+    }
+    Ok(())
+}
+
+/// Fails unless the table has a record with the ID, or no ID is given
+fn refers_to<R>(table: &Table<R>, id: Option<u32>) -> Result<()> {
+    match id {
+        Some(id) if !table.contains_key(&id) => Err(constraint("FOREIGN KEY constraint failed")),
+        _ => Ok(()),
+    }
+}
+
+/// The tags without repeats, in the order they were first given
+fn unique(tags: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.iter().filter(|t| seen.insert(*t)).cloned().collect()
+}
+
+/// Refuses logs that end before they start, as the SQLite repository does
+fn in_order(log: &Log) -> Result<()> {
+    match log.end < log.start {
+        true => Err(constraint("log ends before it starts")),
+        false => Ok(()),
+    }
+}
+
+fn corrupt(message: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(ffi::Error::new(ffi::SQLITE_CORRUPT), Some(message))
+}
+
+fn io_error(file: &Path, e: io::Error) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        ffi::Error::new(ffi::SQLITE_IOERR),
+        Some(format!("{}: {}", file.display(), e)),
+    )
+}
+
+fn no_transaction() -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        ffi::Error::new(ffi::SQLITE_ERROR),
+        Some("no transaction is open".to_owned()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{repository::conformance::repository_tests, time::to_datetime};
+
+    use super::*;
+
+    repository_tests!(|clock| Text::temporary().unwrap().with_clock(clock));
+
+    fn test_log(project_id: u32, start: i64) -> Log {
+        Log::new(
+            0,
+            project_id,
+            "work".to_owned(),
+            to_datetime(start),
+            to_datetime(start + 3600),
+        )
+    }
+
+    #[test]
+    fn logs_should_be_written_to_a_file_per_month() {
+        // Arrange
+        let repo = Text::temporary().unwrap();
+        let project_id = repo
+            .save_project(&Project::new(
+                "web".to_owned(),
+                None,
+                Local::now(),
+                Local::now(),
+            ))
+            .unwrap();
+
+        // Act
+        for start in [1_790_000_000, 1_790_100_000, 1_795_000_000] {
+            repo.save_log(&project_id, &test_log(project_id, start))
+                .unwrap();
+        }
+
+        // Assert
+        let mut months: Vec<String> = fs::read_dir(repo.dir.join("logs"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        months.sort();
+        let expected: BTreeSet<String> = [1_790_000_000, 1_795_000_000]
+            .iter()
+            .map(|s| to_datetime(*s).format("%Y-%m.txt").to_string())
+            .collect();
+        assert_eq!(expected.into_iter().collect::<Vec<_>>(), months);
+    }
+
+    #[test]
+    fn open_should_read_back_what_was_saved() {
+        // Arrange
+        let repo = Text::temporary().unwrap();
+        let mut project = Project::new(
+            "web\tsite".to_owned(),
+            Some(String::new()),
+            Local::now(),
+            Local::now(),
+        );
+        project.id = repo.save_project(&project).unwrap();
+        let mut log = test_log(project.id, 1_790_000_000);
+        log.message = "line one\nline two".to_owned();
+        log.tags = vec!["a,b".to_owned()];
+        repo.save_log(&project.id, &log).unwrap();
+
+        // Act
+        let reopened = Text::open(&repo.dir, false).unwrap();
+
+        // Assert
+        let (actual, logs) = reopened.get_project(&project.id).unwrap();
+        assert_eq!("web\tsite", actual.name);
+        assert_eq!(Some(String::new()), actual.description);
+        assert_eq!("line one\nline two", logs[0].message);
+        assert_eq!(vec!["a,b".to_owned()], logs[0].tags);
+    }
+
+    #[test]
+    fn changes_made_by_arrow_should_keep_chain_intact() {
+        // Arrange
+        let mut repo = Text::temporary().unwrap();
+        repo.chained = true;
+        let project_id = repo
+            .save_project(&Project::new(
+                "web".to_owned(),
+                None,
+                Local::now(),
+                Local::now(),
+            ))
+            .unwrap();
+        let mut logs: Vec<Log> = (0..4)
+            .map(|i| {
+                let mut log = test_log(project_id, i * 3600);
+                log.id = repo.save_log(&project_id, &log).unwrap();
+                log
+            })
+            .collect();
+
+        // Act
+        logs[1].message = "changed".to_owned();
+        repo.update_log(&logs[1]).unwrap();
+        repo.delete_log(&project_id, &logs[2].id).unwrap();
+
+        // Assert
+        let report = chain::verify(&repo.log_chain().unwrap());
+        assert_eq!((0, 3), (report.unsealed, report.sealed));
+        assert!(report.breaks.is_empty());
+    }
+
+    #[test]
+    fn changes_should_only_be_written_when_transaction_commits() {
+        // Arrange
+        let repo = Text::temporary().unwrap();
+        repo.begin_transaction().unwrap();
+
+        // Act
+        repo.save_client(&Client::new(
+            "acme".to_owned(),
+            None,
+            "AUD".to_owned(),
+            None,
+            None,
+        ))
+        .unwrap();
+        let before_commit = Text::open(&repo.dir, false).unwrap().all_clients().unwrap();
+        repo.commit_transaction().unwrap();
+
+        // Assert
+        let after_commit = Text::open(&repo.dir, false).unwrap().all_clients().unwrap();
+        assert!(before_commit.is_empty());
+        assert_eq!(1, after_commit.len());
+    }
+}
@@ -0,0 +1,673 @@
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Duration, Local, SecondsFormat, SubsecRound};
+use rust_decimal::Decimal;
+
+use crate::{
+    integrity::chain::Sealed,
+    model::{
+        Budget, BudgetPeriod, Client, Goal, GoalPeriod, GoalScope, Invoice, InvoiceLine, Log,
+        LogRevision, Operation, OperationState, Project, Rate, RateScope,
+    },
+};
+
+/// Records of one kind, by ID
+pub type Table<R> = BTreeMap<u32, R>;
+
+/// Stands for a missing value, so it can be told apart from an empty one
+const NULL: &str = "\\N";
+
+/// Something stored as a line of tab separated fields, after its ID
+pub trait Record: Clone {
+    /// Names of the fields after the ID, written in the header of the file
+    const COLUMNS: &'static [&'static str];
+
+    fn write(&self, line: &mut Line);
+    fn read(id: u32, fields: &mut Fields) -> Result<Self, String>;
+}
+
+/// An alternative name a project can be referred to by
+#[derive(Clone)]
+pub struct Alias {
+    pub alias: String,
+    pub project_id: u32,
+}
+
+/// A log copied from another time tracker
+#[derive(Clone)]
+pub struct Import {
+    pub source: String,
+    pub external_id: String,
+    pub log_id: u32,
+}
+
+/// A line of an invoice
+#[derive(Clone)]
+pub struct Item {
+    pub invoice_id: u32,
+    pub line: InvoiceLine,
+}
+
+/// A record an operation inserted, updated or deleted, as the line it was stored as before and
+/// after
+#[derive(Clone)]
+pub struct Change {
+    pub operation_id: u32,
+    pub table: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Fields of a line being written
+#[derive(Default)]
+pub struct Line(Vec<String>);
+
+impl Line {
+    pub fn text(&mut self, value: &str) -> &mut Self {
+        self.0.push(escape(value));
+        self
+    }
+
+    pub fn opt_text(&mut self, value: Option<&str>) -> &mut Self {
+        match value {
+            Some(value) => self.text(value),
+            None => self.null(),
+        }
+    }
+
+    pub fn value(&mut self, value: impl Display) -> &mut Self {
+        self.text(&value.to_string())
+    }
+
+    pub fn opt_value(&mut self, value: Option<impl Display>) -> &mut Self {
+        match value {
+            Some(value) => self.value(value),
+            None => self.null(),
+        }
+    }
+
+    /// Times to the millisecond. Whole seconds are written without a fraction
+    pub fn time(&mut self, value: &DateTime<Local>) -> &mut Self {
+        self.text(
+            &value
+                .trunc_subsecs(3)
+                .to_rfc3339_opts(SecondsFormat::AutoSi, false),
+        )
+    }
+
+    /// Values separated by commas. Commas within values are escaped
+    pub fn list(&mut self, values: &[String]) -> &mut Self {
+        let values: Vec<String> = values
+            .iter()
+            .map(|v| match v.is_empty() {
+                true => "\\e".to_owned(),
+                false => escape(v).replace(',', "\\,"),
+            })
+            .collect();
+        self.0.push(values.join(","));
+        self
+    }
+
+    fn null(&mut self) -> &mut Self {
+        self.0.push(NULL.to_owned());
+        self
+    }
+}
+
+/// Fields of a line being read, in order
+pub struct Fields<'a> {
+    fields: std::str::Split<'a, char>,
+    column: usize,
+}
+
+impl<'a> Fields<'a> {
+    fn raw(&mut self) -> Result<&'a str, String> {
+        self.column += 1;
+        self.fields
+            .next()
+            .ok_or_else(|| format!("column {} is missing", self.column))
+    }
+
+    pub fn opt_text(&mut self) -> Result<Option<String>, String> {
+        match self.raw()? {
+            NULL => Ok(None),
+            raw => Ok(Some(unescape(raw))),
+        }
+    }
+
+    pub fn text(&mut self) -> Result<String, String> {
+        self.opt_text()?
+            .ok_or_else(|| format!("column {} cannot be empty", self.column))
+    }
+
+    pub fn opt_value<T: FromStr>(&mut self) -> Result<Option<T>, String>
+    where
+        T::Err: Display,
+    {
+        match self.opt_text()? {
+            Some(text) => text
+                .parse()
+                .map(Some)
+                .map_err(|e| format!("column {}: {}", self.column, e)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn value<T: FromStr>(&mut self) -> Result<T, String>
+    where
+        T::Err: Display,
+    {
+        self.opt_value()?
+            .ok_or_else(|| format!("column {} cannot be empty", self.column))
+    }
+
+    pub fn time(&mut self) -> Result<DateTime<Local>, String> {
+        let text = self.text()?;
+        DateTime::parse_from_rfc3339(&text)
+            .map(|dt| dt.with_timezone(&Local))
+            .map_err(|e| format!("column {}: {} is not a time: {}", self.column, text, e))
+    }
+
+    pub fn list(&mut self) -> Result<Vec<String>, String> {
+        let raw = self.raw()?;
+        if raw.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut values = Vec::new();
+        let mut value = String::new();
+        let mut chars = raw.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    value.push(c);
+                    value.extend(chars.next());
+                }
+                ',' => values.push(unescape(&std::mem::take(&mut value))),
+                c => value.push(c),
+            }
+        }
+        values.push(unescape(&value));
+
+        Ok(values)
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('t') => unescaped.push('\t'),
+                Some('n') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                Some('e') | None => {}
+                Some(c) => unescaped.push(c),
+            },
+            (c, false) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// The record as it is stored, without a line break
+pub fn line<R: Record>(id: u32, record: &R) -> String {
+    let mut line = Line::default();
+    line.value(id);
+    record.write(&mut line);
+    line.0.join("\t")
+}
+
+/// Reads a record stored with `line`
+pub fn parse<R: Record>(line: &str) -> Result<(u32, R), String> {
+    let mut fields = Fields {
+        fields: line.split('\t'),
+        column: 0,
+    };
+    let id = fields.value()?;
+    let record = R::read(id, &mut fields)?;
+    match fields.fields.next() {
+        Some(_) => Err(format!("expected {} columns", R::COLUMNS.len() + 1)),
+        None => Ok((id, record)),
+    }
+}
+
+/// The records as the contents of a file, with a header naming the columns
+pub fn render<'a, R: Record + 'a>(records: impl IntoIterator<Item = (&'a u32, &'a R)>) -> String {
+    let mut text = format!("# id\t{}\n", R::COLUMNS.join("\t"));
+    for (id, record) in records {
+        text.push_str(&line(*id, record));
+        text.push('\n');
+    }
+    text
+}
+
+/// Adds the records in the contents of a file to the table. Blank lines and lines starting
+/// with `#` are skipped. Errors name the line they were found on
+pub fn load<R: Record>(text: &str, table: &mut Table<R>) -> Result<(), String> {
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (id, record) = parse(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        if table.insert(id, record).is_some() {
+            return Err(format!("line {}: ID {} is used twice", i + 1, id));
+        }
+    }
+    Ok(())
+}
+
+impl Record for Project {
+    const COLUMNS: &'static [&'static str] = &[
+        "name",
+        "description",
+        "created",
+        "updated",
+        "duration ms",
+        "parent",
+        "client",
+        "budget",
+        "budget period",
+    ];
+
+    fn write(&self, line: &mut Line) {
+        line.text(&self.name)
+            .opt_text(self.description.as_deref())
+            .time(&self.created)
+            .time(&self.updated)
+            .value(self.duration.num_milliseconds())
+            .opt_value(self.parent_id)
+            .opt_value(self.client_id)
+            .opt_value(self.budget.map(|b| b.seconds))
+            .opt_text(self.budget.and_then(|b| b.period).map(|p| match p {
+                BudgetPeriod::Week => "week",
+                BudgetPeriod::Month => "month",
+            }));
+    }
+
+    fn read(id: u32, fields: &mut Fields) -> Result<Self, String> {
+        let mut project = Project::load(
+            id,
+            fields.text()?,
+            fields.opt_text()?,
+            fields.time()?,
+            fields.time()?,
+            Duration::milliseconds(fields.value()?),
+        );
+        project.parent_id = fields.opt_value()?;
+        project.client_id = fields.opt_value()?;
+        let seconds = fields.opt_value()?;
+        let period = match fields.opt_text()?.as_deref() {
+            Some("week") => Some(BudgetPeriod::Week),
+            Some("month") => Some(BudgetPeriod::Month),
+            None => None,
+            Some(other) => return Err(format!("{} is not a budget period", other)),
+        };
+        project.budget = seconds.map(|seconds| Budget { seconds, period });
+
+        Ok(project)
+    }
+}
+
+impl Record for Alias {
+    const COLUMNS: &'static [&'static str] = &["alias", "project"];
+
+    fn write(&self, line: &mut Line) {
+        line.text(&self.alias).value(self.project_id);
+    }
+
+    fn read(_: u32, fields: &mut Fields) -> Result<Self, String> {
+        Ok(Alias {
+            alias: fields.text()?,
+            project_id: fields.value()?,
+        })
+    }
+}
+
+impl Record for Client {
+    const COLUMNS: &'static [&'static str] =
+        &["name", "contact", "currency", "hourly rate", "address"];
+
+    fn write(&self, line: &mut Line) {
+        line.text(&self.name)
+            .opt_text(self.contact.as_deref())
+            .text(&self.currency)
+            .opt_value(self.hourly_rate)
+            .opt_text(self.address.as_deref());
+    }
+
+    fn read(id: u32, fields: &mut Fields) -> Result<Self, String> {
+        Ok(Client {
+            id,
+            name: fields.text()?,
+            contact: fields.opt_text()?,
+            currency: fields.text()?,
+            hourly_rate: fields.opt_value()?,
+            address: fields.opt_text()?,
+        })
+    }
+}
+
+impl Record for Rate {
+    const COLUMNS: &'static [&'static str] = &["client", "project", "tag", "amount", "from"];
+
+    fn write(&self, line: &mut Line) {
+        match &self.scope {
+            RateScope::Client(id) => line.value(id).null().null(),
+            RateScope::Project(id) => line.null().value(id).null(),
+            RateScope::Tag(tag) => line.null().null().text(tag),
+        };
+        line.value(self.amount).time(&self.effective_from);
+    }
+
+    fn read(id: u32, fields: &mut Fields) -> Result<Self, String> {
+        let scope = match (fields.opt_value()?, fields.opt_value()?, fields.opt_text()?) {
+            (Some(client_id), None, None) => RateScope::Client(client_id),
+            (None, Some(project_id), None) => RateScope::Project(project_id),
+            (None, None, Some(tag)) => RateScope::Tag(tag),
+            _ => return Err("a rate needs exactly one of client, project or tag".to_owned()),
+        };
+
+        Ok(Rate {
+            id,
+            scope,
+            amount: fields.value::<Decimal>()?,
+            effective_from: fields.time()?,
+        })
+    }
+}
+
+impl Record for Goal {
+    const COLUMNS: &'static [&'static str] = &["project", "tag", "seconds", "period"];
+
+    fn write(&self, line: &mut Line) {
+        match &self.scope {
+            GoalScope::Overall => line.null().null(),
+            GoalScope::Project(id) => line.value(id).null(),
+            GoalScope::Tag(tag) => line.null().text(tag),
+        };
+        line.value(self.seconds).text(match self.period {
+            GoalPeriod::Day => "day",
+            GoalPeriod::Weekday => "weekday",
+            GoalPeriod::Week => "week",
+        });
+    }
+
+    fn read(id: u32, fields: &mut Fields) -> Result<Self, String> {
+        let scope = match (fields.opt_value()?, fields.opt_text()?) {
+            (None, None) => GoalScope::Overall,
+            (Some(project_id), None) => GoalScope::Project(project_id),
+            (None, Some(tag)) => GoalScope::Tag(tag),
+            _ => return Err("a goal needs at most one of project or tag".to_owned()),
+        };
+        let seconds = fields.value()?;
+        let period = match fields.text()?.as_str() {
+            "day" => GoalPeriod::Day,
+            "weekday" => GoalPeriod::Weekday,
+            "week" => GoalPeriod::Week,
+            other => return Err(format!("{} is not a goal period", other)),
+        };
+
+        Ok(Goal {
+            id,
+            scope,
+            seconds,
+            period,
+        })
+    }
+}
+
+impl Record for Sealed {
+    const COLUMNS: &'static [&'static str] = &[
+        "project", "start", "end", "billable", "invoice", "zone", "hash", "tags", "message",
+    ];
+
+    fn write(&self, line: &mut Line) {
+        let (log, hash) = self;
+        line.value(log.proj_id)
+            .time(&log.start)
+            .time(&log.end)
+            .value(log.billable)
+            .opt_value(log.invoice_id)
+            .opt_value(log.zone)
+            .opt_text(hash.as_deref())
+            .list(&log.tags)
+            .text(&log.message);
+    }
+
+    fn read(id: u32, fields: &mut Fields) -> Result<Self, String> {
+        let proj_id = fields.value()?;
+        let start = fields.time()?;
+        let end = fields.time()?;
+        let billable = fields.value()?;
+        let invoice_id = fields.opt_value()?;
+        let zone = fields.opt_value()?;
+        let hash = fields.opt_text()?;
+        let tags = fields.list()?;
+
+        let mut log = Log::new(id, proj_id, fields.text()?, start, end);
+        log.billable = billable;
+        log.invoice_id = invoice_id;
+        log.zone = zone;
+        log.tags = tags;
+
+        Ok((log, hash))
+    }
+}
+
+impl Record for Invoice {
+    const COLUMNS: &'static [&'static str] = &[
+        "number", "client", "from", "to", "created", "currency", "total",
+    ];
+
+    fn write(&self, line: &mut Line) {
+        line.text(&self.number)
+            .value(self.client_id)
+            .time(&self.period_start)
+            .time(&self.period_end)
+            .time(&self.created)
+            .text(&self.currency)
+            .value(self.total);
+    }
+
+    fn read(id: u32, fields: &mut Fields) -> Result<Self, String> {
+        Ok(Invoice {
+            id,
+            number: fields.text()?,
+            client_id: fields.value()?,
+            period_start: fields.time()?,
+            period_end: fields.time()?,
+            created: fields.time()?,
+            currency: fields.text()?,
+            lines: Vec::new(),
+            total: fields.value()?,
+        })
+    }
+}
+
+impl Record for Item {
+    const COLUMNS: &'static [&'static str] = &[
+        "invoice",
+        "raw seconds",
+        "seconds",
+        "rate",
+        "amount",
+        "description",
+    ];
+
+    fn write(&self, line: &mut Line) {
+        line.value(self.invoice_id)
+            .value(self.line.raw_seconds)
+            .value(self.line.seconds)
+            .value(self.line.rate)
+            .value(self.line.amount)
+            .text(&self.line.description);
+    }
+
+    fn read(_: u32, fields: &mut Fields) -> Result<Self, String> {
+        Ok(Item {
+            invoice_id: fields.value()?,
+            line: InvoiceLine {
+                raw_seconds: fields.value()?,
+                seconds: fields.value()?,
+                rate: fields.value()?,
+                amount: fields.value()?,
+                description: fields.text()?,
+            },
+        })
+    }
+}
+
+impl Record for Import {
+    const COLUMNS: &'static [&'static str] = &["source", "external id", "log"];
+
+    fn write(&self, line: &mut Line) {
+        line.text(&self.source)
+            .text(&self.external_id)
+            .value(self.log_id);
+    }
+
+    fn read(_: u32, fields: &mut Fields) -> Result<Self, String> {
+        Ok(Import {
+            source: fields.text()?,
+            external_id: fields.text()?,
+            log_id: fields.value()?,
+        })
+    }
+}
+
+impl Record for LogRevision {
+    const COLUMNS: &'static [&'static str] =
+        &["log", "changed", "changed by", "start", "end", "message"];
+
+    fn write(&self, line: &mut Line) {
+        line.value(self.log_id)
+            .time(&self.changed)
+            .text(&self.changed_by)
+            .time(&self.start)
+            .time(&self.end)
+            .text(&self.message);
+    }
+
+    fn read(id: u32, fields: &mut Fields) -> Result<Self, String> {
+        Ok(LogRevision {
+            id,
+            log_id: fields.value()?,
+            changed: fields.time()?,
+            changed_by: fields.text()?,
+            start: fields.time()?,
+            end: fields.time()?,
+            message: fields.text()?,
+        })
+    }
+}
+
+impl Record for Operation {
+    const COLUMNS: &'static [&'static str] = &["created", "state", "command"];
+
+    fn write(&self, line: &mut Line) {
+        line.time(&self.created)
+            .text(match self.state {
+                OperationState::Recording => "recording",
+                OperationState::Done => "done",
+                OperationState::Undone => "undone",
+            })
+            .text(&self.command);
+    }
+
+    fn read(id: u32, fields: &mut Fields) -> Result<Self, String> {
+        let created = fields.time()?;
+        let state = match fields.text()?.as_str() {
+            "recording" => OperationState::Recording,
+            "done" => OperationState::Done,
+            "undone" => OperationState::Undone,
+            other => return Err(format!("{} is not an operation state", other)),
+        };
+
+        Ok(Operation {
+            id,
+            command: fields.text()?,
+            created,
+            changes: 0,
+            state,
+        })
+    }
+}
+
+impl Record for Change {
+    const COLUMNS: &'static [&'static str] = &["operation", "table", "old", "new"];
+
+    fn write(&self, line: &mut Line) {
+        line.value(self.operation_id)
+            .text(&self.table)
+            .opt_text(self.old.as_deref())
+            .opt_text(self.new.as_deref());
+    }
+
+    fn read(_: u32, fields: &mut Fields) -> Result<Self, String> {
+        Ok(Change {
+            operation_id: fields.value()?,
+            table: fields.text()?,
+            old: fields.opt_text()?,
+            new: fields.opt_text()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_should_read_back_escaped_values() {
+        // Arrange
+        let mut log = Log::new(
+            7,
+            1,
+            "tabs\tnew\nlines \\N and, commas".to_owned(),
+            Local::now(),
+            Local::now(),
+        );
+        log.tags = vec!["a,b".to_owned(), String::new(), "\\".to_owned()];
+        let sealed: Sealed = (log, None);
+
+        // Act
+        let text = line(7, &sealed);
+        let (id, (actual, hash)) = parse::<Sealed>(&text).unwrap();
+
+        // Assert
+        assert!(!text.contains('\n'));
+        assert_eq!(7, id);
+        assert_eq!(sealed.0.message, actual.message);
+        assert_eq!(sealed.0.tags, actual.tags);
+        assert_eq!(None, hash);
+    }
+
+    #[test]
+    fn load_should_name_line_with_error() {
+        // Arrange
+        let text = "# id\talias\tproject\n1\tweb\t1\n2\tsite\tone\n";
+
+        // Act
+        let res = load::<Alias>(text, &mut Table::new());
+
+        // Assert
+        assert!(res.unwrap_err().starts_with("line 3: column 3"));
+    }
+}
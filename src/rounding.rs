@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+
+use serde::{de, Deserialize, Deserializer};
+
+use crate::model::Log;
+
+/// Which way durations are rounded to the increment
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+    #[default]
+    Nearest,
+}
+
+/// What gets rounded: each log on its own, or the total of each day
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    #[default]
+    Entry,
+    Day,
+}
+
+/// How billed time is rounded, e.g. up to the next 15 minutes per log. Rounding is only applied
+/// when reporting and invoicing, stored logs keep their exact times.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RoundingRule {
+    /// Minutes to round to, e.g. 6 or 15, up to a day
+    #[serde(deserialize_with = "increment")]
+    pub increment: u32,
+    #[serde(default)]
+    pub direction: Direction,
+    #[serde(default)]
+    pub scope: Scope,
+    /// Fewest minutes billed for any log or day with time on it, up to a day
+    #[serde(default, deserialize_with = "minimum")]
+    pub minimum: u32,
+}
+
+/// Most minutes an increment or minimum can be
+const DAY_MINUTES: u32 = 24 * 60;
+
+fn increment<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    match u32::deserialize(deserializer)? {
+        minutes @ 1..=DAY_MINUTES => Ok(minutes),
+        minutes => Err(de::Error::custom(format!(
+            "increment of {} minutes is not between 1 and {}",
+            minutes, DAY_MINUTES
+        ))),
+    }
+}
+
+fn minimum<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    match u32::deserialize(deserializer)? {
+        minutes @ 0..=DAY_MINUTES => Ok(minutes),
+        minutes => Err(de::Error::custom(format!(
+            "minimum of {} minutes is more than {}",
+            minutes, DAY_MINUTES
+        ))),
+    }
+}
+
+impl RoundingRule {
+    /// Rounds a duration in seconds. Nothing is billed for no time at all, otherwise at least
+    /// the minimum is billed. Durations that would round past the longest that can be counted
+    /// are cut short there.
+    pub fn round(&self, seconds: u32) -> u32 {
+        if seconds == 0 {
+            return 0;
+        }
+
+        let seconds = u64::from(seconds);
+        let step = u64::from(self.increment.max(1)) * 60;
+        let rounded = match self.direction {
+            Direction::Up => seconds.div_ceil(step) * step,
+            Direction::Down => seconds / step * step,
+            Direction::Nearest => (seconds + step / 2) / step * step,
+        };
+
+        let rounded = rounded.max(u64::from(self.minimum) * 60);
+        u32::try_from(rounded).unwrap_or(u32::MAX)
+    }
+
+    /// Rounded duration of logs billed together, such as the logs of one invoice line.
+    pub fn apply(&self, logs: &[&Log]) -> u32 {
+        match self.scope {
+            Scope::Entry => total(logs.iter().map(|l| self.round(l.seconds()))),
+            Scope::Day => {
+                let mut days: BTreeMap<_, u32> = BTreeMap::new();
+                for log in logs {
+                    let day = days.entry(log.start.date_naive()).or_default();
+                    *day = day.saturating_add(log.seconds());
+                }
+                total(days.into_values().map(|s| self.round(s)))
+            }
+        }
+    }
+}
+
+/// Sum of durations in seconds, cut short at the longest that can be counted
+fn total(seconds: impl Iterator<Item = u32>) -> u32 {
+    seconds.fold(0, u32::saturating_add)
+}
+
+/// Rounded duration of logs billed together, or their exact duration without a rule.
+pub fn rounded(rule: Option<&RoundingRule>, logs: &[&Log]) -> u32 {
+    match rule {
+        Some(rule) => rule.apply(logs),
+        None => total(logs.iter().map(|l| l.seconds())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use crate::time::parse_datetime;
+
+    use super::*;
+
+    fn rule(increment: u32, direction: Direction, scope: Scope, minimum: u32) -> RoundingRule {
+        RoundingRule {
+            increment,
+            direction,
+            scope,
+            minimum,
+        }
+    }
+
+    fn log(start: &str, minutes: i64) -> Log {
+        let start = parse_datetime(start).unwrap();
+        Log::new(
+            0,
+            1,
+            "work".to_owned(),
+            start,
+            start + Duration::minutes(minutes),
+        )
+    }
+
+    #[test]
+    fn round_follows_direction() {
+        let seconds = 22 * 60;
+
+        assert_eq!(
+            30 * 60,
+            rule(15, Direction::Up, Scope::Entry, 0).round(seconds)
+        );
+        assert_eq!(
+            15 * 60,
+            rule(15, Direction::Down, Scope::Entry, 0).round(seconds)
+        );
+        assert_eq!(
+            24 * 60,
+            rule(6, Direction::Nearest, Scope::Entry, 0).round(seconds)
+        );
+    }
+
+    #[test]
+    fn round_applies_minimum_but_not_to_nothing() {
+        let rule = rule(15, Direction::Down, Scope::Entry, 30);
+
+        assert_eq!(30 * 60, rule.round(60));
+        assert_eq!(0, rule.round(0));
+    }
+
+    #[test]
+    fn apply_per_entry_rounds_each_log() {
+        // Arrange
+        let logs = [log("2026-09-14 09:00", 5), log("2026-09-14 10:00", 5)];
+        let logs: Vec<&Log> = logs.iter().collect();
+
+        // Act
+        let res = rule(15, Direction::Up, Scope::Entry, 0).apply(&logs);
+
+        // Assert
+        assert_eq!(30 * 60, res);
+    }
+
+    #[test]
+    fn apply_per_day_rounds_daily_totals() {
+        // Arrange
+        let logs = [
+            log("2026-09-14 09:00", 5),
+            log("2026-09-14 10:00", 5),
+            log("2026-09-15 09:00", 20),
+        ];
+        let logs: Vec<&Log> = logs.iter().collect();
+
+        // Act
+        let res = rule(15, Direction::Up, Scope::Day, 0).apply(&logs);
+
+        // Assert
+        assert_eq!(45 * 60, res);
+    }
+
+    #[test]
+    fn rounded_without_rule_is_exact() {
+        let logs = [log("2026-09-14 09:00", 7)];
+
+        assert_eq!(7 * 60, rounded(None, &logs.iter().collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn round_near_longest_duration_does_not_overflow() {
+        let longest = u32::MAX;
+
+        assert_eq!(
+            u32::MAX,
+            rule(DAY_MINUTES, Direction::Up, Scope::Entry, 0).round(longest)
+        );
+        assert_eq!(
+            u32::MAX,
+            rule(1000, Direction::Nearest, Scope::Entry, 0).round(longest)
+        );
+        assert_eq!(
+            longest / 86_400 * 86_400,
+            rule(DAY_MINUTES, Direction::Down, Scope::Entry, DAY_MINUTES).round(longest)
+        );
+    }
+
+    #[test]
+    fn apply_cuts_totals_short_instead_of_overflowing() {
+        // Arrange
+        let logs = [
+            log("2026-09-14 09:00", 50_000_000),
+            log("2026-09-14 09:00", 50_000_000),
+        ];
+        let logs: Vec<&Log> = logs.iter().collect();
+
+        // Act
+        let entry = rule(15, Direction::Up, Scope::Entry, 0).apply(&logs);
+        let day = rule(15, Direction::Up, Scope::Day, 0).apply(&logs);
+
+        // Assert
+        assert_eq!(u32::MAX, entry);
+        assert_eq!(u32::MAX, day);
+        assert_eq!(u32::MAX, rounded(None, &logs));
+    }
+}
@@ -1,13 +1,226 @@
-use chrono::{DateTime, Local, TimeZone};
+use chrono::{
+    DateTime, Duration, Local, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+};
 
-/// Returns corresponding local datetime from a given timestamp in seconds.
+pub mod zone;
+
+/// Returns corresponding local datetime from a given timestamp in seconds. Timestamps are
+/// instants, so unlike local times they are never ambiguous or skipped when clocks change.
 pub fn to_datetime(timestamp: i64) -> DateTime<Local> {
-    Local
-        .timestamp_opt(timestamp, 0)
-        .single()
+    DateTime::from_timestamp(timestamp, 0)
+        .expect("Failed to read timestamp")
+        .with_timezone(&Local)
+}
+
+/// Returns corresponding local datetime from a given timestamp in milliseconds, as the start and
+/// end of logs are stored.
+pub fn from_millis(timestamp: i64) -> DateTime<Local> {
+    DateTime::from_timestamp_millis(timestamp)
         .expect("Failed to read timestamp")
+        .with_timezone(&Local)
+}
+
+/// Whole seconds in a duration, dropping any fraction of a second. Negative durations are none,
+/// and durations too long to count in seconds are cut short at the longest that can be.
+pub fn whole_seconds(duration: Duration) -> u32 {
+    duration.num_seconds().clamp(0, u32::MAX as i64) as u32
 }
 
 pub fn duration_hours(duration: u32) -> String {
     format!("{0:.2}", duration as f64 / 60.0 / 60.0)
-}
\ No newline at end of file
+}
+
+/// Parses a `YYYY-MM-DD` date into the start of that day.
+pub fn parse_date(date: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(start_of_day(naive))
+}
+
+/// The first moment of a local day. That is midnight, unless clocks went forward at midnight,
+/// when it is the first minute after they did.
+pub fn start_of_day(date: NaiveDate) -> DateTime<Local> {
+    let mut naive = date.and_time(NaiveTime::MIN);
+    loop {
+        if let Some(time) = from_local(&naive) {
+            return time;
+        }
+        naive += Duration::minutes(1);
+    }
+}
+
+/// Parses a `YYYY-MM` month into a half-open range from the start of the first of that month to
+/// the start of the first of the next.
+pub fn parse_month(month: &str) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+    let next = first.checked_add_months(Months::new(1))?;
+
+    Some((
+        parse_date(&first.format("%Y-%m-%d").to_string())?,
+        parse_date(&next.format("%Y-%m-%d").to_string())?,
+    ))
+}
+
+/// Parses a local `YYYY-MM-DD HH:MM` date and time.
+pub fn parse_datetime(datetime: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M").ok()?;
+    from_local(&naive)
+}
+
+/// The first moment a local date and time happens. Times repeated when clocks go back are the
+/// earlier of the two, and times skipped when clocks go forward do not exist.
+pub fn from_local(naive: &NaiveDateTime) -> Option<DateTime<Local>> {
+    match Local.from_local_datetime(naive) {
+        LocalResult::Single(time) => Some(time),
+        // the two times are not always in order
+        LocalResult::Ambiguous(a, b) => Some(a.min(b)),
+        LocalResult::None => None,
+    }
+}
+
+/// Parses a duration such as `6h`, `1.5h` or `90m` into whole seconds. Durations must be positive.
+pub fn parse_duration(duration: &str) -> Option<u32> {
+    let duration = duration.trim();
+    let (number, unit_seconds) = if let Some(hours) = duration.strip_suffix('h') {
+        (hours, 3600.0)
+    } else if let Some(minutes) = duration.strip_suffix('m') {
+        (minutes, 60.0)
+    } else {
+        return None;
+    };
+
+    match number.trim().parse::<f64>() {
+        Ok(n) if n > 0.0 && n * unit_seconds <= u32::MAX as f64 => {
+            Some((n * unit_seconds).round() as u32)
+        }
+        _ => None,
+    }
+}
+
+/// Serialises a duration as a whole number of milliseconds, for `#[serde(with = "time::millis")]`
+pub mod millis {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(duration.num_milliseconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::milliseconds(i64::deserialize(deserializer)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Duration};
+
+    use crate::clock::{in_zone, CENTRAL_EUROPE, SKIPPED_MIDNIGHT};
+
+    use super::*;
+
+    #[test]
+    fn parse_date_returns_start_of_day() {
+        // Act
+        let date = parse_date("2026-09-14").unwrap();
+
+        // Assert
+        assert_eq!((2026, 9, 14), (date.year(), date.month(), date.day()));
+        assert_eq!("00:00:00", date.format("%H:%M:%S").to_string());
+    }
+
+    #[test]
+    fn parse_date_skipped_midnight_returns_first_minute_after() {
+        if in_zone(
+            SKIPPED_MIDNIGHT,
+            concat!(
+                module_path!(),
+                "::parse_date_skipped_midnight_returns_first_minute_after"
+            ),
+        ) {
+            return;
+        }
+
+        // Act
+        let date = parse_date("2025-09-01").unwrap();
+
+        // Assert
+        assert_eq!(
+            "2025-09-01 01:00 -03:00",
+            date.format("%Y-%m-%d %H:%M %:z").to_string()
+        );
+    }
+
+    #[test]
+    fn parse_date_invalid_returns_none() {
+        assert!(parse_date("14/09/2026").is_none());
+    }
+
+    #[test]
+    fn parse_month_spans_whole_month() {
+        // Act
+        let (start, end) = parse_month("2026-12").unwrap();
+
+        // Assert
+        assert_eq!(parse_date("2026-12-01").unwrap(), start);
+        assert_eq!(parse_date("2027-01-01").unwrap(), end);
+    }
+
+    #[test]
+    fn parse_datetime_reads_minutes() {
+        // Act
+        let res = parse_datetime("2026-09-14 09:30").unwrap();
+
+        // Assert
+        assert_eq!("2026-09-14 09:30", res.format("%Y-%m-%d %H:%M").to_string());
+    }
+
+    #[test]
+    fn parse_duration_reads_hours_and_minutes() {
+        assert_eq!(Some(5400), parse_duration("1.5h"));
+        assert_eq!(Some(5400), parse_duration("90m"));
+        assert_eq!(None, parse_duration("90"));
+        assert_eq!(None, parse_duration("0h"));
+    }
+
+    #[test]
+    fn parse_datetime_repeated_when_clocks_go_back_returns_earlier_time() {
+        if in_zone(
+            CENTRAL_EUROPE,
+            concat!(
+                module_path!(),
+                "::parse_datetime_repeated_when_clocks_go_back_returns_earlier_time"
+            ),
+        ) {
+            return;
+        }
+
+        // Act
+        let res = parse_datetime("2026-10-25 02:30").unwrap();
+
+        // Assert
+        assert_eq!("02:30 +02:00", res.format("%H:%M %:z").to_string());
+        assert_eq!(
+            "02:30 +01:00",
+            (res + Duration::hours(1)).format("%H:%M %:z").to_string()
+        );
+    }
+
+    #[test]
+    fn parse_datetime_skipped_when_clocks_go_forward_returns_none() {
+        if in_zone(
+            CENTRAL_EUROPE,
+            concat!(
+                module_path!(),
+                "::parse_datetime_skipped_when_clocks_go_forward_returns_none"
+            ),
+        ) {
+            return;
+        }
+
+        // Act
+        let res = parse_datetime("2026-03-29 02:30");
+
+        // Assert
+        assert_eq!(None, res);
+    }
+}
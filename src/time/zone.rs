@@ -0,0 +1,174 @@
+use std::{env, fmt, str::FromStr};
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, Offset};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The time zone a log was recorded in, so its times can be shown as they were on the clock on
+/// the wall where the work was done.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordedZone {
+    /// A zone from the IANA database, e.g. `Europe/Berlin`, which knows when its clocks change
+    Named(Tz),
+    /// An offset from UTC, e.g. `+02:00`, for systems whose zone has no IANA name
+    Offset(FixedOffset),
+}
+
+impl RecordedZone {
+    /// The zone the system's clock is in. Named when `TZ`, or the system's zone if `TZ` is not
+    /// set, is in the IANA database. Otherwise the offset from UTC at the given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Time to take the offset at, when the zone has no name
+    pub fn current(now: DateTime<Local>) -> Self {
+        let name = match env::var("TZ") {
+            Ok(tz) => Some(tz.trim_start_matches(':').to_owned()),
+            Err(_) => iana_time_zone::get_timezone().ok(),
+        };
+
+        match name.and_then(|name| name.parse().ok()) {
+            Some(tz) => RecordedZone::Named(tz),
+            None => RecordedZone::Offset(now.offset().fix()),
+        }
+    }
+
+    /// Offset from UTC at the given time, which for named zones depends on daylight saving
+    pub fn offset_at(&self, time: &DateTime<Local>) -> FixedOffset {
+        match self {
+            RecordedZone::Named(tz) => time.with_timezone(tz).offset().fix(),
+            RecordedZone::Offset(offset) => *offset,
+        }
+    }
+
+    /// The time as it was on the clock in this zone
+    pub fn wall_time(&self, time: &DateTime<Local>) -> DateTime<FixedOffset> {
+        time.with_timezone(&self.offset_at(time))
+    }
+}
+
+impl fmt::Display for RecordedZone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordedZone::Named(tz) => write!(f, "{}", tz.name()),
+            RecordedZone::Offset(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+impl FromStr for RecordedZone {
+    type Err = String;
+
+    /// Parses an IANA name such as `Europe/Berlin` or an offset such as `+02:00`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(RecordedZone::Named)
+            .or_else(|_| s.parse().map(RecordedZone::Offset))
+            .map_err(|_| format!("unknown time zone \"{}\"", s))
+    }
+}
+
+impl Serialize for RecordedZone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RecordedZone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Which zone the times of logs are shown in, and the days of reports run midnight to
+/// midnight in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum DisplayZone {
+    /// The zone the system is in now, whichever zone the logs were recorded in
+    #[default]
+    Current,
+    /// The zone each log was recorded in. Logs recorded before zones were kept are shown in the
+    /// current zone
+    Recorded,
+}
+
+impl DisplayZone {
+    /// The time as shown in this zone, e.g. `2026-09-30 17:00:00 +02:00`
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - Start or end of a log
+    /// * `recorded` - Zone the log was recorded in, if known
+    pub fn show(&self, time: &DateTime<Local>, recorded: Option<RecordedZone>) -> String {
+        match (self, recorded) {
+            (DisplayZone::Recorded, Some(zone)) => zone.wall_time(time).to_string(),
+            _ => time.to_string(),
+        }
+    }
+
+    /// The day a time falls on in this zone. Reports count a log towards the day it started on
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - Start of a log
+    /// * `recorded` - Zone the log was recorded in, if known
+    pub fn day(&self, time: &DateTime<Local>, recorded: Option<RecordedZone>) -> NaiveDate {
+        match (self, recorded) {
+            (DisplayZone::Recorded, Some(zone)) => zone.wall_time(time).date_naive(),
+            _ => time.date_naive(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::time::to_datetime;
+
+    use super::*;
+
+    #[test]
+    fn parse_accepts_names_and_offsets() {
+        // Act
+        let named: RecordedZone = "Asia/Tokyo".parse().unwrap();
+        let offset: RecordedZone = "-03:30".parse().unwrap();
+
+        // Assert
+        assert_eq!("Asia/Tokyo", named.to_string());
+        assert_eq!("-03:30", offset.to_string());
+        assert!("Mars/Olympus".parse::<RecordedZone>().is_err());
+    }
+
+    #[test]
+    fn named_zone_follows_daylight_saving() {
+        // Arrange
+        let zone: RecordedZone = "Europe/Berlin".parse().unwrap();
+
+        // Act
+        // 2026-01-15 12:00 and 2026-07-15 12:00 UTC
+        let winter = zone.wall_time(&to_datetime(1_768_478_400));
+        let summer = zone.wall_time(&to_datetime(1_784_116_800));
+
+        // Assert
+        assert_eq!("2026-01-15 13:00:00 +01:00", winter.to_string());
+        assert_eq!("2026-07-15 14:00:00 +02:00", summer.to_string());
+    }
+
+    #[test]
+    fn day_in_recorded_zone_is_the_day_on_the_wall() {
+        // Arrange
+        let tokyo: RecordedZone = "Asia/Tokyo".parse().unwrap();
+        // 2026-10-19 20:00 UTC, 05:00 the next morning in Tokyo
+        let start = to_datetime(1_792_440_000);
+
+        // Act
+        let recorded = DisplayZone::Recorded.day(&start, Some(tokyo));
+        let unknown = DisplayZone::Recorded.day(&start, None);
+
+        // Assert
+        assert_eq!(NaiveDate::from_ymd_opt(2026, 10, 20).unwrap(), recorded);
+        assert_eq!(start.date_naive(), unknown);
+    }
+}